@@ -0,0 +1,323 @@
+use deltav_calc::{DeltavFormat, DeltavMap, MapLayout, Route};
+use gtk::cairo::{self, Context, FontSlant, FontWeight};
+use gtk::prelude::*;
+use gtk::{
+    Box, Button, CheckButton, DrawingArea, EventControllerScroll, EventControllerScrollFlags,
+    GestureDrag, Inhibit, Orientation,
+};
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+
+// How much one step of the scroll wheel zooms
+const ZOOM_STEP: f64 = 1.2;
+const NODE_RADIUS: f64 = 6.0;
+// The distance between the nodes and the width of the drawing when only the route is drawn
+const ROUTE_STEP: f64 = 56.0;
+const ROUTE_WIDTH: f64 = 360.0;
+const MARGIN: f64 = 30.0;
+
+// What the graph shows and how it is zoomed and moved
+struct GraphState {
+    layout: Option<MapLayout>,
+    // The edges of the map with their formatted cost
+    edges: Vec<(String, String, String)>,
+    // The legs of the route with their formatted deltav
+    route: Vec<(String, String, String)>,
+    whole_map: bool,
+    // The zoom on top of the one fitting the drawing into the area
+    zoom: f64,
+    offset: (f64, f64),
+    // The offset when the current drag started
+    drag_start: (f64, f64),
+}
+
+impl GraphState {
+    // Checks if the route goes along the edge in either direction
+    fn on_route(&self, from: &str, to: &str) -> bool {
+        self.route
+            .iter()
+            .any(|(a, b, _)| (a == from && b == to) || (a == to && b == from))
+    }
+
+    // The size of the drawing before it is zoomed
+    fn size(&self) -> (f64, f64) {
+        if self.whole_map {
+            self.layout
+                .as_ref()
+                .map_or((0.0, 0.0), |layout| (layout.width(), layout.height()))
+        } else {
+            let height = 2.0 * MARGIN + self.route.len() as f64 * ROUTE_STEP;
+            (ROUTE_WIDTH, height)
+        }
+    }
+}
+
+// A drawing of the calculated route, either alone or highlighted on the whole map. It is zoomed
+// with the scroll wheel and moved by dragging
+pub struct RouteGraph {
+    pub layout: Box,
+    area: DrawingArea,
+    state: Arc<Mutex<GraphState>>,
+}
+
+impl RouteGraph {
+    pub fn new() -> RouteGraph {
+        let state = Arc::new(Mutex::new(GraphState {
+            layout: None,
+            edges: Vec::new(),
+            route: Vec::new(),
+            whole_map: false,
+            zoom: 1.0,
+            offset: (0.0, 0.0),
+            drag_start: (0.0, 0.0),
+        }));
+
+        let area = DrawingArea::builder()
+            .content_height(300)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        let state_clone = state.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            // A failed drawing only leaves the area blank until the next one
+            let _ = draw(
+                &state_clone.lock().unwrap(),
+                cr,
+                width as f64,
+                height as f64,
+            );
+        });
+
+        let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        let state_clone = state.clone();
+        let area_clone = area.clone();
+        scroll.connect_scroll(move |_, _, dy| {
+            let factor = if dy < 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+
+            // Zooms around the center of the area
+            let mut state = state_clone.lock().unwrap();
+            let center = (
+                area_clone.width() as f64 / 2.0,
+                area_clone.height() as f64 / 2.0,
+            );
+            state.zoom *= factor;
+            state.offset = (
+                center.0 - (center.0 - state.offset.0) * factor,
+                center.1 - (center.1 - state.offset.1) * factor,
+            );
+            drop(state);
+
+            area_clone.queue_draw();
+            Inhibit(true)
+        });
+        area.add_controller(&scroll);
+
+        let drag = GestureDrag::new();
+        let state_clone = state.clone();
+        drag.connect_drag_begin(move |_, _, _| {
+            let mut state = state_clone.lock().unwrap();
+            state.drag_start = state.offset;
+        });
+        let state_clone = state.clone();
+        let area_clone = area.clone();
+        drag.connect_drag_update(move |_, dx, dy| {
+            let mut state = state_clone.lock().unwrap();
+            state.offset = (state.drag_start.0 + dx, state.drag_start.1 + dy);
+            drop(state);
+            area_clone.queue_draw();
+        });
+        area.add_controller(&drag);
+
+        let whole_map = CheckButton::builder().label("Whole map").build();
+        let state_clone = state.clone();
+        let area_clone = area.clone();
+        whole_map.connect_toggled(move |whole_map| {
+            let mut state = state_clone.lock().unwrap();
+            state.whole_map = whole_map.is_active();
+            state.zoom = 1.0;
+            state.offset = (0.0, 0.0);
+            drop(state);
+            area_clone.queue_draw();
+        });
+
+        let reset = Button::builder().label("Reset zoom").build();
+        let state_clone = state.clone();
+        let area_clone = area.clone();
+        reset.connect_clicked(move |_| {
+            let mut state = state_clone.lock().unwrap();
+            state.zoom = 1.0;
+            state.offset = (0.0, 0.0);
+            drop(state);
+            area_clone.queue_draw();
+        });
+
+        let controls = Box::builder().orientation(Orientation::Horizontal).build();
+        controls.append(&whole_map);
+        controls.append(&reset);
+
+        let layout = Box::builder().orientation(Orientation::Vertical).build();
+        layout.append(&controls);
+        layout.append(&area);
+
+        RouteGraph {
+            layout,
+            area,
+            state,
+        }
+    }
+
+    // Draws the route on the map, or only the map if there is no route
+    pub fn show(&self, map: &DeltavMap, route: Option<&Route>, format: &DeltavFormat) {
+        let mut state = self.state.lock().unwrap();
+        state.layout = Some(map.layout());
+        state.edges = map
+            .edges()
+            .map(|(from, to, edge)| {
+                let cost = format.format(edge.cost() as i64);
+                (from.to_string(), to.to_string(), cost)
+            })
+            .collect();
+        state.route = route.map_or_else(Vec::new, |route| {
+            route
+                .legs()
+                .iter()
+                .map(|leg| {
+                    let delta_v = format.format(leg.delta_v() as i64);
+                    (leg.from().to_string(), leg.to().to_string(), delta_v)
+                })
+                .collect()
+        });
+        drop(state);
+
+        self.area.queue_draw();
+    }
+}
+
+// Draws the graph into an area of the size
+fn draw(state: &GraphState, cr: &Context, width: f64, height: f64) -> Result<(), cairo::Error> {
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.paint()?;
+
+    // Starts with the whole drawing fitting into the area
+    let (drawing_width, drawing_height) = state.size();
+    if drawing_width <= 0.0 || drawing_height <= 0.0 {
+        return Ok(());
+    }
+    let fit = (width / drawing_width).min(height / drawing_height);
+    cr.translate(state.offset.0, state.offset.1);
+    cr.scale(fit * state.zoom, fit * state.zoom);
+    cr.set_font_size(11.0);
+
+    if state.whole_map {
+        draw_map(state, cr)
+    } else {
+        draw_route(state, cr)
+    }
+}
+
+// Draws all nodes and edges of the map with the route highlighted
+fn draw_map(state: &GraphState, cr: &Context) -> Result<(), cairo::Error> {
+    let Some(layout) = &state.layout else {
+        return Ok(());
+    };
+
+    // The edges of the route are drawn last to be on top
+    for highlighted in [false, true] {
+        for (from, to, cost) in &state.edges {
+            if state.on_route(from, to) != highlighted {
+                continue;
+            }
+            let (Some((x1, y1)), Some((x2, y2))) = (layout.position(from), layout.position(to))
+            else {
+                continue;
+            };
+
+            set_color(cr, highlighted);
+            cr.set_line_width(if highlighted { 3.0 } else { 1.0 });
+            cr.move_to(x1, y1);
+            cr.line_to(x2, y2);
+            cr.stroke()?;
+            show_centered(cr, cost, (x1 + x2) / 2.0, (y1 + y2) / 2.0 - 3.0)?;
+        }
+    }
+
+    cr.select_font_face("sans-serif", FontSlant::Normal, FontWeight::Bold);
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    for (category, (x, y)) in layout.categories() {
+        cr.move_to(x, y);
+        cr.show_text(category)?;
+    }
+
+    cr.select_font_face("sans-serif", FontSlant::Normal, FontWeight::Normal);
+    for (node, (x, y)) in layout.nodes() {
+        let highlighted = state
+            .route
+            .iter()
+            .any(|(from, to, _)| from == node || to == node);
+        draw_node(cr, x, y, highlighted)?;
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        show_centered(cr, node, x, y + 20.0)?;
+    }
+
+    Ok(())
+}
+
+// Draws only the nodes of the route from top to bottom with the deltav of every leg
+fn draw_route(state: &GraphState, cr: &Context) -> Result<(), cairo::Error> {
+    let x = MARGIN + NODE_RADIUS;
+    let y = |i: usize| MARGIN + NODE_RADIUS + i as f64 * ROUTE_STEP;
+
+    for (i, (_, _, delta_v)) in state.route.iter().enumerate() {
+        set_color(cr, true);
+        cr.set_line_width(3.0);
+        cr.move_to(x, y(i));
+        cr.line_to(x, y(i + 1));
+        cr.stroke()?;
+
+        cr.set_source_rgb(0.33, 0.33, 0.33);
+        cr.move_to(x + 3.0 * NODE_RADIUS, (y(i) + y(i + 1)) / 2.0 + 4.0);
+        cr.show_text(delta_v)?;
+    }
+
+    let nodes = state
+        .route
+        .first()
+        .map(|(from, _, _)| from)
+        .into_iter()
+        .chain(state.route.iter().map(|(_, to, _)| to));
+    cr.select_font_face("sans-serif", FontSlant::Normal, FontWeight::Bold);
+    for (i, node) in nodes.enumerate() {
+        draw_node(cr, x, y(i), true)?;
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        cr.move_to(x + 3.0 * NODE_RADIUS, y(i) + 5.0);
+        cr.show_text(node)?;
+    }
+
+    Ok(())
+}
+
+// Uses red for the route and gray for the rest of the map
+fn set_color(cr: &Context, highlighted: bool) {
+    if highlighted {
+        cr.set_source_rgb(0.8, 0.13, 0.13);
+    } else {
+        cr.set_source_rgb(0.6, 0.6, 0.6);
+    }
+}
+
+fn draw_node(cr: &Context, x: f64, y: f64, highlighted: bool) -> Result<(), cairo::Error> {
+    if highlighted {
+        cr.set_source_rgb(0.8, 0.13, 0.13);
+    } else {
+        cr.set_source_rgb(0.2, 0.4, 0.8);
+    }
+    cr.arc(x, y, NODE_RADIUS, 0.0, 2.0 * PI);
+    cr.fill()
+}
+
+// Writes the text centered around the x coordinate
+fn show_centered(cr: &Context, text: &str, x: f64, y: f64) -> Result<(), cairo::Error> {
+    let extents = cr.text_extents(text)?;
+    cr.move_to(x - extents.width() / 2.0, y);
+    cr.show_text(text)
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+// How many recently calculated routes are kept
+const MAX_RECENT: usize = 10;
+
+// A route by the names of its start and end
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Debug)]
+pub struct SavedRoute {
+    pub from: String,
+    pub to: String,
+}
+
+impl SavedRoute {
+    // The text the route is listed with
+    pub fn label(&self) -> String {
+        format!("{} → {}", self.from, self.to)
+    }
+}
+
+// The recently calculated and the pinned routes, kept in the config directory between runs
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct History {
+    pub recent: Vec<SavedRoute>,
+    pub favorites: Vec<SavedRoute>,
+}
+
+impl History {
+    // Reads the history, starting with an empty one if there is none yet or it can't be read
+    pub fn load() -> History {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    // Writes the history. Losing it only loses a convenience, so errors are ignored
+    pub fn save(&self) {
+        let Some(path) = path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(text) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(path, text);
+        }
+    }
+
+    // Puts the route at the top of the recent routes
+    pub fn add_recent(&mut self, route: SavedRoute) {
+        self.recent.retain(|recent| *recent != route);
+        self.recent.insert(0, route);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    pub fn is_favorite(&self, route: &SavedRoute) -> bool {
+        self.favorites.contains(route)
+    }
+
+    // Pins the route, or unpins it if it already is a favorite
+    pub fn toggle_favorite(&mut self, route: SavedRoute) {
+        if self.is_favorite(&route) {
+            self.favorites.retain(|favorite| *favorite != route);
+        } else {
+            self.favorites.push(route);
+        }
+    }
+}
+
+// The file the history is stored in, like ~/.config/deltav_calc/history.json on Linux
+fn path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("deltav_calc").join("history.json"))
+}
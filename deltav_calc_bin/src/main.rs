@@ -1,13 +1,36 @@
-use deltav_calc::{DeltavMap, MenuTree};
+mod graph;
+mod history;
+mod node_popover;
+
+use crate::graph::RouteGraph;
+use crate::history::{History, SavedRoute};
+use crate::node_popover::NodePopover;
+use deltav_calc::discovery::{self, InstalledMap};
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap, ValidationOptions};
+use gtk::gio::{Menu, SimpleAction};
 use gtk::prelude::*;
 use gtk::{
-    Application, ApplicationWindow, Box, Button, Expander, Inhibit, Label, Orientation,
-    ScrolledWindow, Widget, Window,
+    Application, ApplicationWindow, Box, Button, ButtonsType, CheckButton, ComboBoxText, Expander,
+    FileChooserAction, FileChooserDialog, FileFilter, Label, MessageDialog, MessageType,
+    Orientation, ResponseType, SpinButton, ToggleButton,
 };
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 const APP_ID: &str = "vck.zll.deltav_calc";
 
+// The labels of the buttons before a node is selected
+const START_LABEL: &str = "Click here to select the start";
+const END_LABEL: &str = "Click here to select the end";
+
+// The maps that come with the calculator, which are listed before the installed ones
+const BUILT_IN_MAPS: [(&str, fn() -> DeltavMap); 2] = [
+    ("Stock", DeltavMap::new_stock),
+    ("Real Solar System", DeltavMap::new_rss),
+];
+
 fn main() {
     let app = Application::builder().application_id(APP_ID).build();
 
@@ -16,35 +39,27 @@ fn main() {
     app.run();
 }
 
-// Defines if the origin or the target should be selected
+// Defines if the origin, the target or a node to avoid should be selected
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 enum Selection {
     ORIGIN,
     TARGET,
+    AVOID,
 }
 
 // Builds the ui
 fn build_ui(app: &Application) {
-    // The deltav map to use
-    let map = Arc::new(DeltavMap::new_stock());
+    // The deltav map to use, which is replaced when a map file is opened
+    let map = Rc::new(RefCell::new(DeltavMap::stock().clone()));
 
     // Defines if the origin or the target should be selected
     let sel = Arc::new(Mutex::new(Selection::ORIGIN));
 
-    // The window for the node selection
-    let select_window = Arc::new(
-        Window::builder()
-            .title("Select a node")
-            .width_request(700)
-            .height_request(300)
-            .resizable(false)
-            .maximized(false)
-            .build(),
-    );
-
     // THe button to click when you want to set te start node
     let origin_button = Arc::new(
         Button::builder()
-            .label("Click here to select the start")
+            .label(START_LABEL)
             .width_request(300)
             .build(),
     );
@@ -52,86 +67,174 @@ fn build_ui(app: &Application) {
     // THe button to click when you want to set te end node
     let target_button = Arc::new(
         Button::builder()
-            .label("Click here to select the end")
+            .label(END_LABEL)
             .width_request(300)
             .build(),
     );
-    // When clicked open the selection window
+
+    let result = Rc::new(ResultArea::new());
+    let lists = Rc::new(RouteLists::new());
+
+    // The popover to pick the nodes from, which updates the start, the end or the avoided nodes
     let sel_clone = sel.clone();
-    let select_window_clone = select_window.clone();
     let origin_button_clone = origin_button.clone();
     let target_button_clone = target_button.clone();
-    target_button.connect_clicked(move |_| {
-        let mut sel = sel_clone.lock().unwrap();
-        *sel = Selection::TARGET;
-        drop(sel);
-        show_selection(
-            &select_window_clone,
+    let result_clone = result.clone();
+    let lists_clone = lists.clone();
+    let map_clone = map.clone();
+    let node_popover = NodePopover::new(map.borrow().menu_tree(), move |name| {
+        selected(
+            name,
+            &sel_clone,
             &origin_button_clone,
             &target_button_clone,
+            &result_clone,
+            &lists_clone,
+            &map_clone.borrow(),
         );
     });
-    // When origin button is clicked open the selection window
-    let sel_clone = sel.clone();
-    let select_window_clone = select_window.clone();
+
+    // The start, the end and the avoided nodes are picked below their buttons
+    for (button, selection) in [
+        (&*origin_button, Selection::ORIGIN),
+        (&*target_button, Selection::TARGET),
+        (&result.options.avoid, Selection::AVOID),
+    ] {
+        let sel_clone = sel.clone();
+        let node_popover_clone = node_popover.clone();
+        button.connect_clicked(move |button| {
+            *sel_clone.lock().unwrap() = selection;
+            node_popover_clone.show_at(button);
+        });
+    }
+
+    calculate(
+        &result,
+        &lists,
+        &map.borrow(),
+        origin_button.label().unwrap().as_str(),
+        target_button.label().unwrap().as_str(),
+    );
+
+    // The button to exchange the start and the end
+    let swap_button = Button::builder().label("⇄ Swap").build();
     let origin_button_clone = origin_button.clone();
     let target_button_clone = target_button.clone();
-    origin_button.connect_clicked(move |_| {
-        let mut sel = sel_clone.lock().unwrap();
-        *sel = Selection::ORIGIN;
-        drop(sel);
-        show_selection(
-            &select_window_clone,
+    let result_clone = result.clone();
+    let lists_clone = lists.clone();
+    let map_clone = map.clone();
+    swap_button.connect_clicked(move |_| {
+        swap(
             &origin_button_clone,
             &target_button_clone,
+            &result_clone,
+            &lists_clone,
+            &map_clone.borrow(),
         );
     });
 
-    let result_label = Label::builder().width_request(300).build();
-    set_result(
-        &result_label,
-        &map,
-        origin_button.label().unwrap().as_str(),
-        target_button.label().unwrap().as_str(),
-    );
-
-    // Build the layout everything is put in
-    let layout = Box::builder().orientation(Orientation::Horizontal).build();
-    layout.append(&*origin_button);
-    layout.append(&result_label);
-    layout.append(&*target_button);
+    // Copies the shown route to the clipboard
+    let result_clone = result.clone();
+    result.copy.connect_clicked(move |button| {
+        button.clipboard().set_text(&result_clone.text.borrow());
+    });
 
-    let sel_clone = sel.clone();
-    let map_clone = map.clone();
-    let select_window_clone = select_window.clone();
+    // Shows the route again after the unit or the options changed
     let origin_button_clone = origin_button.clone();
     let target_button_clone = target_button.clone();
-    let selection_tree = ScrolledWindow::builder()
-        .width_request(100)
-        .child(&build_tree(
-            map.menu_tree(),
-            Arc::new(move |button: &Button| {
-                selected(
-                    button.label().unwrap().as_str(),
-                    &sel_clone,
-                    &*origin_button_clone,
-                    &*target_button_clone,
-                    &result_label,
-                    &map_clone,
-                    &select_window_clone,
-                );
-            }),
-        ))
-        .build();
-    select_window.set_child(Some(&selection_tree));
+    let result_clone = result.clone();
+    let map_clone = map.clone();
+    let recalculate = Rc::new(move || {
+        set_result(
+            &result_clone,
+            &map_clone.borrow(),
+            origin_button_clone.label().unwrap().as_str(),
+            target_button_clone.label().unwrap().as_str(),
+        );
+    });
+    let recalculate_clone = recalculate.clone();
+    result
+        .kilometers
+        .connect_toggled(move |_| recalculate_clone());
+    let recalculate_clone = recalculate.clone();
+    result
+        .options
+        .margin
+        .connect_value_changed(move |_| recalculate_clone());
+    let recalculate_clone = recalculate.clone();
+    result
+        .options
+        .aerobrake
+        .connect_toggled(move |_| recalculate_clone());
+
+    // Allows the node picked in the list of avoided nodes again
+    let result_clone = result.clone();
+    result.options.remove.connect_clicked(move |_| {
+        if let Some(node) = result_clone.options.avoided.active_text() {
+            result_clone.options.set_avoided(node.as_str(), false);
+            recalculate();
+        }
+    });
+
+    // Picking a favorite or recent route selects its start and end
+    for combo in [&lists.favorites, &lists.recent] {
+        let origin_button_clone = origin_button.clone();
+        let target_button_clone = target_button.clone();
+        let result_clone = result.clone();
+        let lists_clone = lists.clone();
+        let map_clone = map.clone();
+        combo.connect_changed(move |combo| {
+            let Some(route) = lists_clone.route(combo) else {
+                return;
+            };
+            origin_button_clone.set_label(&route.from);
+            target_button_clone.set_label(&route.to);
+            calculate(
+                &result_clone,
+                &lists_clone,
+                &map_clone.borrow(),
+                &route.from,
+                &route.to,
+            );
+        });
+    }
 
+    // Pins the selected route or unpins it if it is a favorite
     let origin_button_clone = origin_button.clone();
     let target_button_clone = target_button.clone();
-    select_window.connect_close_request(move |window| {
-        close_selection(window, &origin_button_clone, &target_button_clone);
-        Inhibit(true)
+    let lists_clone = lists.clone();
+    lists.pin.connect_clicked(move |_| {
+        let route = SavedRoute {
+            from: origin_button_clone.label().unwrap().to_string(),
+            to: target_button_clone.label().unwrap().to_string(),
+        };
+        lists_clone
+            .history
+            .borrow_mut()
+            .toggle_favorite(route.clone());
+        lists_clone.history.borrow().save();
+        lists_clone.refresh(Some(&route));
     });
 
+    // Build the layout everything is put in
+    let middle = Box::builder().orientation(Orientation::Vertical).build();
+    middle.append(&swap_button);
+    middle.append(&result.layout);
+    let calculator = Box::builder().orientation(Orientation::Horizontal).build();
+    calculator.append(&*origin_button);
+    calculator.append(&middle);
+    calculator.append(&*target_button);
+    // The maps to choose from, the built-in maps first and then the ones the user installed
+    let installed_maps = discovery::installed_maps();
+    let map_selector = build_map_selector(&installed_maps);
+
+    let layout = Box::builder().orientation(Orientation::Vertical).build();
+    layout.append(&map_selector.0);
+    layout.append(&calculator);
+    layout.append(&result.options.layout);
+    layout.append(&result.graph.layout);
+    layout.append(&lists.layout);
+
     // Build the final window
     let window = ApplicationWindow::builder()
         .title("Deltav Calculator")
@@ -140,75 +243,443 @@ fn build_ui(app: &Application) {
         .hide_on_close(false)
         .width_request(900)
         .resizable(false)
+        .show_menubar(true)
         .build();
+
+    // Replaces the map, shows its nodes in the popover and recalculates the route. A selected
+    // node the new map doesn't have is unselected
+    let use_map = Rc::new(move |new_map: DeltavMap| {
+        node_popover.set_tree(new_map.menu_tree());
+
+        for (button, placeholder) in [(&origin_button, START_LABEL), (&target_button, END_LABEL)] {
+            let label = button.label().unwrap();
+            let exists = new_map
+                .menu_tree()
+                .search(label.as_str())
+                .is_ok_and(|node| node.id().is_some());
+            if !exists {
+                button.set_label(placeholder);
+            }
+        }
+        *map.borrow_mut() = new_map;
+
+        calculate(
+            &result,
+            &lists,
+            &map.borrow(),
+            origin_button.label().unwrap().as_str(),
+            target_button.label().unwrap().as_str(),
+        );
+    });
+
+    // Switches to the map chosen in the dropdown
+    let window_clone = window.clone();
+    let use_map_clone = use_map.clone();
+    map_selector.1.connect_changed(move |selector| {
+        let new_map = match selector.active().map(|index| index as usize) {
+            None => Ok(DeltavMap::stock().clone()),
+            Some(index) if index < BUILT_IN_MAPS.len() => Ok(BUILT_IN_MAPS[index].1()),
+            Some(index) => installed_maps[index - BUILT_IN_MAPS.len()]
+                .map()
+                .cloned()
+                .map_err(|e| e.to_string()),
+        };
+        match new_map {
+            Ok(new_map) => use_map_clone(new_map),
+            Err(message) => show_error(&window_clone, &message),
+        }
+    });
+
+    // Uses the map in the file if it is valid
+    let window_clone = window.clone();
+    let open = Rc::new(move |path: &Path| match load_map(path) {
+        Ok(new_map) => use_map(new_map),
+        Err(message) => show_error(&window_clone, &message),
+    });
+
+    // File -> Open lets the user pick a map file
+    let window_clone = window.clone();
+    let open_action = SimpleAction::new("open", None);
+    open_action.connect_activate(move |_, _| {
+        let dialog = FileChooserDialog::new(
+            Some("Open a map"),
+            Some(&window_clone),
+            FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Open", ResponseType::Accept),
+            ],
+        );
+        let filter = FileFilter::new();
+        filter.set_name(Some("Maps (*.json, *.yaml, *.deltav, *.dvmap)"));
+        for pattern in ["*.json", "*.yaml", "*.yml", "*.deltav", "*.dvmap"] {
+            filter.add_pattern(pattern);
+        }
+        dialog.add_filter(&filter);
+        dialog.set_modal(true);
+
+        let open = open.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    open(&path);
+                }
+            }
+            dialog.close();
+        });
+        dialog.show();
+    });
+    window.add_action(&open_action);
+    app.set_accels_for_action("win.open", &["<Primary>o"]);
+
+    let file_menu = Menu::new();
+    file_menu.append(Some("Open…"), Some("win.open"));
+    let menubar = Menu::new();
+    menubar.append_submenu(Some("File"), &file_menu);
+    app.set_menubar(Some(&menubar));
+
     window.show();
 }
 
-// Gets called when a node should be selected
-fn show_selection(
-    select_window: &Arc<Window>,
-    start_button: &Arc<Button>,
-    end_button: &Arc<Button>,
-) {
-    start_button.set_sensitive(false);
-    end_button.set_sensitive(false);
-    select_window.show();
+// Builds the dropdown to switch between the stock map and the installed maps, with the stock map
+// selected
+fn build_map_selector(installed_maps: &[InstalledMap]) -> (Box, ComboBoxText) {
+    let selector = ComboBoxText::new();
+    for (name, _) in BUILT_IN_MAPS {
+        selector.append_text(name);
+    }
+    for installed in installed_maps {
+        match installed.map() {
+            Ok(map) => selector.append_text(map.title().unwrap_or(installed.name())),
+            Err(_) => selector.append_text(&format!("{} (invalid)", installed.name())),
+        }
+    }
+    selector.set_active(Some(0));
+
+    let layout = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    layout.append(&Label::new(Some("Map")));
+    layout.append(&selector);
+
+    (layout, selector)
+}
+
+// Reads a map file and runs every check on it, so all problems can be shown at once
+fn load_map(path: &Path) -> Result<DeltavMap, String> {
+    let unchecked = ValidationOptions::new().skip_all(true);
+    let map = DeltavMap::from_path_with_options(path, &unchecked).map_err(|e| e.to_string())?;
+
+    let errors: Vec<String> = map
+        .validation_errors(&ValidationOptions::new())
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(map)
+}
+
+// Shows why a map couldn't be opened
+fn show_error(window: &ApplicationWindow, message: &str) {
+    let dialog = MessageDialog::builder()
+        .transient_for(window)
+        .modal(true)
+        .message_type(MessageType::Error)
+        .buttons(ButtonsType::Close)
+        .text("The map couldn't be opened")
+        .secondary_text(message)
+        .build();
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.show();
+}
+
+// The area between the buttons with the total and an expandable list of the legs below it
+struct ResultArea {
+    layout: Box,
+    total: Label,
+    legs: Expander,
+    copy: Button,
+    kilometers: ToggleButton,
+    graph: RouteGraph,
+    options: OptionsPanel,
+    // The route as text for the clipboard
+    text: RefCell<String>,
+}
+
+impl ResultArea {
+    fn new() -> ResultArea {
+        let total = Label::builder().width_request(300).build();
+        let legs = Expander::builder().label("Legs").visible(false).build();
+        let copy = Button::builder().label("Copy").sensitive(false).build();
+        let kilometers = ToggleButton::builder().label("km/s").build();
+
+        let buttons = Box::builder().orientation(Orientation::Horizontal).build();
+        buttons.append(&copy);
+        buttons.append(&kilometers);
+
+        let layout = Box::builder().orientation(Orientation::Vertical).build();
+        layout.append(&total);
+        layout.append(&legs);
+        layout.append(&buttons);
+
+        ResultArea {
+            layout,
+            total,
+            legs,
+            copy,
+            kilometers,
+            graph: RouteGraph::new(),
+            options: OptionsPanel::new(),
+            text: RefCell::new(String::new()),
+        }
+    }
+
+    // The format of the shown values, always in km/s or always in m/s depending on the toggle
+    fn format(&self) -> DeltavFormat {
+        let threshold = self.kilometers.is_active().then_some(0);
+        DeltavFormat::new().kilometers_from(threshold)
+    }
+}
+
+// The options the routes are calculated with, folded away below the calculator
+struct OptionsPanel {
+    layout: Expander,
+    margin: SpinButton,
+    aerobrake: CheckButton,
+    avoided: ComboBoxText,
+    avoid: Button,
+    remove: Button,
+    // The names of the avoided nodes in the order they were added
+    avoided_nodes: RefCell<Vec<String>>,
+}
+
+impl OptionsPanel {
+    fn new() -> OptionsPanel {
+        let margin = SpinButton::with_range(0.0, 100.0, 1.0);
+        let aerobrake = CheckButton::builder().label("Aerobrake").build();
+        let avoided = ComboBoxText::new();
+        let avoid = Button::builder().label("Avoid a node…").build();
+        let remove = Button::builder().label("Remove").sensitive(false).build();
+
+        let options = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(10)
+            .build();
+        options.append(&Label::new(Some("Margin (%)")));
+        options.append(&margin);
+        options.append(&aerobrake);
+        options.append(&Label::new(Some("Avoided")));
+        options.append(&avoided);
+        options.append(&avoid);
+        options.append(&remove);
+
+        let layout = Expander::builder().label("Options").child(&options).build();
+
+        OptionsPanel {
+            layout,
+            margin,
+            aerobrake,
+            avoided,
+            avoid,
+            remove,
+            avoided_nodes: RefCell::new(Vec::new()),
+        }
+    }
+
+    // The options to calculate the routes with
+    fn calc_options(&self) -> CalcOptions {
+        let mut options = CalcOptions::new()
+            .margin(self.margin.value_as_int() as u32)
+            .aerobrake(self.aerobrake.is_active());
+        for node in self.avoided_nodes.borrow().iter() {
+            options = options.avoid_node(node);
+        }
+        options
+    }
+
+    // Adds the node to the avoided nodes or removes it from them and updates the list
+    fn set_avoided(&self, node: &str, avoided: bool) {
+        let mut avoided_nodes = self.avoided_nodes.borrow_mut();
+        avoided_nodes.retain(|avoided_node| avoided_node != node);
+        if avoided {
+            avoided_nodes.push(node.to_string());
+        }
+
+        self.avoided.remove_all();
+        for avoided_node in avoided_nodes.iter() {
+            self.avoided.append_text(avoided_node);
+        }
+        self.avoided
+            .set_active((!avoided_nodes.is_empty()).then_some(0));
+        self.remove.set_sensitive(!avoided_nodes.is_empty());
+    }
 }
 
-// Uses the map to calculate the delta v needed to get from start to end and puts it into the result label
-fn set_result(result_label: &Label, map: &DeltavMap, start: &str, end: &str) {
-    match map.calculate_delta_v(start, end) {
+// Uses the map to calculate the route from start to end and puts its total into the result label
+// and every leg with its cost into the list below it. Returns whether there is a route
+fn set_result(result: &ResultArea, map: &DeltavMap, start: &str, end: &str) -> bool {
+    result.legs.set_visible(false);
+    result.copy.set_sensitive(false);
+    result.text.borrow_mut().clear();
+    result.graph.show(map, None, &result.format());
+
+    let options = result.options.calc_options();
+    let route = map.calculate_route(start, end, &options);
+    let found = matches!(route, Ok(Some(_)));
+
+    match route {
         Err(e) => {
             if e.cause_name() == start {
-                result_label.set_label("The start node hasn't been selected yet");
+                result
+                    .total
+                    .set_label("The start node hasn't been selected yet");
             } else {
-                result_label.set_label("The end node hasn't been selected yet");
+                result
+                    .total
+                    .set_label("The end node hasn't been selected yet");
             }
         }
 
-        Ok(result) => match result {
-            None => result_label.set_label("There seems to be no connection between the nodes"),
+        Ok(route) => match route {
+            None => result
+                .total
+                .set_label("There seems to be no connection between the nodes"),
 
-            Some(result) => {
-                result_label.set_label(&result.to_string());
+            Some(route) => {
+                let format = result.format();
+                let mut total = format.format(route.delta_v());
+                if options.margin_percent() > 0 {
+                    total.push_str(&format!(
+                        " ({} with a {}% margin)",
+                        format.format(options.apply_margin(route.delta_v())),
+                        options.margin_percent()
+                    ));
+                }
+                result.total.set_label(&total);
+
+                let mut text = format!("{} -> {}: {}", start, end, total);
+                let legs = Box::builder().orientation(Orientation::Vertical).build();
+                for leg in route.legs() {
+                    let mut line = format!(
+                        "{} -> {}: {}",
+                        leg.from(),
+                        leg.to(),
+                        format.format(leg.delta_v() as i64)
+                    );
+                    if leg.aerobrake() {
+                        line.push_str(" (aerobrake)");
+                    }
+                    let label = Label::builder().label(&line).xalign(0.0).build();
+                    legs.append(&label);
+                    text.push_str(&format!("\n  {line}"));
+                }
+                *result.text.borrow_mut() = text;
+                result.graph.show(map, Some(&route), &format);
+                result.copy.set_sensitive(true);
+                result.legs.set_child(Some(&legs));
+                result.legs.set_visible(!route.legs().is_empty());
             }
         },
     }
+
+    found
 }
 
-// Builds the node selection tree
-fn build_tree(tree: &MenuTree, click_callback: Arc<impl Fn(&Button) + 'static>) -> Widget {
-    return match tree {
-        MenuTree::MiddleNode { name, children } => {
-            let layout = Box::builder()
-                .orientation(Orientation::Vertical)
-                .width_request(100)
-                .margin_start(10)
-                .build();
+// Calculates the route and remembers it as a recent route if there is one
+fn calculate(result: &ResultArea, lists: &RouteLists, map: &DeltavMap, start: &str, end: &str) {
+    if set_result(result, map, start, end) {
+        lists.record(SavedRoute {
+            from: start.to_string(),
+            to: end.to_string(),
+        });
+    } else {
+        lists.refresh(None);
+    }
+}
 
-            let expander = Expander::builder()
-                .label(&name)
-                .width_request(50)
-                .child(&layout)
-                .build();
+// The favorite and recent routes below the calculator and the button to pin the current route
+struct RouteLists {
+    layout: Box,
+    favorites: ComboBoxText,
+    recent: ComboBoxText,
+    pin: Button,
+    history: RefCell<History>,
+}
 
-            for child in children {
-                let cloned_callback = click_callback.clone();
-                layout.append(&build_tree(child, cloned_callback));
-            }
+impl RouteLists {
+    fn new() -> RouteLists {
+        let favorites = ComboBoxText::new();
+        let recent = ComboBoxText::new();
+        let pin = Button::new();
 
-            Widget::from(expander)
+        let layout = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(10)
+            .build();
+        layout.append(&Label::new(Some("Favorites")));
+        layout.append(&favorites);
+        layout.append(&Label::new(Some("Recent")));
+        layout.append(&recent);
+        layout.append(&pin);
+
+        let lists = RouteLists {
+            layout,
+            favorites,
+            recent,
+            pin,
+            history: RefCell::new(History::load()),
+        };
+        lists.refresh(None);
+        lists
+    }
+
+    // The route picked in one of the lists
+    fn route(&self, combo: &ComboBoxText) -> Option<SavedRoute> {
+        let index = combo.active()? as usize;
+        let history = self.history.borrow();
+        let routes = if combo == &self.favorites {
+            &history.favorites
+        } else {
+            &history.recent
+        };
+        routes.get(index).cloned()
+    }
+
+    // Adds the route to the recent routes and saves them
+    fn record(&self, route: SavedRoute) {
+        {
+            let mut history = self.history.borrow_mut();
+            history.add_recent(route.clone());
+            history.save();
         }
+        self.refresh(Some(&route));
+    }
 
-        MenuTree::EndNode { name, .. } => {
-            let button = Button::builder().label(&name).width_request(100).build();
-            button.connect_clicked(move |button| {
-                click_callback(button);
-            });
+    // Fills the lists from the history and lets the current route be pinned, if there is one
+    fn refresh(&self, current: Option<&SavedRoute>) {
+        let history = self.history.borrow();
+        let lists = [
+            (&self.favorites, history.favorites.clone()),
+            (&self.recent, history.recent.clone()),
+        ];
+        let pinned = current.is_some_and(|route| history.is_favorite(route));
+        // Clearing the lists emits their changed signal, which reads the history
+        drop(history);
 
-            Widget::from(button)
+        for (combo, routes) in lists {
+            combo.remove_all();
+            for route in routes {
+                combo.append_text(&route.label());
+            }
         }
-    };
+
+        self.pin.set_sensitive(current.is_some());
+        self.pin.set_label(if pinned { "★ Unpin" } else { "☆ Pin" });
+    }
 }
 
 // Updates the selected button and the result label
@@ -217,9 +688,9 @@ fn selected(
     to_change: &Arc<Mutex<Selection>>,
     start: &Button,
     end: &Button,
-    result: &Label,
+    result: &ResultArea,
+    lists: &RouteLists,
     map: &DeltavMap,
-    select_window: &Arc<Window>,
 ) {
     let to_change = to_change.lock().unwrap();
     match *to_change {
@@ -229,19 +700,35 @@ fn selected(
         Selection::TARGET => {
             end.set_label(selection);
         }
+        Selection::AVOID => {
+            result.options.set_avoided(selection, true);
+        }
     }
-    set_result(
+    calculate(
         result,
+        lists,
         map,
         start.label().unwrap().as_str(),
         end.label().unwrap().as_str(),
     );
-    close_selection(&*select_window, start, end);
 }
 
-// Closes the selection window and activates the buttons
-fn close_selection(select_window: &Window, start_button: &Button, end_button: &Button) {
-    select_window.hide();
-    start_button.set_sensitive(true);
-    end_button.set_sensitive(true);
+// Exchanges the start and the end and recalculates the result. A start or end that wasn't
+// selected yet stays unselected
+fn swap(start: &Button, end: &Button, result: &ResultArea, lists: &RouteLists, map: &DeltavMap) {
+    let start_label = start.label().unwrap();
+    let end_label = end.label().unwrap();
+
+    let new_start = match end_label.as_str() {
+        END_LABEL => START_LABEL,
+        node => node,
+    };
+    let new_end = match start_label.as_str() {
+        START_LABEL => END_LABEL,
+        node => node,
+    };
+
+    start.set_label(new_start);
+    end.set_label(new_end);
+    calculate(result, lists, map, new_start, new_end);
 }
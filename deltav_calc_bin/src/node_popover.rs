@@ -0,0 +1,211 @@
+use deltav_calc::MenuTree;
+use gtk::gio::ListModel;
+use gtk::prelude::*;
+use gtk::{
+    Box, Label, ListItem, ListView, NoSelection, Orientation, Popover, ScrolledWindow, SearchEntry,
+    SignalListItemFactory, StringList, StringObject, TreeExpander, TreeListModel, TreeListRow,
+    Widget,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// The nodes of the map the popover shows
+struct PopoverState {
+    tree: MenuTree,
+    // The names of the nodes and categories directly in every category
+    children: HashMap<String, Vec<String>>,
+}
+
+impl PopoverState {
+    fn new(tree: &MenuTree) -> PopoverState {
+        // The categories the flattened tree is currently in, the innermost one last
+        let mut path: Vec<&str> = Vec::new();
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (depth, name, id) in tree.flatten() {
+            path.truncate(depth);
+            if let Some(parent) = path.last() {
+                children
+                    .entry(parent.to_string())
+                    .or_default()
+                    .push(name.to_string());
+            }
+            if id.is_none() {
+                children.entry(name.to_string()).or_default();
+                path.push(name);
+            }
+        }
+
+        PopoverState {
+            tree: tree.clone(),
+            children,
+        }
+    }
+}
+
+// A popover to pick a node of the map from an expandable list of its categories or by searching
+// for it. It is shown below the widget the node is picked for
+pub struct NodePopover {
+    popover: Popover,
+    search: SearchEntry,
+    view: ListView,
+    no_results: Label,
+    state: Rc<RefCell<PopoverState>>,
+}
+
+impl NodePopover {
+    // Creates the popover for the nodes of the tree, calling back with the name of the picked node
+    pub fn new(tree: &MenuTree, on_selected: impl Fn(&str) + 'static) -> Rc<NodePopover> {
+        let factory = SignalListItemFactory::new();
+        factory.connect_setup(|_, item| {
+            let Some(item) = item.downcast_ref::<ListItem>() else {
+                return;
+            };
+            let expander = TreeExpander::new();
+            expander.set_child(Some(&Label::builder().xalign(0.0).build()));
+            item.set_child(Some(&expander));
+        });
+        factory.connect_bind(|_, item| {
+            let Some(item) = item.downcast_ref::<ListItem>() else {
+                return;
+            };
+            let row = item
+                .item()
+                .and_then(|row| row.downcast::<TreeListRow>().ok());
+            let expander = item
+                .child()
+                .and_then(|child| child.downcast::<TreeExpander>().ok());
+            let (Some(row), Some(expander)) = (row, expander) else {
+                return;
+            };
+
+            if let Some(label) = expander
+                .child()
+                .and_then(|child| child.downcast::<Label>().ok())
+            {
+                label.set_label(&row_name(&row));
+            }
+            expander.set_list_row(Some(&row));
+        });
+
+        let view = ListView::new(None::<&NoSelection>, Some(&factory));
+        view.set_single_click_activate(true);
+        let scrolled = ScrolledWindow::builder()
+            .min_content_width(350)
+            .min_content_height(400)
+            .child(&view)
+            .build();
+
+        let search = SearchEntry::new();
+        let no_results = Label::builder()
+            .label("No matching nodes")
+            .visible(false)
+            .build();
+
+        let layout = Box::builder().orientation(Orientation::Vertical).build();
+        layout.append(&search);
+        layout.append(&no_results);
+        layout.append(&scrolled);
+        let popover = Popover::builder().child(&layout).build();
+
+        let node_popover = Rc::new(NodePopover {
+            popover,
+            search,
+            view,
+            no_results,
+            state: Rc::new(RefCell::new(PopoverState::new(tree))),
+        });
+        node_popover.show_tree();
+
+        // Categories are opened and closed, nodes are picked
+        let popover_clone = node_popover.popover.clone();
+        node_popover.view.connect_activate(move |view, position| {
+            let Some(row) = view
+                .model()
+                .and_then(|model| model.item(position))
+                .and_then(|row| row.downcast::<TreeListRow>().ok())
+            else {
+                return;
+            };
+
+            if row.is_expandable() {
+                row.set_expanded(!row.is_expanded());
+            } else {
+                popover_clone.popdown();
+                on_selected(&row_name(&row));
+            }
+        });
+
+        // Replaces the tree with the matching nodes while something is searched
+        let node_popover_clone = node_popover.clone();
+        node_popover.search.connect_search_changed(move |search| {
+            let query = search.text();
+            if query.is_empty() {
+                node_popover_clone.show_tree();
+            } else {
+                let state = node_popover_clone.state.borrow();
+                let results = state.tree.fuzzy_search(query.as_str());
+                let results: Vec<String> = results.iter().map(|name| name.to_string()).collect();
+                drop(state);
+                node_popover_clone.show_names(&results);
+            }
+        });
+
+        node_popover
+    }
+
+    // Shows the nodes of another map
+    pub fn set_tree(&self, tree: &MenuTree) {
+        *self.state.borrow_mut() = PopoverState::new(tree);
+        self.search.set_text("");
+        self.show_tree();
+    }
+
+    // Opens the popover below the widget
+    pub fn show_at(&self, widget: &impl IsA<Widget>) {
+        if self.popover.parent().is_some() {
+            self.popover.unparent();
+        }
+        self.popover.set_parent(widget);
+        self.search.set_text("");
+        self.popover.popup();
+        self.search.grab_focus();
+    }
+
+    // Shows the categories and nodes at the top of the tree
+    fn show_tree(&self) {
+        let state = self.state.borrow();
+        let top = state.children.get(state.tree.name()).cloned();
+        drop(state);
+        self.show_names(&top.unwrap_or_default());
+    }
+
+    // Lists the categories and nodes with the names, letting the categories be expanded
+    fn show_names(&self, names: &[String]) {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let state = self.state.clone();
+        let model = TreeListModel::new(&StringList::new(&names), false, false, move |item| {
+            let name = item.downcast_ref::<StringObject>()?.string();
+            let state = state.borrow();
+            let children: Vec<&str> = state
+                .children
+                .get(name.as_str())?
+                .iter()
+                .map(String::as_str)
+                .collect();
+            Some(StringList::new(&children).upcast::<ListModel>())
+        });
+
+        self.view.set_model(Some(&NoSelection::new(Some(&model))));
+        self.no_results.set_visible(names.is_empty());
+    }
+}
+
+// The name of the node or category in the row
+fn row_name(row: &TreeListRow) -> String {
+    row.item()
+        .and_then(|item| item.downcast::<StringObject>().ok())
+        .map(|item| item.string().to_string())
+        .unwrap_or_default()
+}
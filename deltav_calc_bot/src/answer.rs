@@ -0,0 +1,96 @@
+use crate::command::Question;
+use crate::render::Renderer;
+use crate::resolve::resolve;
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap};
+
+/// The reply to a question, with the route drawn as PNG if there is one
+#[derive(Debug)]
+pub struct Reply {
+    pub text: String,
+    pub image: Option<Vec<u8>>,
+}
+
+impl Reply {
+    // Creates a reply without an image
+    fn text(text: String) -> Reply {
+        Reply { text, image: None }
+    }
+}
+
+/// Answers the question, mentioning the nodes the names were matched to if they weren't exact
+pub fn answer(
+    map: &DeltavMap,
+    question: &Question,
+    format: &DeltavFormat,
+    renderer: &Renderer,
+) -> Reply {
+    let mut notes = Vec::new();
+    let mut nodes = Vec::new();
+    for query in [&question.from, &question.to] {
+        let Some(resolved) = resolve(map, query) else {
+            return Reply::text(format!("I don't know a node called \"{query}\""));
+        };
+        if !resolved.exact {
+            notes.push(format!("Assuming \"{}\" for \"{query}\"\n", resolved.name));
+        }
+        nodes.push(resolved.name);
+    }
+    let (from, to) = (&nodes[0], &nodes[1]);
+
+    let options = CalcOptions::new().aerobrake(question.aerobrake);
+    let route = match map.calculate_route(from, to, &options) {
+        Ok(Some(route)) => route,
+        Ok(None) => return Reply::text(format!("There is no route from {from} to {to}")),
+        Err(e) => return Reply::text(e.to_string()),
+    };
+
+    let text = format!(
+        "{}**{from} → {to}: {}**\n```\n{}\n```",
+        notes.concat(),
+        format.format(route.delta_v()),
+        route.to_table(format)
+    );
+    // The text already contains the whole answer, so a failed drawing is only logged
+    let image = renderer
+        .to_png(&route.to_svg(format))
+        .inspect_err(|e| eprintln!("Couldn't draw the route: {e}"))
+        .ok();
+
+    Reply { text, image }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::answer;
+    use crate::command::Question;
+    use crate::render::Renderer;
+    use deltav_calc::{DeltavFormat, DeltavMap};
+
+    fn ask(from: &str, to: &str) -> super::Reply {
+        let question = Question {
+            from: from.to_string(),
+            to: to.to_string(),
+            aerobrake: false,
+        };
+        answer(
+            &DeltavMap::new_stock(),
+            &question,
+            &DeltavFormat::new(),
+            &Renderer::new(),
+        )
+    }
+
+    #[test]
+    fn test_answer() {
+        let reply = ask("Kerbin Surface", "mun surf");
+        assert!(reply.text.starts_with(
+            "Assuming \"Mun Surface\" for \"mun surf\"\n**Kerbin Surface → Mun Surface: "
+        ));
+        assert!(reply.text.contains("Low Kerbin Orbit (80km)"));
+        assert!(reply.image.is_some());
+
+        let reply = ask("Kerbin Surface", "xyzzy");
+        assert_eq!(reply.text, "I don't know a node called \"xyzzy\"");
+        assert!(reply.image.is_none());
+    }
+}
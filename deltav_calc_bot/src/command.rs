@@ -0,0 +1,102 @@
+/// The word messages have to start with to be answered
+pub const PREFIX: &str = "!dv";
+
+/// How the command is used, sent when it is used wrong
+pub const USAGE: &str = "Usage: `!dv \"<start>\" \"<end>\" [--aerobrake]`, for example \
+                         `!dv \"Kerbin Surface\" \"Mun Surface\"`";
+
+/// A deltav question asked in a message
+#[derive(Eq, PartialEq, Debug)]
+pub struct Question {
+    pub from: String,
+    pub to: String,
+    pub aerobrake: bool,
+}
+
+/// Reads the question of a message. Returns `None` for messages that aren't meant for the bot
+/// and the usage for commands that can't be understood
+pub fn parse(content: &str) -> Option<Result<Question, &'static str>> {
+    let rest = content.trim().strip_prefix(PREFIX)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut aerobrake = false;
+    let mut names = Vec::new();
+    for word in split(rest) {
+        match word.as_str() {
+            "--aerobrake" | "-a" => aerobrake = true,
+            _ => names.push(word),
+        }
+    }
+
+    let Ok([from, to]) = <[String; 2]>::try_from(names) else {
+        return Some(Err(USAGE));
+    };
+    Some(Ok(Question {
+        from,
+        to,
+        aerobrake,
+    }))
+}
+
+// Splits the text at whitespace, keeping text in quotes together. Phones often replace quotes
+// with typographic ones, so those are accepted as well
+fn split(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+
+    for c in text.chars() {
+        match c {
+            '"' | '“' | '”' => {
+                if quoted || !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                quoted = !quoted;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Question, USAGE};
+
+    fn question(from: &str, to: &str, aerobrake: bool) -> Option<Result<Question, &'static str>> {
+        Some(Ok(Question {
+            from: from.to_string(),
+            to: to.to_string(),
+            aerobrake,
+        }))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("!dv \"Kerbin Surface\" \"Mun Surface\""),
+            question("Kerbin Surface", "Mun Surface", false)
+        );
+        assert_eq!(
+            parse("  !dv “Kerbin Surface” Duna --aerobrake"),
+            question("Kerbin Surface", "Duna", true)
+        );
+        assert_eq!(parse("!dv -a Eve Moho"), question("Eve", "Moho", true));
+
+        assert_eq!(parse("!dv"), Some(Err(USAGE)));
+        assert_eq!(parse("!dv Kerbin Surface Mun Surface"), Some(Err(USAGE)));
+        assert_eq!(parse("!dvx \"A\" \"B\""), None);
+        assert_eq!(parse("how much dv to the mun?"), None);
+    }
+}
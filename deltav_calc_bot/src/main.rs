@@ -0,0 +1,85 @@
+use answer::answer;
+use clap::Parser;
+use deltav_calc::{DeltavFormat, DeltavMap};
+use render::Renderer;
+use serenity::all::{
+    Context, CreateAttachment, CreateMessage, EventHandler, GatewayIntents, Message,
+};
+use serenity::async_trait;
+use serenity::Client;
+use std::error::Error;
+use std::path::PathBuf;
+
+mod answer;
+mod command;
+mod render;
+mod resolve;
+
+// Answers deltav questions on Discord. The token of the bot is read from DISCORD_TOKEN
+#[derive(Parser, Debug)]
+#[command(name = "deltav-bot", version, about)]
+struct Args {
+    /// Reads the map from the file instead of using the stock map
+    #[arg(long)]
+    map: Option<PathBuf>,
+}
+
+// Answers the messages that start with the command
+struct Handler {
+    map: DeltavMap,
+    format: DeltavFormat,
+    renderer: Renderer,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn message(&self, ctx: Context, message: Message) {
+        if message.author.bot {
+            return;
+        }
+        let reply = match command::parse(&message.content) {
+            None => return,
+            Some(Ok(question)) => answer(&self.map, &question, &self.format, &self.renderer),
+            Some(Err(usage)) => answer::Reply {
+                text: usage.to_string(),
+                image: None,
+            },
+        };
+
+        let mut builder = CreateMessage::new()
+            .content(reply.text)
+            .reference_message(&message);
+        if let Some(image) = reply.image {
+            builder = builder.add_file(CreateAttachment::bytes(image, "route.png"));
+        }
+        if let Err(e) = message.channel_id.send_message(&ctx.http, builder).await {
+            eprintln!("Couldn't answer a message: {e}");
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let map = match &args.map {
+        None => DeltavMap::new_stock(),
+        Some(path) => DeltavMap::from_path(path)
+            .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?,
+    };
+    let token = std::env::var("DISCORD_TOKEN").map_err(|_| "DISCORD_TOKEN isn't set")?;
+
+    let handler = Handler {
+        map,
+        format: DeltavFormat::new(),
+        renderer: Renderer::new(),
+    };
+    let intents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
+    let mut client = Client::builder(&token, intents)
+        .event_handler(handler)
+        .await?;
+    client.start().await?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::fontdb::{Family, Query};
+use resvg::usvg::{Options, Tree};
+use std::error::Error;
+
+// Sans-serif fonts in the order they are preferred
+const SANS_SERIF: [&str; 5] = [
+    "Arial",
+    "Helvetica",
+    "DejaVu Sans",
+    "Liberation Sans",
+    "Noto Sans",
+];
+
+/// Turns SVG images into PNG, which Discord shows inline
+pub struct Renderer {
+    options: Options<'static>,
+}
+
+impl Renderer {
+    /// Creates a renderer using the fonts installed on the system
+    pub fn new() -> Renderer {
+        let mut options = Options::default();
+        let fonts = options.fontdb_mut();
+        fonts.load_system_fonts();
+
+        // The generic sans-serif family defaults to Arial, which servers often don't have
+        let installed = SANS_SERIF.into_iter().find(|name| {
+            let families = [Family::Name(name)];
+            fonts
+                .query(&Query {
+                    families: &families,
+                    ..Query::default()
+                })
+                .is_some()
+        });
+        if let Some(name) = installed {
+            fonts.set_sans_serif_family(name);
+        }
+
+        Renderer { options }
+    }
+
+    /// Renders the SVG image as PNG
+    pub fn to_png(&self, svg: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let tree = Tree::from_str(svg, &self.options)?;
+        let size = tree.size().to_int_size();
+        let mut pixmap =
+            Pixmap::new(size.width(), size.height()).ok_or("The image has no pixels")?;
+        resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+
+    #[test]
+    fn test_to_png() {
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"10\">\
+                   <rect width=\"100%\" height=\"100%\" fill=\"white\"/></svg>";
+        let png = Renderer::new().to_png(svg).unwrap();
+
+        assert!(png.starts_with(b"\x89PNG"));
+        assert!(Renderer::new().to_png("not an image").is_err());
+    }
+}
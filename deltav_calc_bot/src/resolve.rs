@@ -0,0 +1,68 @@
+use deltav_calc::DeltavMap;
+
+/// The node a name given by a user was matched to
+#[derive(Eq, PartialEq, Debug)]
+pub struct Resolved {
+    pub name: String,
+    /// If the name was written exactly as the node is called
+    pub exact: bool,
+}
+
+/// Finds the node the user meant. Names that don't match exactly are compared ignoring case and
+/// then with [`fuzzy_search`](deltav_calc::MenuTree::fuzzy_search), taking its best match
+pub fn resolve(map: &DeltavMap, query: &str) -> Option<Resolved> {
+    let nodes: Vec<&str> = map
+        .menu_tree()
+        .iter()
+        .filter(|node| node.id().is_some())
+        .map(|node| node.name())
+        .collect();
+
+    if nodes.contains(&query) {
+        return Some(Resolved {
+            name: query.to_string(),
+            exact: true,
+        });
+    }
+
+    let name = nodes
+        .iter()
+        .find(|node| node.eq_ignore_ascii_case(query))
+        .copied()
+        .or_else(|| map.menu_tree().fuzzy_search(query).first().copied())?;
+
+    Some(Resolved {
+        name: name.to_string(),
+        exact: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve, Resolved};
+    use deltav_calc::DeltavMap;
+
+    fn resolved(name: &str, exact: bool) -> Option<Resolved> {
+        Some(Resolved {
+            name: name.to_string(),
+            exact,
+        })
+    }
+
+    #[test]
+    fn test_resolve() {
+        let map = DeltavMap::new_stock();
+
+        assert_eq!(
+            resolve(&map, "Kerbin Surface"),
+            resolved("Kerbin Surface", true)
+        );
+        assert_eq!(
+            resolve(&map, "kerbin surface"),
+            resolved("Kerbin Surface", false)
+        );
+        assert_eq!(resolve(&map, "mun surf"), resolved("Mun Surface", false));
+        assert_eq!(resolve(&map, "Kerbol System"), None);
+        assert_eq!(resolve(&map, "xyzzy"), None);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::output::{write_csv, write_json, OutputFormat};
+use clap::Args;
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap, Route};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// A CSV file with a `from` and a `to` column, one query per row
+    queries: PathBuf,
+
+    /// Allows aerobraking when entering an atmosphere
+    #[arg(long)]
+    aerobrake: bool,
+
+    /// Adds a safety margin in percent
+    #[arg(long, default_value_t = 0)]
+    margin: u32,
+}
+
+// A row of the queries file
+#[derive(Deserialize, Debug)]
+struct Query {
+    from: String,
+    to: String,
+}
+
+// The result of a query. The deltav and the route are missing if there is no route or the query
+// is invalid, which is described by the error
+#[derive(Serialize, Debug)]
+struct Answer {
+    from: String,
+    to: String,
+    delta_v: Option<i64>,
+    route: Option<Route>,
+    error: Option<String>,
+}
+
+// Calculates the deltav of every query and prints them
+pub fn run(
+    map: &DeltavMap,
+    args: &BatchArgs,
+    format: &DeltavFormat,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::open(&args.queries)
+        .map_err(|e| format!("Couldn't read {}: {e}", args.queries.display()))?;
+    let options = CalcOptions::new()
+        .aerobrake(args.aerobrake)
+        .margin(args.margin);
+    let answers = answer(map, &read_queries(file)?, &options);
+
+    write_answers(&answers, format, output, out)
+}
+
+// Reads the queries from CSV
+fn read_queries(reader: impl Read) -> Result<Vec<Query>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    Ok(reader.deserialize().collect::<Result<_, _>>()?)
+}
+
+// Calculates the queries in parallel
+fn answer(map: &DeltavMap, queries: &[Query], options: &CalcOptions) -> Vec<Answer> {
+    // The library expects the names of nodes, so queries with categories are answered here
+    let is_category = |name: &str| {
+        map.menu_tree()
+            .search(name)
+            .is_ok_and(|node| node.id().is_none())
+    };
+    let routable: Vec<(&str, &str)> = queries
+        .iter()
+        .filter(|query| !is_category(&query.from) && !is_category(&query.to))
+        .map(|query| (query.from.as_str(), query.to.as_str()))
+        .collect();
+    let mut results = map.calculate_routes(&routable, options).into_iter();
+
+    queries
+        .iter()
+        .map(|query| {
+            let names = [&query.from, &query.to];
+            let (route, error) = match names.iter().find(|name| is_category(name)) {
+                Some(category) => (None, Some(format!("\"{category}\" is a category"))),
+                None => match results.next().expect("Every routable query has a result") {
+                    Ok(route) => (route, None),
+                    Err(e) => (None, Some(e.to_string())),
+                },
+            };
+
+            Answer {
+                from: query.from.clone(),
+                to: query.to.clone(),
+                delta_v: route
+                    .as_ref()
+                    .map(|route| options.apply_margin(route.delta_v())),
+                route,
+                error,
+            }
+        })
+        .collect()
+}
+
+// Writes the answers. The table has one aligned row per query, CSV leaves out the routes
+fn write_answers(
+    answers: &[Answer],
+    format: &DeltavFormat,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    match output {
+        OutputFormat::Json => return write_json(out, &answers),
+        OutputFormat::Csv => {
+            let rows = answers.iter().map(|answer| {
+                [
+                    answer.from.clone(),
+                    answer.to.clone(),
+                    answer.delta_v.map(|v| v.to_string()).unwrap_or_default(),
+                    answer.error.clone().unwrap_or_default(),
+                ]
+            });
+            return write_csv(out, ["from", "to", "delta_v", "error"], rows);
+        }
+        OutputFormat::Table => {}
+    }
+
+    let mut rows = vec![[
+        String::from("From"),
+        String::from("To"),
+        String::from("Deltav"),
+    ]];
+    for answer in answers {
+        let delta_v = match (answer.delta_v, &answer.error) {
+            (Some(delta_v), _) => format.format(delta_v),
+            (None, Some(error)) => error.clone(),
+            (None, None) => String::from("no route"),
+        };
+        rows.push([answer.from.clone(), answer.to.clone(), delta_v]);
+    }
+
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or_default()
+    };
+    let (from_width, to_width) = (width(0), width(1));
+    for [from, to, delta_v] in rows {
+        writeln!(out, "{from:<from_width$}  {to:<to_width$}  {delta_v}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{answer, read_queries, write_answers};
+    use crate::output::OutputFormat;
+    use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap};
+
+    #[test]
+    fn test_batch() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Test]
+            Node1
+            Node2
+            Node3
+            Node4
+            Node1 -> Node2 : 9000
+            Node3 -> Node4 : 50
+            ",
+        )
+        .unwrap();
+        let queries = read_queries(
+            "from,to\nNode1,Node2\nNode2 , Node1\nNode1,Node3\nTest,Node1\nNode1,Node5\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let answers = answer(&map, &queries, &CalcOptions::new());
+        let write = |output| {
+            let mut out = Vec::new();
+            write_answers(&answers, &DeltavFormat::new(), output, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            write(OutputFormat::Table),
+            "From   To     Deltav\n\
+             Node1  Node2  9,000 m/s\n\
+             Node2  Node1  9,000 m/s\n\
+             Node1  Node3  no route\n\
+             Test   Node1  \"Test\" is a category\n\
+             Node1  Node5  There is no node with the name \"Node5\" in the tree\n"
+        );
+        assert!(write(OutputFormat::Csv).starts_with("from,to,delta_v,error\nNode1,Node2,9000,\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["route"]["legs"][0]["delta_v"], 9000);
+        assert_eq!(json[2]["delta_v"], serde_json::Value::Null);
+
+        assert!(read_queries("start,end\nNode1,Node2\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::completion;
+use crate::output::{write_csv, write_json, OutputFormat};
+use crate::picker;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap, Route};
+use serde::Serialize;
+use std::error::Error;
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct CalcArgs {
+    /// The node the route starts at
+    #[arg(long, add = ArgValueCandidates::new(completion::node_names))]
+    from: String,
+
+    /// The node the route ends at
+    #[arg(long, add = ArgValueCandidates::new(completion::node_names))]
+    to: String,
+
+    /// Allows aerobraking when entering an atmosphere
+    #[arg(long)]
+    aerobrake: bool,
+
+    /// Adds a safety margin in percent
+    #[arg(long, default_value_t = 0)]
+    margin: u32,
+
+    /// Prints every leg of the route instead of just the total
+    #[arg(long)]
+    route: bool,
+
+    /// Prints the total as a plain number in m/s, for use in scripts
+    #[arg(long, conflicts_with = "route")]
+    raw: bool,
+
+    /// Prints a kOS mission script with the budget of every leg instead of the total, ignoring
+    /// the output format
+    #[arg(long, conflicts_with_all = ["route", "raw"])]
+    kos: bool,
+
+    /// Prints a checklist of the maneuvers with the budget of every leg including the margin and
+    /// notes on aerobraking and hazards
+    #[arg(long, conflicts_with_all = ["route", "raw", "kos"])]
+    checklist: bool,
+
+    /// Fails if a name doesn't match a node exactly instead of asking which node was meant
+    #[arg(long)]
+    exact: bool,
+}
+
+// The JSON output, which contains the total with the margin and the route
+#[derive(Serialize, Debug)]
+struct CalcReport<'a> {
+    delta_v: i64,
+    margin: u32,
+    route: &'a Route,
+}
+
+// Prints the deltav required to get from the start to the end. CSV lists the legs of the route
+pub fn run(
+    map: &DeltavMap,
+    args: &CalcArgs,
+    format: &DeltavFormat,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let from = picker::resolve(map, &args.from, !args.exact)?;
+    let to = picker::resolve(map, &args.to, !args.exact)?;
+
+    let options = CalcOptions::new()
+        .aerobrake(args.aerobrake)
+        .margin(args.margin);
+    let Some(route) = map.calculate_route(&from, &to, &options)? else {
+        return Err(format!("There is no route from \"{from}\" to \"{to}\"").into());
+    };
+    let total = options.apply_margin(route.delta_v());
+
+    if args.kos {
+        write!(out, "{}", route.to_kos())?;
+        return Ok(());
+    }
+
+    if args.checklist {
+        let checklist = route.checklist(&options);
+        return match output {
+            OutputFormat::Table => Ok(writeln!(out, "{checklist}")?),
+            OutputFormat::Json => write_json(out, &checklist),
+            OutputFormat::Csv => {
+                let maneuvers = checklist.maneuvers().iter().map(|maneuver| {
+                    [
+                        maneuver.step().to_string(),
+                        maneuver.from().to_string(),
+                        maneuver.to().to_string(),
+                        maneuver.delta_v().to_string(),
+                        maneuver.budget().to_string(),
+                        maneuver.notes().join("; "),
+                    ]
+                });
+                write_csv(
+                    out,
+                    ["step", "from", "to", "delta_v", "budget", "notes"],
+                    maneuvers,
+                )
+            }
+        };
+    }
+
+    match output {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            let report = CalcReport {
+                delta_v: total,
+                margin: args.margin,
+                route: &route,
+            };
+            return write_json(out, &report);
+        }
+        OutputFormat::Csv => {
+            let legs = route.legs().iter().map(|leg| {
+                [
+                    leg.from().to_string(),
+                    leg.to().to_string(),
+                    leg.delta_v().to_string(),
+                    leg.aerobrake().to_string(),
+                ]
+            });
+            return write_csv(out, ["from", "to", "delta_v", "aerobrake"], legs);
+        }
+    }
+
+    if args.raw {
+        writeln!(out, "{total}")?;
+    } else if args.route {
+        writeln!(out, "{}", route.to_table(format))?;
+        if args.margin > 0 {
+            writeln!(
+                out,
+                "With a {}% margin: {}",
+                args.margin,
+                format.format(total)
+            )?;
+        }
+    } else {
+        writeln!(out, "{}", format.format(total))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, CalcArgs};
+    use crate::output::OutputFormat;
+    use deltav_calc::{DeltavFormat, DeltavMap};
+
+    fn test_map() -> DeltavMap {
+        DeltavMap::from_dsl(
+            "
+            [Test]
+            Node1
+            Node2
+            Node3
+            Node1 -> Node2 : 9000
+            Node2 -> Node3 : 4000
+            ",
+        )
+        .unwrap()
+    }
+
+    fn calc_as(args: CalcArgs, output: OutputFormat) -> Result<String, String> {
+        let mut out = Vec::new();
+        run(&test_map(), &args, &DeltavFormat::new(), output, &mut out)
+            .map_err(|e| e.to_string())?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn calc(args: CalcArgs) -> Result<String, String> {
+        calc_as(args, OutputFormat::Table)
+    }
+
+    fn args(from: &str, to: &str) -> CalcArgs {
+        CalcArgs {
+            from: from.to_string(),
+            to: to.to_string(),
+            aerobrake: false,
+            margin: 0,
+            route: false,
+            raw: false,
+            kos: false,
+            checklist: false,
+            exact: true,
+        }
+    }
+
+    #[test]
+    fn test_calc() {
+        assert_eq!(calc(args("Node1", "Node3")).unwrap(), "13.0 km/s\n");
+        assert_eq!(calc(args("Node2", "Node3")).unwrap(), "4,000 m/s\n");
+
+        let raw = CalcArgs {
+            margin: 10,
+            raw: true,
+            ..args("Node1", "Node3")
+        };
+        assert_eq!(calc(raw).unwrap(), "14300\n");
+
+        let route = CalcArgs {
+            margin: 10,
+            route: true,
+            ..args("Node1", "Node3")
+        };
+        assert_eq!(
+            calc(route).unwrap(),
+            "Node1 -> Node2  9,000 m/s\n\
+             Node2 -> Node3  4,000 m/s\n\
+             Total           13.0 km/s\n\
+             With a 10% margin: 14.3 km/s\n"
+        );
+
+        assert_eq!(
+            calc_as(args("Node1", "Node3"), OutputFormat::Csv).unwrap(),
+            "from,to,delta_v,aerobrake\nNode1,Node2,9000,false\nNode2,Node3,4000,false\n"
+        );
+        let margin = CalcArgs {
+            margin: 10,
+            ..args("Node1", "Node3")
+        };
+        let json = calc_as(margin, OutputFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["delta_v"], 14300);
+        assert_eq!(json["route"]["legs"][1]["to"], "Node3");
+
+        let kos = CalcArgs {
+            kos: true,
+            ..args("Node1", "Node3")
+        };
+        let script = calc_as(kos, OutputFormat::Json).unwrap();
+        assert!(script.starts_with("// Node1 -> Node3\n"));
+        assert!(script.contains("global LEG_2_DV is 4000.\n"));
+
+        let checklist = CalcArgs {
+            margin: 10,
+            checklist: true,
+            ..args("Node1", "Node3")
+        };
+        assert_eq!(
+            calc(checklist).unwrap(),
+            "1. Node1 -> Node2: 9000 m/s (budget 9900 m/s)\n\
+             2. Node2 -> Node3: 4000 m/s (budget 4400 m/s)\n\
+             Total: 13000 m/s (budget 14300 m/s with a 10% margin)\n"
+        );
+
+        assert!(calc(args("Node1", "Node4")).is_err());
+        assert_eq!(
+            calc(args("Test", "Node1")).unwrap_err(),
+            "\"Test\" is a category, not a node"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use crate::load_map;
+use clap_complete::CompletionCandidate;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+// Completes the names of the nodes of the map passed with --map on the command line being
+// completed, or of the stock map
+pub fn node_names() -> Vec<CompletionCandidate> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let Ok(map) = load_map(map_path(&args).as_deref()) else {
+        return vec![];
+    };
+
+    map.menu_tree()
+        .iter()
+        .filter(|node| node.id().is_some())
+        .map(|node| CompletionCandidate::new(node.name()))
+        .collect()
+}
+
+// Finds the value of --map in the arguments, which come after a "--" when completing
+fn map_path(args: &[OsString]) -> Option<PathBuf> {
+    let start = args.iter().position(|arg| arg == "--").map_or(0, |i| i + 1);
+    let mut args = args[start..].iter();
+
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--map" {
+            path = args.next().map(PathBuf::from);
+        } else if let Some(value) = arg.to_str().and_then(|arg| arg.strip_prefix("--map=")) {
+            path = Some(PathBuf::from(value));
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_path;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_map_path() {
+        let args = |line: &str| line.split(' ').map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(map_path(&args("deltav -- deltav calc --from Ke")), None);
+        assert_eq!(
+            map_path(&args("deltav -- deltav --map a.json calc --from Ke")),
+            Some(PathBuf::from("a.json"))
+        );
+        assert_eq!(
+            map_path(&args("deltav -- deltav calc --map=b.dsl --to")),
+            Some(PathBuf::from("b.dsl"))
+        );
+        assert_eq!(map_path(&args("deltav --map c.json -- deltav calc")), None);
+    }
+}
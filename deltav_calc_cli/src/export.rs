@@ -0,0 +1,87 @@
+use clap::{Args, ValueEnum};
+use deltav_calc::DeltavMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ExportFormat {
+    /// The DOT language of Graphviz
+    Dot,
+
+    /// An SVG image
+    Svg,
+
+    /// The JSON format the maps are stored in
+    Json,
+
+    /// A nodes.csv and an edges.csv file, which need a directory given with --file
+    Csv,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// The format to write the map in
+    #[arg(long, value_enum)]
+    format: ExportFormat,
+
+    /// Writes the map to the file instead of stdout. For CSV this is the directory the files are
+    /// written to
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+// Writes the map in the chosen format
+pub fn run(map: &DeltavMap, args: &ExportArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let text = match args.format {
+        ExportFormat::Dot => map.to_dot(),
+        ExportFormat::Svg => map.to_svg(),
+        ExportFormat::Json => {
+            let mut json = Vec::new();
+            map.to_writer(&mut json)?;
+            String::from_utf8(json)?
+        }
+        ExportFormat::Csv => {
+            let Some(directory) = &args.file else {
+                return Err("CSV export needs a directory given with --file".into());
+            };
+            fs::create_dir_all(directory)?;
+            let nodes = File::create(directory.join("nodes.csv"))?;
+            let edges = File::create(directory.join("edges.csv"))?;
+
+            return Ok(map.to_csv(nodes, edges)?);
+        }
+    };
+
+    match &args.file {
+        None => out.write_all(text.as_bytes())?,
+        Some(path) => fs::write(path, text)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, ExportArgs, ExportFormat};
+    use deltav_calc::DeltavMap;
+
+    #[test]
+    fn test_export() {
+        let map = DeltavMap::from_dsl("[Test]\nNode1\nNode2\nNode1 -> Node2 : 900").unwrap();
+        let export = |format| {
+            let args = ExportArgs { format, file: None };
+            let mut out = Vec::new();
+            run(&map, &args, &mut out).map(|_| String::from_utf8(out).unwrap())
+        };
+
+        assert_eq!(export(ExportFormat::Dot).unwrap(), map.to_dot());
+        assert!(export(ExportFormat::Svg).unwrap().starts_with("<svg"));
+        assert_eq!(
+            DeltavMap::from_reader(export(ExportFormat::Json).unwrap().as_bytes()).unwrap(),
+            map
+        );
+        assert!(export(ExportFormat::Csv).is_err());
+    }
+}
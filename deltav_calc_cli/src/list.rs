@@ -0,0 +1,99 @@
+use crate::output::{write_csv, write_json, OutputFormat};
+use clap::Args;
+use deltav_calc::DeltavMap;
+use std::error::Error;
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Only lists the nodes in the category, for example a planet or moon
+    #[arg(long)]
+    body: Option<String>,
+}
+
+// Prints the names of the nodes, one per line or as a JSON array
+pub fn run(
+    map: &DeltavMap,
+    args: &ListArgs,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let tree = match &args.body {
+        None => map.menu_tree().root(),
+        Some(body) => {
+            let tree = map.menu_tree().search(body)?;
+            if tree.id().is_some() {
+                return Err(format!("\"{body}\" is a node, not a category").into());
+            }
+            tree
+        }
+    };
+
+    let names: Vec<&str> = tree
+        .iter()
+        .filter(|node| node.id().is_some())
+        .map(|node| node.name())
+        .collect();
+
+    match output {
+        OutputFormat::Table => {
+            for name in names {
+                writeln!(out, "{name}")?;
+            }
+        }
+        OutputFormat::Json => write_json(out, &names)?,
+        OutputFormat::Csv => write_csv(out, ["name"], names.iter().map(|name| [name.to_string()]))?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, ListArgs};
+    use crate::output::OutputFormat;
+    use deltav_calc::DeltavMap;
+
+    fn list_as(body: Option<&str>, output: OutputFormat) -> Result<String, String> {
+        let map = DeltavMap::from_dsl(
+            "
+            [System/Planet]
+            Surface
+            Orbit
+            Surface -> Orbit : 3000
+
+            [System/Planet/Moon]
+            Moon Orbit
+            Orbit -> Moon Orbit : 800
+            ",
+        )
+        .unwrap();
+        let args = ListArgs {
+            body: body.map(str::to_string),
+        };
+        let mut out = Vec::new();
+        run(&map, &args, output, &mut out).map_err(|e| e.to_string())?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn list(body: Option<&str>) -> Result<String, String> {
+        list_as(body, OutputFormat::Table)
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(list(None).unwrap(), "Surface\nOrbit\nMoon Orbit\n");
+        assert_eq!(list(Some("Moon")).unwrap(), "Moon Orbit\n");
+        assert_eq!(
+            list_as(Some("Planet"), OutputFormat::Csv).unwrap(),
+            "name\nSurface\nOrbit\nMoon Orbit\n"
+        );
+        assert_eq!(
+            list_as(Some("Moon"), OutputFormat::Json).unwrap(),
+            "[\n  \"Moon Orbit\"\n]\n"
+        );
+        assert!(list(Some("Orbit")).is_err());
+        assert!(list(Some("Sun")).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::CompleteEnv;
+use deltav_calc::{DeltavFormat, DeltavMap};
+use output::OutputFormat;
+use std::error::Error;
+use std::io::{stdout, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+mod batch;
+mod calc;
+mod completion;
+mod export;
+mod list;
+mod output;
+mod picker;
+#[cfg(feature = "server")]
+mod serve;
+mod shell;
+mod tree;
+mod validate;
+
+// Calculates the deltav requirements of Kerbal Space Program without a graphical interface
+#[derive(Parser, Debug)]
+#[command(name = "deltav", version, about)]
+struct Cli {
+    /// Reads the map from the file instead of using the stock map
+    #[arg(long, global = true)]
+    map: Option<PathBuf>,
+
+    /// How the results are printed. Export has its own --format instead
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Calculates the deltav required to get from one node to another
+    Calc(calc::CalcArgs),
+
+    /// Calculates the deltav of many pairs of nodes read from a CSV file
+    Batch(batch::BatchArgs),
+
+    /// Prints the categories and nodes of the map as a tree
+    Tree,
+
+    /// Lists the names of the nodes
+    List(list::ListArgs),
+
+    /// Starts an interactive prompt with tab completion of node names
+    Shell,
+
+    /// Writes the map in another format
+    Export(export::ExportArgs),
+
+    /// Serves the map over HTTP as JSON at /maps, /nodes and /route
+    #[cfg(feature = "server")]
+    Serve(serve::ServeArgs),
+
+    /// Checks a map file and prints every problem. Exits with 1 if the map has problems and with
+    /// 2 if it can't be read
+    Validate(validate::ValidateArgs),
+}
+
+fn main() -> ExitCode {
+    // Answers the shell when it asks for completions, see the README
+    CompleteEnv::with_factory(Cli::command).complete();
+
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(code) => code,
+        // The output was piped into a program like head that stopped reading
+        Err(e) if is_broken_pipe(e.as_ref()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Loads the map and runs the subcommand
+fn run(cli: &Cli) -> Result<ExitCode, Box<dyn Error>> {
+    let mut out = stdout().lock();
+
+    // Validation reads the map itself, so it can report maps that can't be loaded normally
+    if let Command::Validate(args) = &cli.command {
+        return validate::run(args, cli.output, &mut out);
+    }
+
+    // The server loads the map passed with --map and any number of other maps
+    #[cfg(feature = "server")]
+    if let Command::Serve(args) = &cli.command {
+        serve::run(cli.map.as_deref(), args)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let map = load_map(cli.map.as_deref())?;
+    match &cli.command {
+        Command::Calc(args) => calc::run(&map, args, &number_format(), cli.output, &mut out)?,
+        Command::Batch(args) => batch::run(&map, args, &number_format(), cli.output, &mut out)?,
+        Command::Tree => tree::run(&map, cli.output, &mut out)?,
+        Command::List(args) => list::run(&map, args, cli.output, &mut out)?,
+        Command::Export(args) => export::run(&map, args, &mut out)?,
+        Command::Shell => shell::run(&map, &number_format(), &mut out)?,
+        Command::Validate(_) => unreachable!("Validation is handled above"),
+        #[cfg(feature = "server")]
+        Command::Serve(_) => unreachable!("The server is handled above"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// Checks if the error happened because stdout was closed
+fn is_broken_pipe(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == ErrorKind::BrokenPipe)
+}
+
+// Reads the map from the path or returns the stock map if there is none
+fn load_map(path: Option<&Path>) -> Result<DeltavMap, Box<dyn Error>> {
+    match path {
+        None => Ok(DeltavMap::new_stock()),
+        Some(path) => DeltavMap::from_path(path)
+            .map_err(|e| format!("Couldn't read {}: {e}", path.display()).into()),
+    }
+}
+
+// Formats numbers with the separators of the locale from the environment
+fn number_format() -> DeltavFormat {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|locale| !locale.is_empty())
+        .map(|locale| DeltavFormat::for_locale(&locale))
+        .unwrap_or_default()
+}
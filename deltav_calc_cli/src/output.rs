@@ -0,0 +1,42 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::error::Error;
+use std::io::Write;
+
+/// How the results of a subcommand are printed
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq, Default, Debug)]
+pub enum OutputFormat {
+    /// Text meant to be read by people
+    #[default]
+    Table,
+
+    /// JSON, using the same shape as the serialized types of the library
+    Json,
+
+    /// CSV with a header row
+    Csv,
+}
+
+// Writes the value as pretty printed JSON
+pub fn write_json(out: &mut impl Write, value: &impl Serialize) -> Result<(), Box<dyn Error>> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)?;
+
+    Ok(())
+}
+
+// Writes the header and the rows as CSV
+pub fn write_csv<const N: usize>(
+    out: &mut impl Write,
+    header: [&str; N],
+    rows: impl IntoIterator<Item = [String; N]>,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(header)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
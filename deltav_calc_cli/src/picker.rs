@@ -0,0 +1,56 @@
+use deltav_calc::DeltavMap;
+use dialoguer::FuzzySelect;
+use std::error::Error;
+use std::io::{stderr, stdin, IsTerminal};
+
+// Returns the name of the node the user meant. Names that don't match a node exactly open a fuzzy
+// selector over all nodes if the picker is enabled and the shell is interactive, and the chosen
+// name is printed so scripts can be fixed
+pub fn resolve(map: &DeltavMap, name: &str, picker: bool) -> Result<String, Box<dyn Error>> {
+    let error = match map.menu_tree().search(name) {
+        Ok(node) if node.id().is_some() => return Ok(name.to_string()),
+        Ok(_) => return Err(format!("\"{name}\" is a category, not a node").into()),
+        Err(e) => e,
+    };
+    if !picker || !stdin().is_terminal() || !stderr().is_terminal() {
+        return Err(error.into());
+    }
+
+    let names: Vec<&str> = map
+        .menu_tree()
+        .iter()
+        .filter(|node| node.id().is_some())
+        .map(|node| node.name())
+        .collect();
+    let selection = FuzzySelect::new()
+        .with_prompt(format!("\"{name}\" isn't a node, pick one"))
+        .with_initial_text(name)
+        .items(&names)
+        .interact_opt()?;
+
+    match selection {
+        Some(index) => {
+            eprintln!("note: using \"{}\" for \"{name}\"", names[index]);
+            Ok(names[index].to_string())
+        }
+        None => Err(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve;
+    use deltav_calc::DeltavMap;
+
+    #[test]
+    fn test_resolve() {
+        let map = DeltavMap::from_dsl("[Test]\nNode1\nNode2\nNode1 -> Node2 : 900").unwrap();
+
+        assert_eq!(resolve(&map, "Node1", false).unwrap(), "Node1");
+        assert!(resolve(&map, "node1", false).is_err());
+        assert_eq!(
+            resolve(&map, "Test", true).unwrap_err().to_string(),
+            "\"Test\" is a category, not a node"
+        );
+    }
+}
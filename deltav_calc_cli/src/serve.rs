@@ -0,0 +1,326 @@
+use crate::load_map;
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use clap::Args;
+use deltav_calc::{CalcOptions, DeltavMap, MenuTree, Metadata, Route};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+// The name the stock map is served under
+const STOCK: &str = "stock";
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// The address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+
+    /// Prints the OpenAPI description of the endpoints instead of starting the server
+    #[arg(long)]
+    openapi: bool,
+
+    /// More map files to serve, each named after its file name without the extension. The stock
+    /// map is always served as "stock"
+    maps: Vec<PathBuf>,
+}
+
+// The maps shared by all requests
+struct Maps {
+    maps: BTreeMap<String, DeltavMap>,
+    // The map used by requests that don't name one
+    default: String,
+}
+
+impl Maps {
+    // Returns the map with the name or the default map
+    fn get(&self, name: Option<&str>) -> Result<&DeltavMap, ApiError> {
+        let name = name.unwrap_or(&self.default);
+        self.maps
+            .get(name)
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("There is no map \"{name}\"")))
+    }
+}
+
+// A failed request, answered with the status code and the message as JSON
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+// The body of failed requests
+#[derive(Serialize, ToSchema, Debug)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+// An entry of /maps
+#[derive(Serialize, ToSchema, Debug)]
+struct MapInfo {
+    name: String,
+    metadata: Metadata,
+}
+
+// The query of /nodes
+#[derive(Deserialize, IntoParams, Debug)]
+#[into_params(parameter_in = Query)]
+struct NodesQuery {
+    /// The name of the map, the default map if it is missing
+    map: Option<String>,
+}
+
+// The query of /route
+#[derive(Deserialize, IntoParams, Debug)]
+#[into_params(parameter_in = Query)]
+struct RouteQuery {
+    /// The name of the map, the default map if it is missing
+    map: Option<String>,
+    /// The node the route starts at
+    from: String,
+    /// The node the route ends at
+    to: String,
+    /// Allows aerobraking when entering an atmosphere
+    #[serde(default)]
+    aerobrake: bool,
+}
+
+// The description of the endpoints, which clients can be generated from
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "deltav",
+        description = "Deltav requirements of Kerbal Space Program"
+    ),
+    paths(list_maps, nodes, route)
+)]
+struct ApiDoc;
+
+// Loads the maps and answers requests until the process is stopped. The map passed with --map is
+// used by requests that don't name a map
+pub fn run(map: Option<&Path>, args: &ServeArgs) -> Result<(), Box<dyn Error>> {
+    if args.openapi {
+        println!("{}", ApiDoc::openapi().to_pretty_json()?);
+        return Ok(());
+    }
+
+    let mut maps = BTreeMap::from([(STOCK.to_string(), DeltavMap::new_stock())]);
+    let mut default = STOCK.to_string();
+    for path in map
+        .into_iter()
+        .chain(args.maps.iter().map(PathBuf::as_path))
+    {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("{} isn't a file", path.display()))?;
+        if Some(path) == map {
+            default = name.clone();
+        }
+        maps.insert(name, load_map(Some(path))?);
+    }
+
+    let app = router(Maps { maps, default });
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let listener = tokio::net::TcpListener::bind(args.bind).await?;
+        eprintln!("Listening on http://{}", listener.local_addr()?);
+        axum::serve(listener, app).await
+    })?;
+
+    Ok(())
+}
+
+// Routes the endpoints to their handlers
+fn router(maps: Maps) -> Router {
+    Router::new()
+        .route("/maps", get(list_maps))
+        .route("/nodes", get(nodes))
+        .route("/route", get(route))
+        .route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+        .with_state(Arc::new(maps))
+}
+
+// Lists the names and the metadata of the served maps
+#[utoipa::path(get, path = "/maps", responses((status = OK, body = Vec<MapInfo>)))]
+async fn list_maps(State(maps): State<Arc<Maps>>) -> Json<Vec<MapInfo>> {
+    let infos = maps.maps.iter().map(|(name, map)| MapInfo {
+        name: name.clone(),
+        metadata: map.metadata().clone(),
+    });
+
+    Json(infos.collect())
+}
+
+// Returns the menu tree of a map
+#[utoipa::path(
+    get,
+    path = "/nodes",
+    params(NodesQuery),
+    responses(
+        (status = OK, body = MenuTree),
+        (status = NOT_FOUND, description = "There is no such map", body = ErrorBody)
+    )
+)]
+async fn nodes(
+    State(maps): State<Arc<Maps>>,
+    Query(query): Query<NodesQuery>,
+) -> Result<Response, ApiError> {
+    let map = maps.get(query.map.as_deref())?;
+
+    // The tree is written directly instead of copying it into the response first
+    let json = serde_json::to_vec(map.menu_tree())
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], json).into_response())
+}
+
+// Calculates the route between two nodes of a map
+#[utoipa::path(
+    get,
+    path = "/route",
+    params(RouteQuery),
+    responses(
+        (status = OK, body = Route),
+        (status = BAD_REQUEST, description = "A name belongs to a category", body = ErrorBody),
+        (status = NOT_FOUND, description = "A map, node or route doesn't exist", body = ErrorBody)
+    )
+)]
+async fn route(
+    State(maps): State<Arc<Maps>>,
+    Query(query): Query<RouteQuery>,
+) -> Result<Json<Route>, ApiError> {
+    let map = maps.get(query.map.as_deref())?;
+
+    // The library expects the names of nodes, not categories
+    for name in [&query.from, &query.to] {
+        let node = map
+            .menu_tree()
+            .search(name)
+            .map_err(|e| ApiError(StatusCode::NOT_FOUND, e.to_string()))?;
+        if node.id().is_none() {
+            let message = format!("\"{name}\" is a category, not a node");
+            return Err(ApiError(StatusCode::BAD_REQUEST, message));
+        }
+    }
+
+    let options = CalcOptions::new().aerobrake(query.aerobrake);
+    match map.calculate_route(&query.from, &query.to, &options) {
+        Ok(Some(route)) => Ok(Json(route)),
+        Ok(None) => Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!(
+                "There is no route from \"{}\" to \"{}\"",
+                query.from, query.to
+            ),
+        )),
+        Err(e) => Err(ApiError(StatusCode::NOT_FOUND, e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{router, Maps, STOCK};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use deltav_calc::DeltavMap;
+    use serde_json::Value;
+    use std::collections::BTreeMap;
+    use tower::ServiceExt;
+
+    // Sends a GET request to a server with the stock map and a test map
+    fn get(uri: &str) -> (StatusCode, Value) {
+        let test = DeltavMap::from_dsl("[Test]\nNode1\nNode2\nNode1 -> Node2 : 900").unwrap();
+        let maps = Maps {
+            maps: BTreeMap::from([
+                (STOCK.to_string(), DeltavMap::new_stock()),
+                ("test".to_string(), test),
+            ]),
+            default: "test".to_string(),
+        };
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let response = router(maps).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            // Missing query parameters are rejected with plain text
+            (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+        })
+    }
+
+    #[test]
+    fn test_maps() {
+        let (status, body) = get("/maps");
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["name"], "stock");
+        assert_eq!(body[1]["name"], "test");
+    }
+
+    #[test]
+    fn test_nodes() {
+        let (status, body) = get("/nodes");
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.to_string().contains("Node1"));
+
+        let (status, body) = get("/nodes?map=stock");
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.to_string().contains("Kerbin Surface"));
+
+        assert_eq!(get("/nodes?map=other").0, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_openapi() {
+        let (status, body) = get("/openapi.json");
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["paths"]["/route"]["get"]["parameters"].is_array());
+        assert_eq!(
+            body["paths"]["/route"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/Route"
+        );
+        for schema in [
+            "Route",
+            "Leg",
+            "WarningKind",
+            "MenuTree",
+            "MapInfo",
+            "ErrorBody",
+        ] {
+            assert!(
+                body["components"]["schemas"][schema].is_object(),
+                "{schema}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let (status, body) = get("/route?from=Node1&to=Node2");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["legs"][0]["delta_v"], 900);
+
+        let (status, body) = get("/route?map=stock&from=Kerbin%20Surface&to=Mun%20Surface");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["start"], "Kerbin Surface");
+
+        let (status, body) = get("/route?from=Test&to=Node2");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "\"Test\" is a category, not a node");
+
+        assert_eq!(get("/route?from=Node2&to=Nothing").0, StatusCode::NOT_FOUND);
+        assert_eq!(get("/route?from=Node1").0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,306 @@
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::error::Error;
+use std::io::Write;
+
+// The usage of the commands of the shell and what they do, also used for completion
+const COMMANDS: [(&str, &str); 7] = [
+    ("from <node>", "Sets the start of the route"),
+    ("to <node>", "Sets the end of the route"),
+    ("route", "Prints the route between the start and the end"),
+    (
+        "budget <m/s|off>",
+        "Sets the deltav the craft has available",
+    ),
+    ("aerobrake <on|off>", "Sets if aerobraking is allowed"),
+    ("help", "Prints this help"),
+    ("quit", "Leaves the shell"),
+];
+
+// The state of an interactive session
+#[derive(Default, Debug)]
+struct Session {
+    from: Option<String>,
+    to: Option<String>,
+    budget: Option<i64>,
+    aerobrake: bool,
+}
+
+// What the shell does after a command
+#[derive(Eq, PartialEq, Debug)]
+enum Outcome {
+    Print(String),
+    Quit,
+}
+
+impl Session {
+    // Runs a single line entered by the user
+    fn execute(
+        &mut self,
+        map: &DeltavMap,
+        format: &DeltavFormat,
+        line: &str,
+    ) -> Result<Outcome, String> {
+        let line = line.trim();
+        let (command, argument) = match line.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+
+        let message = match (command, argument) {
+            ("", _) => String::new(),
+            ("from" | "to", "") => return Err(format!("Usage: {command} <node>")),
+            ("from", name) => {
+                let name = node_name(map, name)?;
+                self.from = Some(name.clone());
+                format!("Start: {name}")
+            }
+            ("to", name) => {
+                let name = node_name(map, name)?;
+                self.to = Some(name.clone());
+                format!("End: {name}")
+            }
+            ("route", _) => self.route(map, format)?,
+            ("budget", "off") => {
+                self.budget = None;
+                String::from("Budget removed")
+            }
+            ("budget", budget) => {
+                let budget = budget
+                    .parse()
+                    .map_err(|_| String::from("Usage: budget <m/s|off>"))?;
+                self.budget = Some(budget);
+                format!("Budget: {}", format.format(budget))
+            }
+            ("aerobrake", "on" | "off") => {
+                self.aerobrake = argument == "on";
+                format!("Aerobraking: {argument}")
+            }
+            ("aerobrake", _) => return Err(String::from("Usage: aerobrake <on|off>")),
+            ("help", _) => COMMANDS
+                .map(|(usage, description)| format!("{usage:<20}{description}"))
+                .join("\n"),
+            ("quit" | "exit", _) => return Ok(Outcome::Quit),
+            _ => return Err(format!("Unknown command \"{command}\", try help")),
+        };
+
+        Ok(Outcome::Print(message))
+    }
+
+    // Formats the route between the start and the end and how it fits the budget
+    fn route(&self, map: &DeltavMap, format: &DeltavFormat) -> Result<String, String> {
+        let (Some(from), Some(to)) = (&self.from, &self.to) else {
+            return Err(String::from(
+                "Set the start with from and the end with to first",
+            ));
+        };
+
+        let options = CalcOptions::new().aerobrake(self.aerobrake);
+        let Some(route) = map
+            .calculate_route(from, to, &options)
+            .map_err(|e| e.to_string())?
+        else {
+            return Err(format!("There is no route from \"{from}\" to \"{to}\""));
+        };
+
+        let mut text = route.to_table(format);
+        if let Some(budget) = self.budget {
+            let left = budget - route.delta_v();
+            let verdict = match left {
+                0.. => format!("{} left", format.format(left)),
+                _ => format!("{} short", format.format(-left)),
+            };
+            text.push_str(&format!("\nBudget {}: {verdict}", format.format(budget)));
+        }
+
+        Ok(text)
+    }
+}
+
+// Checks that the name belongs to a node and not a category or nothing
+fn node_name(map: &DeltavMap, name: &str) -> Result<String, String> {
+    match map.menu_tree().search(name) {
+        Ok(node) if node.id().is_some() => Ok(node.name().to_string()),
+        Ok(_) => Err(format!("\"{name}\" is a category, not a node")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Completes command names at the start of the line and node names after from and to. Returns
+// where the completed word starts and the candidates
+fn complete<'a>(names: &'a [String], line: &str) -> (usize, Vec<&'a str>) {
+    let Some((command, argument)) = line.split_once(' ') else {
+        let commands = COMMANDS
+            .iter()
+            .filter_map(|(usage, _)| usage.split(' ').next());
+        return (
+            0,
+            commands
+                .filter(|command| command.starts_with(line))
+                .collect(),
+        );
+    };
+    if command != "from" && command != "to" {
+        return (line.len(), vec![]);
+    }
+
+    let start = line.len() - argument.trim_start().len();
+    let prefix = argument.trim_start().to_lowercase();
+    let candidates = names
+        .iter()
+        .filter(|name| name.to_lowercase().starts_with(&prefix))
+        .map(String::as_str)
+        .collect();
+
+    (start, candidates)
+}
+
+// Completes commands and node names in the editor
+struct ShellHelper {
+    names: Vec<String>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, candidates) = complete(&self.names, &line[..pos]);
+        let pairs = candidates
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.to_string(),
+                replacement: candidate.to_string(),
+            })
+            .collect();
+
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+// Runs the interactive shell until the user quits or closes the input
+pub fn run(
+    map: &DeltavMap,
+    format: &DeltavFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let names = map
+        .menu_tree()
+        .iter()
+        .filter(|node| node.id().is_some())
+        .map(|node| node.name().to_string())
+        .collect();
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper { names }));
+    let mut session = Session::default();
+
+    writeln!(
+        out,
+        "Type help for a list of commands, tab completes node names"
+    )?;
+    loop {
+        let line = match editor.readline("deltav> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        editor.add_history_entry(line.as_str())?;
+
+        match session.execute(map, format, &line) {
+            Ok(Outcome::Quit) => break,
+            Ok(Outcome::Print(message)) if message.is_empty() => {}
+            Ok(Outcome::Print(message)) => writeln!(out, "{message}")?,
+            Err(error) => writeln!(out, "error: {error}")?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{complete, Outcome, Session};
+    use deltav_calc::{DeltavFormat, DeltavMap};
+
+    fn test_map() -> DeltavMap {
+        DeltavMap::from_dsl(
+            "
+            [Test]
+            Node One
+            Node Two
+            Other
+            Node One -> Node Two : 900
+            Node Two -> Other : 80
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        let map = test_map();
+        let format = DeltavFormat::new();
+        let mut session = Session::default();
+        let mut run = |line| session.execute(&map, &format, line);
+
+        assert!(run("route").is_err());
+        assert_eq!(
+            run("from Node One"),
+            Ok(Outcome::Print(String::from("Start: Node One")))
+        );
+        assert!(run("to Test").is_err());
+        assert!(run("to Nothing").is_err());
+        assert!(run("to  Other ").is_ok());
+        assert!(run("budget 1000").is_ok());
+        assert_eq!(
+            run("route"),
+            Ok(Outcome::Print(String::from(
+                "Node One -> Node Two  900 m/s\n\
+                 Node Two -> Other      80 m/s\n\
+                 Total                 980 m/s\n\
+                 Budget 1,000 m/s: 20 m/s left"
+            )))
+        );
+        assert!(run("budget 500").is_ok());
+        let Ok(Outcome::Print(route)) = run("route") else {
+            panic!("The route wasn't printed");
+        };
+        assert!(route.ends_with("Budget 500 m/s: 480 m/s short"));
+        assert!(run("budget lots").is_err());
+        assert!(run("fly").is_err());
+        assert_eq!(run("quit"), Ok(Outcome::Quit));
+    }
+
+    #[test]
+    fn test_complete() {
+        let names: Vec<String> = ["Node One", "Node Two", "Other"].map(String::from).to_vec();
+
+        assert_eq!(complete(&names, "r"), (0, vec!["route"]));
+        assert_eq!(
+            complete(&names, "from node"),
+            (5, vec!["Node One", "Node Two"])
+        );
+        assert_eq!(complete(&names, "to  Oth"), (4, vec!["Other"]));
+        assert_eq!(complete(&names, "budget 1"), (8, vec![]));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::output::{write_csv, write_json, OutputFormat};
+use deltav_calc::DeltavMap;
+use std::error::Error;
+use std::io::Write;
+
+// Prints the menu tree. The table indents every level by two spaces and categories end with a
+// slash, JSON is the serialized menu tree and CSV has one row per entry with its depth
+pub fn run(
+    map: &DeltavMap,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let entries = map.menu_tree().flatten();
+
+    match output {
+        OutputFormat::Table => {
+            for (depth, name, id) in entries {
+                let suffix = if id.is_none() { "/" } else { "" };
+                writeln!(out, "{}{name}{suffix}", "  ".repeat(depth))?;
+            }
+        }
+        OutputFormat::Json => write_json(out, map.menu_tree())?,
+        OutputFormat::Csv => write_csv(
+            out,
+            ["depth", "name", "category"],
+            entries.into_iter().map(|(depth, name, id)| {
+                [
+                    depth.to_string(),
+                    name.to_string(),
+                    id.is_none().to_string(),
+                ]
+            }),
+        )?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use crate::output::OutputFormat;
+    use deltav_calc::DeltavMap;
+
+    #[test]
+    fn test_tree() {
+        let map = DeltavMap::from_dsl(
+            "
+            [System/Planet]
+            Surface
+            Orbit
+            Surface -> Orbit : 3000
+            ",
+        )
+        .unwrap();
+        let tree = |output| {
+            let mut out = Vec::new();
+            run(&map, output, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            tree(OutputFormat::Table),
+            "System/\n  Planet/\n    Surface\n    Orbit\n"
+        );
+        assert_eq!(
+            tree(OutputFormat::Csv),
+            "depth,name,category\n0,System,true\n1,Planet,true\n2,Surface,false\n2,Orbit,false\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&tree(OutputFormat::Json)).unwrap();
+        assert_eq!(json["MiddleNode"]["name"], "System");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::output::{write_csv, write_json, OutputFormat};
+use clap::Args;
+use deltav_calc::{DeltavMap, ValidationOptions};
+use serde::Serialize;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// The exit code for maps that were read but have problems
+const INVALID: u8 = 1;
+
+// The exit code for files that couldn't be read as a map
+const UNREADABLE: u8 = 2;
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// The map file to check
+    path: PathBuf,
+
+    /// Allows edges that cost zero or less
+    #[arg(long)]
+    allow_nonpositive_weights: bool,
+
+    /// Doesn't compare the content hash stored in the metadata
+    #[arg(long)]
+    ignore_content_hash: bool,
+}
+
+// Whether a map passed validation
+#[derive(Serialize, Clone, Copy, Eq, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Valid,
+    Invalid,
+    Unreadable,
+}
+
+// The result of checking a map, which is printed as JSON or CSV
+#[derive(Serialize, Debug)]
+struct Report {
+    path: String,
+    status: Status,
+    errors: Vec<String>,
+}
+
+// Checks the map and prints every problem. Exits with 1 if the map has problems and with 2 if it
+// couldn't be read at all
+pub fn run(
+    args: &ValidateArgs,
+    output: OutputFormat,
+    out: &mut impl Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let path = args.path.display().to_string();
+    let unchecked = ValidationOptions::new().skip_all(true);
+    let options = ValidationOptions::new()
+        .allow_nonpositive_weights(args.allow_nonpositive_weights)
+        .ignore_content_hash(args.ignore_content_hash);
+
+    let (map, report) = match DeltavMap::from_path_with_options(&args.path, &unchecked) {
+        Ok(map) => {
+            let errors: Vec<String> = map
+                .validation_errors(&options)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            let status = match errors.is_empty() {
+                true => Status::Valid,
+                false => Status::Invalid,
+            };
+            (
+                Some(map),
+                Report {
+                    path,
+                    status,
+                    errors,
+                },
+            )
+        }
+        Err(e) => {
+            let report = Report {
+                path,
+                status: Status::Unreadable,
+                errors: vec![e.to_string()],
+            };
+            (None, report)
+        }
+    };
+
+    match output {
+        OutputFormat::Json => write_json(out, &report)?,
+        OutputFormat::Csv => write_csv(
+            out,
+            ["path", "error"],
+            report
+                .errors
+                .iter()
+                .map(|error| [report.path.clone(), error.clone()]),
+        )?,
+        OutputFormat::Table => write_table(map.as_ref(), &report, out)?,
+    }
+
+    Ok(match report.status {
+        Status::Valid => ExitCode::SUCCESS,
+        Status::Invalid => ExitCode::from(INVALID),
+        Status::Unreadable => ExitCode::from(UNREADABLE),
+    })
+}
+
+// Writes the report for people, with a summary of the map if it is valid
+fn write_table(
+    map: Option<&DeltavMap>,
+    report: &Report,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let path = &report.path;
+    match (report.status, map) {
+        (Status::Valid, Some(map)) => {
+            let nodes = map.menu_tree().iter().filter(|node| node.id().is_some());
+            writeln!(
+                out,
+                "{path}: valid, {} nodes and {} edges",
+                nodes.count(),
+                map.edges().count()
+            )?;
+            return Ok(());
+        }
+        (Status::Unreadable, _) => writeln!(out, "{path}: couldn't be read")?,
+        _ => {
+            let problems = match report.errors.len() {
+                1 => "problem",
+                _ => "problems",
+            };
+            writeln!(out, "{path}: {} {problems}", report.errors.len())?;
+        }
+    }
+
+    for error in &report.errors {
+        writeln!(out, "  error: {error}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, ValidateArgs};
+    use crate::output::OutputFormat;
+    use std::path::PathBuf;
+    use std::process::ExitCode;
+
+    fn validate(path: &str) -> (ExitCode, String) {
+        validate_as(path, OutputFormat::Table)
+    }
+
+    fn validate_as(path: &str, output: OutputFormat) -> (ExitCode, String) {
+        let args = ValidateArgs {
+            path: PathBuf::from(path),
+            allow_nonpositive_weights: false,
+            ignore_content_hash: false,
+        };
+        let mut out = Vec::new();
+        let code = run(&args, output, &mut out).unwrap();
+
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_validate() {
+        let (code, out) = validate("../deltav_calc_lib/test_res/test.json");
+        assert_eq!(code, ExitCode::SUCCESS);
+        assert!(out.ends_with(": valid, 4 nodes and 3 edges\n"));
+
+        let (code, out) = validate("../deltav_calc_lib/test_res/duplicate_name.json");
+        assert_eq!(code, ExitCode::from(1));
+        assert!(out.contains(": 1 problem\n  error: There are multiple"));
+
+        let (code, out) = validate("missing.json");
+        assert_eq!(code, ExitCode::from(2));
+        assert!(out.starts_with("missing.json: couldn't be read\n  error: "));
+
+        let (_, out) = validate_as(
+            "../deltav_calc_lib/test_res/duplicate_name.json",
+            OutputFormat::Json,
+        );
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["status"], "invalid");
+        assert_eq!(json["errors"].as_array().unwrap().len(), 1);
+
+        let (_, out) = validate_as("missing.json", OutputFormat::Csv);
+        assert!(out.starts_with("path,error\nmissing.json,"));
+    }
+}
@@ -0,0 +1,207 @@
+//! An egui widget to select a node of a deltav map, so tools built with egui don't have to render
+//! the menu tree themselves
+//!
+//! # Example
+//! ```no_run
+//! use deltav_calc::DeltavMap;
+//! use deltav_calc_egui::NodeSelector;
+//!
+//! fn start_selector(ui: &mut egui::Ui, map: &DeltavMap, start: &mut Option<String>) {
+//!     let selector = NodeSelector::new(map.menu_tree()).selected(start.as_deref());
+//!     if let Some(node) = selector.show(ui) {
+//!         *start = Some(node);
+//!     }
+//! }
+//! ```
+
+use deltav_calc::{MenuNode, MenuTree, MAX_DEPTH};
+use egui::{CollapsingHeader, Id, Ui};
+use std::hash::Hash;
+
+/// Shows the categories of a [`MenuTree`] as collapsible headers with the nodes as selectable
+/// labels in them. The categories leading to the selected node start unfolded
+pub struct NodeSelector<'a> {
+    tree: &'a MenuTree,
+    selected: Option<&'a str>,
+    query: &'a str,
+    id_salt: Id,
+}
+
+impl<'a> NodeSelector<'a> {
+    /// Creates a selector for the nodes of the tree, usually the one of
+    /// [`DeltavMap::menu_tree`](deltav_calc::DeltavMap::menu_tree)
+    pub fn new(tree: &'a MenuTree) -> NodeSelector<'a> {
+        NodeSelector {
+            tree,
+            selected: None,
+            query: "",
+            id_salt: Id::new("deltav_calc_node_selector"),
+        }
+    }
+
+    /// Highlights the node, usually the one that was picked before
+    pub fn selected(mut self, node: Option<&'a str>) -> NodeSelector<'a> {
+        self.selected = node;
+        self
+    }
+
+    /// Lists only the nodes matching the query of a search field instead of the tree, the best
+    /// match first as ranked by [`MenuTree::fuzzy_search`]. An empty query shows the tree
+    pub fn search(mut self, query: &'a str) -> NodeSelector<'a> {
+        self.query = query;
+        self
+    }
+
+    /// Sets what the ids of the categories are derived from. Selectors for the same tree shown at
+    /// the same time need different ones, so their categories are folded separately
+    pub fn id_salt(mut self, salt: impl Hash) -> NodeSelector<'a> {
+        self.id_salt = Id::new(salt);
+        self
+    }
+
+    /// Shows the selector and returns the name of the node that was clicked, if any
+    pub fn show(self, ui: &mut Ui) -> Option<String> {
+        let mut picked = None;
+
+        ui.push_id(self.id_salt, |ui| {
+            if self.query.trim().is_empty() {
+                // The root is the map itself, so its children are shown at the top
+                let root = self.tree.root();
+                if root.is_category() {
+                    for child in root.children() {
+                        self.show_tree(ui, child, 1, &mut picked);
+                    }
+                } else {
+                    self.show_node(ui, root.name(), &mut picked);
+                }
+            } else {
+                let results = self.tree.fuzzy_search(self.query);
+                if results.is_empty() {
+                    ui.weak("No matching nodes");
+                }
+                for name in results {
+                    self.show_node(ui, name, &mut picked);
+                }
+            }
+        });
+
+        picked
+    }
+
+    // Shows a category at the depth with everything in it, or a node
+    fn show_tree(&self, ui: &mut Ui, tree: MenuNode, depth: usize, picked: &mut Option<String>) {
+        if !tree.is_category() {
+            self.show_node(ui, tree.name(), picked);
+            return;
+        }
+
+        let contains_selected = self
+            .selected
+            .is_some_and(|selected| tree.search(selected).is_ok());
+        CollapsingHeader::new(tree.name())
+            .default_open(contains_selected)
+            .show(ui, |ui| {
+                // The headers are nested recursively, so deeper categories only list their nodes
+                if depth >= MAX_DEPTH {
+                    for node in tree.iter().filter(|node| !node.is_category()) {
+                        self.show_node(ui, node.name(), picked);
+                    }
+                    return;
+                }
+                for child in tree.children() {
+                    self.show_tree(ui, child, depth + 1, picked);
+                }
+            });
+    }
+
+    // Shows a node that can be clicked to pick it
+    fn show_node(&self, ui: &mut Ui, name: &str, picked: &mut Option<String>) {
+        if ui
+            .selectable_label(self.selected == Some(name), name)
+            .clicked()
+        {
+            *picked = Some(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NodeSelector;
+    use deltav_calc::DeltavMap;
+    use egui::{CentralPanel, Context, Event, PointerButton, Pos2, RawInput};
+
+    // Shows the selector for one frame with the events and returns the picked node and where
+    // the label of the node was drawn
+    fn run(
+        ctx: &Context,
+        map: &DeltavMap,
+        query: &str,
+        events: Vec<Event>,
+        node: &str,
+    ) -> (Option<String>, Option<Pos2>) {
+        let mut picked = None;
+        let mut position = None;
+        let input = RawInput {
+            events,
+            ..RawInput::default()
+        };
+
+        let output = ctx.run(input, |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                picked = NodeSelector::new(map.menu_tree())
+                    .selected(Some("Mun Surface"))
+                    .search(query)
+                    .show(ui);
+            });
+        });
+        for shape in output.shapes {
+            if let egui::Shape::Text(text) = shape.shape {
+                if text.galley.text() == node {
+                    position = Some(text.pos);
+                }
+            }
+        }
+
+        (picked, position)
+    }
+
+    fn click(position: Pos2) -> Vec<Event> {
+        let button = |pressed| Event::PointerButton {
+            pos: position,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Default::default(),
+        };
+        vec![Event::PointerMoved(position), button(true), button(false)]
+    }
+
+    #[test]
+    fn test_node_selector() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Kerbol/Kerbin]
+            Kerbin Surface
+            Low Kerbin Orbit
+            Kerbin Surface -> Low Kerbin Orbit : 3400
+
+            [Kerbol/Mun]
+            Mun Surface
+            Low Kerbin Orbit -> Mun Surface : 1500
+            ",
+        )
+        .unwrap();
+        let ctx = Context::default();
+
+        // The category of the selected node is unfolded, the others aren't
+        let (picked, position) = run(&ctx, &map, "", vec![], "Mun Surface");
+        assert_eq!(picked, None);
+        assert!(position.is_some());
+        assert_eq!(run(&ctx, &map, "", vec![], "Kerbin Surface").1, None);
+
+        let (_, position) = run(&ctx, &map, "kerbin surf", vec![], "Kerbin Surface");
+        let position = position.unwrap() + egui::vec2(5.0, 5.0);
+        let (picked, _) = run(&ctx, &map, "kerbin surf", click(position), "Kerbin Surface");
+        assert_eq!(picked.as_deref(), Some("Kerbin Surface"));
+    }
+}
@@ -0,0 +1,23 @@
+fn main() {
+    // The C header is only generated for the C API
+    #[cfg(feature = "ffi")]
+    {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+
+        let mut config = cbindgen::Config {
+            language: cbindgen::Language::C,
+            include_guard: Some(String::from("DELTAV_CALC_H")),
+            ..Default::default()
+        };
+        // C enums share one namespace, so the variants are prefixed
+        config.enumeration.prefix_with_name = true;
+
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{dir}/src/ffi.rs"))
+            .generate()
+            .expect("Couldn't generate the C header")
+            .write_to_file(format!("{dir}/include/deltav_calc.h"));
+    }
+}
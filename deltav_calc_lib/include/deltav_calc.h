@@ -0,0 +1,99 @@
+#ifndef DELTAV_CALC_H
+#define DELTAV_CALC_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of a calculation
+ */
+typedef enum DeltavStatus {
+  /**
+   * The calculation succeeded
+   */
+  DeltavStatus_Ok = 0,
+  /**
+   * There is no path between the nodes
+   */
+  DeltavStatus_NoPath = 1,
+  /**
+   * One of the nodes doesn't exist
+   */
+  DeltavStatus_NoSuchNode = 2,
+  /**
+   * A pointer was null or a string wasn't valid UTF-8
+   */
+  DeltavStatus_InvalidArgument = 3,
+} DeltavStatus;
+
+/**
+ * A map handed out to C
+ */
+typedef struct DeltavCalcMap DeltavCalcMap;
+
+/**
+ * Called for every entry of the menu tree by [`deltav_map_for_each_node`]. The name is only
+ * valid during the call
+ */
+typedef void (*DeltavNodeCallback)(const char *name,
+                                   uintptr_t depth,
+                                   bool is_category,
+                                   void *user_data);
+
+/**
+ * Returns the stock map
+ */
+struct DeltavCalcMap *deltav_map_new_stock(void);
+
+/**
+ * Reads the map file at the path. Returns null if the map can't be loaded
+ *
+ * # Safety
+ * The path has to be null or a valid NUL-terminated string
+ */
+struct DeltavCalcMap *deltav_map_new_from_path(const char *path);
+
+/**
+ * Reads a map from the text of a JSON file. Returns null if the map can't be loaded
+ *
+ * # Safety
+ * The text has to be null or a valid NUL-terminated string
+ */
+struct DeltavCalcMap *deltav_map_new_from_json(const char *text);
+
+/**
+ * Frees a map. Passing null does nothing
+ *
+ * # Safety
+ * The map has to be null or a pointer returned by one of the `deltav_map_new_*` functions that
+ * hasn't been freed yet
+ */
+void deltav_map_free(struct DeltavCalcMap *map);
+
+/**
+ * Calculates the delta-v required to get from the start to the end and writes it to `delta_v`
+ * if the status is [`DeltavStatus::Ok`]
+ *
+ * # Safety
+ * The map has to be null or a valid map, the names null or valid NUL-terminated strings and
+ * `delta_v` null or valid for writes
+ */
+enum DeltavStatus deltav_map_calculate_delta_v(const struct DeltavCalcMap *map,
+                                               const char *start,
+                                               const char *end,
+                                               int64_t *delta_v);
+
+/**
+ * Calls the callback for every category and node of the menu tree in document order, together
+ * with its depth, which is 0 for the root
+ *
+ * # Safety
+ * The map has to be null or a valid map. The user data is passed to the callback as it is
+ */
+enum DeltavStatus deltav_map_for_each_node(const struct DeltavCalcMap *map,
+                                           DeltavNodeCallback callback,
+                                           void *user_data);
+
+#endif  /* DELTAV_CALC_H */
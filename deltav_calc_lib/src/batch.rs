@@ -0,0 +1,58 @@
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, NodePayload, Route};
+use alloc::vec::Vec;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+impl<N: NodePayload + Sync> DeltavMap<N> {
+    /// Calculates the cheapest route for every pair of start and end, for example to generate a
+    /// cheatsheet. The results are in the order of the queries. With the `parallel` feature the
+    /// routes are calculated on all cores
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{CalcOptions, DeltavMap};
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let queries = [
+    ///     ("Kerbin Surface", "Mun Surface"),
+    ///     ("Kerbin Surface", "Minmus Surface"),
+    /// ];
+    /// for result in stock_map.calculate_routes(&queries, &CalcOptions::new()) {
+    ///     println!("{:?}", result.unwrap().map(|route| route.delta_v()));
+    /// }
+    /// ```
+    pub fn calculate_routes(
+        &self,
+        queries: &[(&str, &str)],
+        options: &CalcOptions,
+    ) -> Vec<Result<Option<Route>, NoSuchNodeError>> {
+        #[cfg(feature = "parallel")]
+        let queries = queries.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let queries = queries.iter();
+
+        queries
+            .map(|(start, end)| self.calculate_route(start, end, options))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::CalcOptions;
+
+    #[test]
+    fn test_calculate_routes() {
+        let test_map = get_test_map();
+        let queries = [("Node1", "Node4"), ("Node3", "Node2"), ("Node1", "Node5")];
+        let results = test_map.calculate_routes(&queries, &CalcOptions::new());
+
+        assert_eq!(
+            results[0].as_ref().unwrap().as_ref().unwrap().delta_v(),
+            1030
+        );
+        assert_eq!(results[1].as_ref().unwrap().as_ref().unwrap().delta_v(), 80);
+        assert!(results[2].is_err());
+    }
+}
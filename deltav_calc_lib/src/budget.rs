@@ -0,0 +1,203 @@
+use crate::{CalcOptions, DeltavMap, Leg, NoSuchNodeError, NodePayload, Route};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The deltav a craft has left while it flies a mission. It starts with the total deltav of the
+/// craft at a node and is decremented as legs are flown
+///
+/// # Example
+/// ```
+/// use deltav_calc::{Budget, CalcOptions, DeltavMap};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let options = CalcOptions::new().aerobrake(true);
+/// let mut budget = Budget::new("Low Kerbin Orbit (80km)", 2500).options(options.clone());
+///
+/// let route = stock_map
+///     .calculate_route("Low Kerbin Orbit (80km)", "Low Mun Orbit (14km)", &options)
+///     .unwrap()
+///     .unwrap();
+/// budget.fly_route(&route);
+///
+/// assert_eq!(budget.position(), "Low Mun Orbit (14km)");
+/// assert!(budget.can_still_reach(&stock_map, "Kerbin Surface").unwrap());
+/// assert!(!budget.can_still_reach(&stock_map, "Duna Surface").unwrap());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Budget {
+    pub(crate) total: i64,
+    pub(crate) spent: i64,
+    pub(crate) position: String,
+    pub(crate) options: CalcOptions,
+}
+
+impl Budget {
+    /// Creates a budget for a craft at the node with the given total deltav
+    pub fn new(position: &str, delta_v: i64) -> Budget {
+        Budget {
+            total: delta_v,
+            spent: 0,
+            position: position.to_string(),
+            options: CalcOptions::new(),
+        }
+    }
+
+    /// Sets the options the routes of the queries are calculated with. Their margin is added to
+    /// the cost of a route before it is compared with the remaining deltav
+    pub fn options(mut self, options: CalcOptions) -> Budget {
+        self.options = options;
+        self
+    }
+
+    /// The deltav the craft started with
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    /// The deltav spent so far
+    pub fn spent(&self) -> i64 {
+        self.spent
+    }
+
+    /// The deltav the craft has left. It is negative if more was spent than the craft had
+    pub fn remaining(&self) -> i64 {
+        self.total - self.spent
+    }
+
+    /// The node the craft is at
+    pub fn position(&self) -> &str {
+        &self.position
+    }
+
+    /// Spends deltav without moving the craft, for example on corrections or docking
+    pub fn spend(&mut self, delta_v: i64) {
+        self.spent += delta_v;
+    }
+
+    /// Flies the leg, spending its deltav and moving the craft to its end
+    pub fn fly(&mut self, leg: &Leg) {
+        self.spent += leg.delta_v as i64;
+        self.position = leg.to.clone();
+    }
+
+    /// Flies all legs of the route
+    pub fn fly_route(&mut self, route: &Route) {
+        for leg in &route.legs {
+            self.fly(leg);
+        }
+    }
+
+    /// Moves the craft to the node and spends what it actually cost, for example as read from
+    /// the game after the burns
+    pub fn arrive(&mut self, node: &str, spent: i64) {
+        self.spent += spent;
+        self.position = node.to_string();
+    }
+
+    /// The deltav including the margin the craft needs to get from its position to the node.
+    /// Returns `None` if there is no route
+    ///
+    /// Returns a [`NoSuchNodeError`] if the position or the node aren't valid nodes
+    pub fn cost_to<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        node: &str,
+    ) -> Result<Option<i64>, NoSuchNodeError> {
+        let route = map.calculate_route(&self.position, node, &self.options)?;
+        Ok(route.map(|route| self.options.apply_margin(route.delta_v())))
+    }
+
+    /// Checks if the remaining deltav is enough to get from the position of the craft to the node
+    ///
+    /// Returns a [`NoSuchNodeError`] if the position or the node aren't valid nodes
+    pub fn can_still_reach<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        node: &str,
+    ) -> Result<bool, NoSuchNodeError> {
+        let cost = self.cost_to(map, node)?;
+        Ok(cost.is_some_and(|cost| cost <= self.remaining()))
+    }
+
+    /// All nodes of the map the craft can still reach, with what it costs to get there
+    ///
+    /// Returns a [`NoSuchNodeError`] if the position isn't a valid node
+    pub fn reachable_nodes<'a, N: NodePayload>(
+        &self,
+        map: &'a DeltavMap<N>,
+    ) -> Result<Vec<(&'a str, i64)>, NoSuchNodeError> {
+        map.menu_tree().search(&self.position)?;
+
+        let mut reachable = Vec::new();
+        for node in map.menu_tree().iter().filter(|node| node.id().is_some()) {
+            if let Some(cost) = self.cost_to(map, node.name())? {
+                if cost <= self.remaining() {
+                    reachable.push((node.name(), cost));
+                }
+            }
+        }
+
+        Ok(reachable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Budget, CalcOptions, DeltavMap};
+
+    fn get_map() -> DeltavMap {
+        DeltavMap::from_dsl(
+            "
+            [Test]
+            Surface
+            Orbit
+            Moon Orbit
+            Moon
+            Surface -> Orbit : 3400
+            Orbit -> Moon Orbit : 1000
+            Moon Orbit -> Moon : 600
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_budget() {
+        let map = get_map();
+        let mut budget = Budget::new("Surface", 5000);
+        assert!(budget.can_still_reach(&map, "Moon").unwrap());
+
+        let route = map
+            .calculate_route("Surface", "Orbit", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        budget.fly_route(&route);
+        assert_eq!(budget.position(), "Orbit");
+        assert_eq!(budget.remaining(), 1600);
+        assert_eq!(budget.cost_to(&map, "Moon").unwrap(), Some(1600));
+        assert!(budget.can_still_reach(&map, "Moon").unwrap());
+
+        budget.spend(50);
+        assert_eq!(budget.spent(), 3450);
+        assert!(!budget.can_still_reach(&map, "Moon").unwrap());
+        assert_eq!(
+            budget.reachable_nodes(&map).unwrap(),
+            vec![("Orbit", 0), ("Moon Orbit", 1000)]
+        );
+
+        budget.arrive("Moon Orbit", 1100);
+        assert_eq!(budget.remaining(), 450);
+        assert!(budget.can_still_reach(&map, "Surface").is_ok());
+        assert!(budget.can_still_reach(&map, "Nowhere").is_err());
+    }
+
+    #[test]
+    fn test_margin() {
+        let map = get_map();
+        let budget = Budget::new("Orbit", 1600).options(CalcOptions::new().margin(10));
+
+        assert_eq!(budget.cost_to(&map, "Moon").unwrap(), Some(1760));
+        assert!(!budget.can_still_reach(&map, "Moon").unwrap());
+        assert_eq!(budget.total(), 1600);
+    }
+}
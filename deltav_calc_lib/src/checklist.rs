@@ -0,0 +1,239 @@
+use crate::{CalcOptions, Route};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// One maneuver of a [`Checklist`], flying one leg of the route
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Maneuver {
+    pub(crate) step: usize,
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) delta_v: i64,
+    pub(crate) budget: i64,
+    pub(crate) aerobrake: bool,
+    pub(crate) notes: Vec<String>,
+}
+
+impl Maneuver {
+    /// The number of the maneuver, starting at 1
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// The name of the node the maneuver starts at
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// The name of the node the maneuver ends at
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// The deltav the maneuver requires without any margin
+    pub fn delta_v(&self) -> i64 {
+        self.delta_v
+    }
+
+    /// The deltav to budget for the maneuver, including the margin
+    pub fn budget(&self) -> i64 {
+        self.budget
+    }
+
+    /// Checks if the maneuver relies on aerobraking
+    pub fn aerobrake(&self) -> bool {
+        self.aerobrake
+    }
+
+    /// Things to keep in mind while flying the maneuver, like aerobraking and the hazards of the
+    /// leg
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+}
+
+/// The maneuvers to fly a route one after another with the deltav to budget for each of them.
+/// It serializes to JSON for flight planning tools and prints as a numbered list
+///
+/// # Example
+/// ```
+/// use deltav_calc::{CalcOptions, DeltavMap};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let options = CalcOptions::new().aerobrake(true).margin(10);
+/// let route = stock_map
+///     .calculate_route("Kerbin Surface", "Duna Surface", &options)
+///     .unwrap()
+///     .unwrap();
+///
+/// let checklist = route.checklist(&options);
+/// println!("{checklist}");
+/// assert_eq!(checklist.maneuvers().len(), route.legs().len());
+/// ```
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Checklist {
+    pub(crate) start: String,
+    pub(crate) end: String,
+    pub(crate) margin: u32,
+    pub(crate) delta_v: i64,
+    pub(crate) budget: i64,
+    pub(crate) maneuvers: Vec<Maneuver>,
+}
+
+impl Checklist {
+    /// The name of the node the route starts at
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    /// The name of the node the route ends at
+    pub fn end(&self) -> &str {
+        &self.end
+    }
+
+    /// The safety margin in percent that was added to every budget
+    pub fn margin_percent(&self) -> u32 {
+        self.margin
+    }
+
+    /// The deltav the whole route requires without any margin
+    pub fn delta_v(&self) -> i64 {
+        self.delta_v
+    }
+
+    /// The deltav to budget for the whole route. The margin is added to the total, so it can be
+    /// a bit lower than the sum of the budgets of the maneuvers, which are rounded one by one
+    pub fn budget(&self) -> i64 {
+        self.budget
+    }
+
+    /// The maneuvers in the order they are flown
+    pub fn maneuvers(&self) -> &[Maneuver] {
+        &self.maneuvers
+    }
+}
+
+impl Display for Checklist {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for maneuver in &self.maneuvers {
+            writeln!(
+                f,
+                "{}. {} -> {}: {} m/s (budget {} m/s)",
+                maneuver.step, maneuver.from, maneuver.to, maneuver.delta_v, maneuver.budget
+            )?;
+            for note in &maneuver.notes {
+                writeln!(f, "   - {note}")?;
+            }
+        }
+        write!(
+            f,
+            "Total: {} m/s (budget {} m/s with a {}% margin)",
+            self.delta_v, self.budget, self.margin
+        )
+    }
+}
+
+impl Route {
+    /// Turns the route into a checklist of maneuvers with the margin of the options added to
+    /// every budget
+    pub fn checklist(&self, options: &CalcOptions) -> Checklist {
+        let maneuvers = self
+            .legs
+            .iter()
+            .enumerate()
+            .map(|(index, leg)| {
+                let mut notes = Vec::new();
+                if leg.aerobrake {
+                    notes.push(String::from(
+                        "Aerobrake to shed the rest of the velocity, the budget only covers the burns",
+                    ));
+                }
+                notes.extend(
+                    self.warnings
+                        .iter()
+                        .filter(|warning| warning.leg == index)
+                        .map(|warning| warning.kind.to_string()),
+                );
+
+                Maneuver {
+                    step: index + 1,
+                    from: leg.from.clone(),
+                    to: leg.to.clone(),
+                    delta_v: leg.delta_v as i64,
+                    budget: options.apply_margin(leg.delta_v as i64),
+                    aerobrake: leg.aerobrake,
+                    notes,
+                }
+            })
+            .collect();
+
+        Checklist {
+            start: self.start().to_string(),
+            end: self.end().to_string(),
+            margin: options.margin_percent(),
+            delta_v: self.delta_v(),
+            budget: options.apply_margin(self.delta_v()),
+            maneuvers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CalcOptions, DeltavMap};
+
+    fn get_map() -> DeltavMap {
+        DeltavMap::from_dsl(
+            "
+            [Kerbol/Kerbin]
+            Surface
+            Orbit
+            Surface -> Orbit : 3400
+
+            [Kerbol/Duna]
+            Duna Surface
+            Orbit -> Duna Surface : 1450 aerobrake=100 tags=atmosphere
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_checklist() {
+        let options = CalcOptions::new().aerobrake(true).margin(10);
+        let route = get_map()
+            .calculate_route("Surface", "Duna Surface", &options)
+            .unwrap()
+            .unwrap();
+        let checklist = route.checklist(&options);
+
+        assert_eq!(checklist.start(), "Surface");
+        assert_eq!(checklist.end(), "Duna Surface");
+        assert_eq!(checklist.margin_percent(), 10);
+
+        let maneuvers = checklist.maneuvers();
+        assert_eq!(maneuvers.len(), 2);
+        assert_eq!(maneuvers[0].step(), 1);
+        assert_eq!(maneuvers[0].budget(), 3740);
+        assert!(maneuvers[0].notes().is_empty());
+        assert_eq!(maneuvers[1].to(), "Duna Surface");
+        assert_eq!(maneuvers[1].delta_v(), 100);
+        assert!(maneuvers[1].aerobrake());
+        assert_eq!(
+            maneuvers[1].notes()[1],
+            "The route enters an atmosphere, bring parachutes"
+        );
+        assert_eq!(
+            checklist.budget(),
+            options.apply_margin(checklist.delta_v())
+        );
+
+        let json = serde_json::to_value(&checklist).unwrap();
+        assert_eq!(json["maneuvers"][0]["budget"], 3740);
+        assert!(checklist.to_string().starts_with(
+            "1. Surface -> Orbit: 3400 m/s (budget 3740 m/s)\n2. Orbit -> Duna Surface: "
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, Route};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Several named maps that answer the same queries together, for example to compare the stock
+/// system with a rescaled one
+///
+/// # Example
+/// ```
+/// use deltav_calc::{DeltavMap, MapCollection};
+///
+/// let mut maps = MapCollection::new();
+/// maps.insert("Stock", DeltavMap::new_stock());
+/// maps.insert("2.5x", DeltavMap::new_stock().scaled(2.5));
+///
+/// for (name, delta_v) in maps.calculate_delta_v("Kerbin Surface", "Mun Surface") {
+///     println!("{name}: {:?}", delta_v);
+/// }
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct MapCollection {
+    maps: Vec<(String, DeltavMap)>,
+}
+
+impl MapCollection {
+    /// Creates an empty collection
+    pub fn new() -> MapCollection {
+        MapCollection::default()
+    }
+
+    /// Adds the map. A map that was added earlier with the same name is replaced, but keeps its
+    /// position
+    pub fn insert(&mut self, name: &str, map: DeltavMap) {
+        match self.maps.iter().position(|(other, _)| other == name) {
+            Some(position) => self.maps[position].1 = map,
+            None => self.maps.push((name.to_string(), map)),
+        }
+    }
+
+    /// Removes the map with the given name and returns it, if there is one
+    pub fn remove(&mut self, name: &str) -> Option<DeltavMap> {
+        let position = self.maps.iter().position(|(other, _)| other == name)?;
+
+        Some(self.maps.remove(position).1)
+    }
+
+    /// Returns the map with the given name, if there is one
+    pub fn get(&self, name: &str) -> Option<&DeltavMap> {
+        self.maps
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, map)| map)
+    }
+
+    /// The names of all maps in the order they were added
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.maps.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// All maps together with their names in the order they were added
+    pub fn maps(&self) -> impl Iterator<Item = (&str, &DeltavMap)> {
+        self.maps.iter().map(|(name, map)| (name.as_str(), map))
+    }
+
+    /// The number of maps
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
+    /// Checks if the collection contains no maps
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Calculates the deltav required to get from the start to the end on every map, see
+    /// [`DeltavMap::calculate_delta_v`]
+    pub fn calculate_delta_v(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Vec<(&str, Result<Option<i64>, NoSuchNodeError>)> {
+        self.maps()
+            .map(|(name, map)| (name, map.calculate_delta_v(start, end)))
+            .collect()
+    }
+
+    /// Calculates the cheapest route from the start to the end on every map, see
+    /// [`DeltavMap::calculate_route`]
+    pub fn calculate_route(
+        &self,
+        start: &str,
+        end: &str,
+        options: &CalcOptions,
+    ) -> Vec<(&str, Result<Option<Route>, NoSuchNodeError>)> {
+        self.maps()
+            .map(|(name, map)| (name, map.calculate_route(start, end, options)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, MapCollection};
+
+    #[test]
+    fn test_collection() {
+        let mut maps = MapCollection::new();
+        maps.insert("Test", DeltavMap::new_stock());
+        maps.insert("Scaled", get_test_map().scaled(2.0));
+        maps.insert("Test", get_test_map());
+
+        assert_eq!(maps.names().collect::<Vec<_>>(), ["Test", "Scaled"]);
+        assert_eq!(
+            maps.calculate_delta_v("Node1", "Node2"),
+            vec![("Test", Ok(Some(900))), ("Scaled", Ok(Some(1800)))]
+        );
+
+        maps.insert("Stock", DeltavMap::new_stock());
+        let results = maps.calculate_delta_v("Node1", "Node2");
+        assert!(results[2].1.is_err());
+
+        assert!(maps.remove("Stock").is_some());
+        assert_eq!(maps.len(), 2);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::menutree::PathError;
+use crate::{DeltavMap, EdgeInfo, MenuTree, Metadata, TransferWindow, ValidationError, MAX_DEPTH};
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+/// This error is raised when a map can't be read from or written to CSV
+#[derive(Debug)]
+pub enum CsvError {
+    /// The CSV data couldn't be read or written
+    Csv(csv::Error),
+
+    /// A node has no category. Every node has to be put into at least the root category
+    NoCategory(String),
+
+    /// The category of a node doesn't start with the same root category as the first node
+    DifferentRoot { node: String, root: String },
+
+    /// The category of a node has more than [`MAX_DEPTH`](crate::MAX_DEPTH) levels
+    TooDeep(String),
+
+    /// There are two nodes with the same name
+    DuplicateNode(String),
+
+    /// An edge refers to a node that isn't in the nodes file
+    UnknownNode(String),
+
+    /// An edge has only one of the phase angle and the synodic period
+    IncompleteTransferWindow { from: String, to: String },
+
+    /// The map that was read isn't valid, for example because an edge costs zero or less
+    Invalid(ValidationError),
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvError::Csv(e) => write!(f, "{e}"),
+            CsvError::NoCategory(node) => write!(f, "The node \"{node}\" has no category"),
+            CsvError::DifferentRoot { node, root } => write!(
+                f,
+                "The category of the node \"{node}\" doesn't start with \"{root}\""
+            ),
+            CsvError::TooDeep(node) => write!(
+                f,
+                "The category of the node \"{node}\" is nested more than {MAX_DEPTH} levels deep"
+            ),
+            CsvError::DuplicateNode(node) => {
+                write!(f, "There are multiple nodes with the name \"{node}\"")
+            }
+            CsvError::UnknownNode(node) => {
+                write!(f, "There is no node with the name \"{node}\" in the nodes")
+            }
+            CsvError::IncompleteTransferWindow { from, to } => write!(
+                f,
+                "The transfer window from \"{from}\" to \"{to}\" needs both a phase angle and a synodic period"
+            ),
+            CsvError::Invalid(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Csv(e) => Some(e),
+            CsvError::Invalid(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(e: csv::Error) -> Self {
+        CsvError::Csv(e)
+    }
+}
+
+impl From<ValidationError> for CsvError {
+    fn from(e: ValidationError) -> Self {
+        CsvError::Invalid(e)
+    }
+}
+
+// A row of the nodes file
+#[derive(Deserialize, Serialize)]
+struct NodeRow {
+    name: String,
+    category: String,
+    #[serde(default)]
+    tags: String,
+}
+
+// A row of the edges file
+#[derive(Deserialize, Serialize)]
+struct EdgeRow {
+    from: String,
+    to: String,
+    cost: i32,
+    #[serde(default)]
+    min: Option<i32>,
+    #[serde(default)]
+    max: Option<i32>,
+    #[serde(default)]
+    aerobrake: Option<i32>,
+    #[serde(default)]
+    low_thrust: Option<f64>,
+    #[serde(default)]
+    time: Option<u64>,
+    #[serde(default)]
+    phase_angle: Option<f64>,
+    #[serde(default)]
+    synodic_period: Option<f64>,
+    #[serde(default)]
+    ejection_angle: Option<f64>,
+    #[serde(default)]
+    tags: String,
+}
+
+// Lists are stored in a single cell, separated by semicolons
+fn split_list(cell: &str) -> impl Iterator<Item = &str> {
+    cell.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn join_list<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    items.collect::<Vec<_>>().join(";")
+}
+
+impl DeltavMap {
+    /// Reads a map from a CSV file of nodes and a CSV file of edges and
+    /// [validates](DeltavMap::validate) it
+    ///
+    /// The nodes file has the columns `name`, `category` and `tags`. The category is the path of
+    /// categories the node is put into, separated by slashes, like `Kerbol System/Kerbin/Mun`.
+    /// All nodes have to share the same root category. The edges file has the columns `from`,
+    /// `to` and `cost`, and optionally `min`, `max`, `aerobrake`, `low_thrust`, `time`,
+    /// `phase_angle`, `synodic_period`, `ejection_angle` and `tags`, see [`EdgeInfo`]. Tags are
+    /// separated by semicolons
+    ///
+    /// # Example
+    /// ```csv
+    /// name,category,tags
+    /// Kerbin Surface,Kerbol System/Kerbin,surface;atmosphere
+    /// Low Kerbin Orbit (80km),Kerbol System/Kerbin,
+    /// ```
+    /// ```csv
+    /// from,to,cost,aerobrake
+    /// Low Kerbin Orbit (80km),Kerbin Surface,3400,0
+    /// ```
+    pub fn from_csv(nodes: impl Read, edges: impl Read) -> Result<DeltavMap, CsvError> {
+        let mut graph: UnGraph<Arc<str>, EdgeInfo> = UnGraph::new_undirected();
+        let mut menu_tree = None;
+        let mut indices: BTreeMap<String, NodeIndex> = BTreeMap::new();
+        let mut tags: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for row in csv::Reader::from_reader(nodes).deserialize() {
+            let row: NodeRow = row?;
+            if indices.contains_key(&row.name) {
+                return Err(CsvError::DuplicateNode(row.name));
+            }
+
+            let name: Arc<str> = Arc::from(row.name.as_str());
+            let index = graph.add_node(name.clone());
+            let node = MenuTree::from_node(name, Some(index.into()));
+            match MenuTree::insert_at_path(&mut menu_tree, &row.category, node) {
+                Ok(()) => {}
+                Err(PathError::Empty) => return Err(CsvError::NoCategory(row.name)),
+                Err(PathError::TooDeep) => return Err(CsvError::TooDeep(row.name)),
+                Err(PathError::DifferentRoot(root)) => {
+                    return Err(CsvError::DifferentRoot {
+                        node: row.name,
+                        root,
+                    })
+                }
+            }
+
+            let node_tags: BTreeSet<String> = split_list(&row.tags).map(String::from).collect();
+            if !node_tags.is_empty() {
+                tags.insert(row.name.clone(), node_tags);
+            }
+            indices.insert(row.name, index);
+        }
+
+        for row in csv::Reader::from_reader(edges).deserialize() {
+            let row: EdgeRow = row?;
+            let index = |name: &String| {
+                indices
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| CsvError::UnknownNode(name.clone()))
+            };
+            let from = index(&row.from)?;
+            let to = index(&row.to)?;
+
+            let window = match (row.phase_angle, row.synodic_period) {
+                (None, None) => None,
+                (Some(phase_angle), Some(synodic_period)) => {
+                    let window = TransferWindow::new(phase_angle, synodic_period);
+                    Some(match row.ejection_angle {
+                        None => window,
+                        Some(angle) => window.with_ejection_angle(angle),
+                    })
+                }
+                _ => {
+                    return Err(CsvError::IncompleteTransferWindow {
+                        from: row.from,
+                        to: row.to,
+                    })
+                }
+            };
+            let edge = EdgeInfo {
+                cost: row.cost,
+                min: row.min,
+                max: row.max,
+                aerobrake: row.aerobrake,
+                low_thrust: row.low_thrust,
+                time: row.time,
+                window,
+                tags: split_list(&row.tags).map(String::from).collect(),
+            };
+
+            graph.add_edge(from, to, edge);
+        }
+
+        let menu_tree = match menu_tree {
+            Some(menu_tree) => menu_tree,
+            None => MenuTree::new_category(""),
+        };
+
+        let map = DeltavMap {
+            menu_tree,
+            graph,
+            tags,
+            metadata: Metadata::default(),
+        };
+        map.validate()?;
+
+        Ok(map)
+    }
+
+    /// Writes the map as a CSV file of nodes and a CSV file of edges in the format read by
+    /// [`from_csv`](DeltavMap::from_csv). The metadata of the map and tags of categories aren't
+    /// written
+    pub fn to_csv(&self, nodes: impl Write, edges: impl Write) -> Result<(), CsvError> {
+        let mut writer = csv::Writer::from_writer(nodes);
+        for (path, node) in self.menu_tree.end_nodes() {
+            writer.serialize(NodeRow {
+                name: node.name().to_string(),
+                category: path.join("/"),
+                tags: join_list(
+                    self.tags
+                        .get(node.name())
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str),
+                ),
+            })?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+
+        let mut writer = csv::Writer::from_writer(edges);
+        for edge in self.graph.edge_references() {
+            let info = edge.weight();
+            let window = info.transfer_window();
+
+            writer.serialize(EdgeRow {
+                from: self.graph[edge.source()].to_string(),
+                to: self.graph[edge.target()].to_string(),
+                cost: info.cost,
+                min: info.min,
+                max: info.max,
+                aerobrake: info.aerobrake,
+                low_thrust: info.low_thrust,
+                time: info.time,
+                phase_angle: window.map(TransferWindow::phase_angle),
+                synodic_period: window.map(TransferWindow::synodic_period),
+                ejection_angle: window.and_then(TransferWindow::ejection_angle),
+                tags: join_list(info.tags()),
+            })?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{CsvError, DeltavMap, Metadata, ValidationError};
+    use std::fs::File;
+
+    #[test]
+    fn test_from_csv() {
+        let nodes = File::open("test_res/nodes.csv").unwrap();
+        let edges = File::open("test_res/edges.csv").unwrap();
+        let map = DeltavMap::from_csv(nodes, edges).unwrap();
+
+        let mut expected = get_test_map();
+        expected.metadata = Metadata::default();
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        get_test_map().to_csv(&mut nodes, &mut edges).unwrap();
+
+        assert_eq!(
+            String::from_utf8(nodes).unwrap(),
+            std::fs::read_to_string("test_res/nodes.csv").unwrap()
+        );
+        assert_eq!(
+            String::from_utf8(edges).unwrap(),
+            std::fs::read_to_string("test_res/edges.csv").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let nodes = "name,category,tags\nNode1,Category1,\nNode2,,\n";
+        let result = DeltavMap::from_csv(nodes.as_bytes(), "from,to,cost\n".as_bytes());
+        assert!(matches!(result, Err(CsvError::NoCategory(node)) if node == "Node2"));
+
+        let nodes = "name,category,tags\nNode1,Category1,\nNode2,Category2,\n";
+        let result = DeltavMap::from_csv(nodes.as_bytes(), "from,to,cost\n".as_bytes());
+        assert!(matches!(result, Err(CsvError::DifferentRoot { .. })));
+
+        let nodes = "name,category,tags\nNode1,Category1,\n";
+        let edges = "from,to,cost\nNode1,Node2,900\n";
+        let result = DeltavMap::from_csv(nodes.as_bytes(), edges.as_bytes());
+        assert!(matches!(result, Err(CsvError::UnknownNode(node)) if node == "Node2"));
+
+        let nodes = format!(
+            "name,category,tags\nNode1,{},\n",
+            ["Category1"; 33].join("/")
+        );
+        let result = DeltavMap::from_csv(nodes.as_bytes(), "from,to,cost\n".as_bytes());
+        assert!(matches!(result, Err(CsvError::TooDeep(node)) if node == "Node1"));
+
+        let nodes = "name,category,tags\nNode1,Category1,\nNode2,Category1,\n";
+        let edges = "from,to,cost\nNode1,Node2,-900\n";
+        let result = DeltavMap::from_csv(nodes.as_bytes(), edges.as_bytes());
+        assert!(matches!(
+            result,
+            Err(CsvError::Invalid(ValidationError::InvalidWeight { .. }))
+        ));
+    }
+}
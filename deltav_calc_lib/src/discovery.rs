@@ -0,0 +1,106 @@
+use crate::{DeltavMap, LoadError, MapFormat};
+use std::path::{Path, PathBuf};
+
+/// A map file found in a maps directory
+#[derive(Debug)]
+pub struct InstalledMap {
+    path: PathBuf,
+    map: Result<DeltavMap, LoadError>,
+}
+
+impl InstalledMap {
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The name of the map, which is the name of the file without the extension
+    pub fn name(&self) -> &str {
+        self.path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    }
+
+    /// The loaded map, or the reason it couldn't be loaded
+    pub fn map(&self) -> Result<&DeltavMap, &LoadError> {
+        self.map.as_ref()
+    }
+
+    /// Takes the loaded map out of the entry
+    pub fn into_map(self) -> Result<DeltavMap, LoadError> {
+        self.map
+    }
+}
+
+/// The directory the user's maps are installed in, like `~/.config/deltav_calc/maps` on Linux.
+///
+/// Returns `None` if the config directory of the platform can't be determined
+pub fn maps_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("deltav_calc").join("maps"))
+}
+
+/// Loads all maps installed in the [`maps_dir`]. If the directory doesn't exist, no maps are
+/// returned
+pub fn installed_maps() -> Vec<InstalledMap> {
+    match maps_dir() {
+        None => vec![],
+        Some(dir) => maps_in(&dir),
+    }
+}
+
+/// Loads all files in the directory whose format can be detected from the extension, sorted by
+/// their path. Maps that can't be loaded are returned with the error
+pub fn maps_in(dir: &Path) -> Vec<InstalledMap> {
+    map_files_in(dir)
+        .into_iter()
+        .map(|path| InstalledMap {
+            map: DeltavMap::from_path(&path),
+            path,
+        })
+        .collect()
+}
+
+// Lists the files in the directory whose format can be detected from the extension, sorted by
+// their path
+pub(crate) fn map_files_in(dir: &Path) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && MapFormat::from_path(path).is_some())
+        .collect();
+    paths.sort();
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::maps_in;
+    use crate::tests::get_test_map;
+    use std::path::Path;
+
+    #[test]
+    fn test_maps_in() {
+        let dir = std::env::temp_dir().join("deltav_calc_test_maps_in");
+        std::fs::create_dir_all(&dir).unwrap();
+        get_test_map().save(&dir.join("test.json")).unwrap();
+        std::fs::write(dir.join("broken.json"), "{}").unwrap();
+        std::fs::write(dir.join("notes.txt"), "Not a map").unwrap();
+
+        let maps = maps_in(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = maps.iter().map(|map| map.name()).collect();
+        assert_eq!(names, vec!["broken", "test"]);
+        assert!(maps[0].map().is_err());
+        assert_eq!(maps[1].map().unwrap(), &get_test_map());
+
+        assert!(maps_in(Path::new("test_res/missing")).is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+use crate::menutree::{depth_error, PathError};
+use crate::{DeltavMap, EdgeInfo, MenuTree, Metadata, TransferWindow, ValidationError};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Write};
+use core::str::FromStr;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// This error is raised when a map written in the text format can't be parsed
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DslError {
+    line: usize,
+    reason: String,
+}
+
+impl DslError {
+    fn new(line: usize, reason: impl Into<String>) -> DslError {
+        DslError {
+            line,
+            reason: reason.into(),
+        }
+    }
+
+    /// The number of the line the error occurred in, starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// What is wrong with the line
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for DslError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for DslError {}
+
+// Builds a map while the lines are parsed
+struct MapBuilder {
+    graph: UnGraph<Arc<str>, EdgeInfo>,
+    menu_tree: Option<MenuTree>,
+    // The nodes with the line they are declared in
+    indices: BTreeMap<String, (NodeIndex, usize)>,
+    tags: BTreeMap<String, BTreeSet<String>>,
+    category: Option<String>,
+}
+
+impl MapBuilder {
+    // Declares the node in the current category. Every node can only be declared once, so it
+    // can't end up in the wrong category by accident
+    fn declare(&mut self, name: &str, line: usize) -> Result<(), DslError> {
+        if let Some((_, declared)) = self.indices.get(name) {
+            return Err(DslError::new(
+                line,
+                format!("\"{name}\" is already declared on line {declared}"),
+            ));
+        }
+
+        let category = self.category.as_deref().ok_or_else(|| {
+            DslError::new(
+                line,
+                format!("\"{name}\" is declared outside of a category"),
+            )
+        })?;
+        let index = self.graph.add_node(Arc::from(name));
+        let node = MenuTree::from_node(self.graph[index].clone(), Some(index.into()));
+        MenuTree::insert_at_path(&mut self.menu_tree, category, node).map_err(|e| {
+            let reason = match e {
+                PathError::TooDeep => depth_error(),
+                _ => format!(
+                    "The category \"{category}\" has another root than the other categories"
+                ),
+            };
+            DslError::new(line, reason)
+        })?;
+        self.indices.insert(name.to_string(), (index, line));
+
+        Ok(())
+    }
+
+    // Returns a node used by an edge, which has to be declared on an earlier line so a misspelled
+    // name doesn't create a new node
+    fn declared(&self, name: &str, line: usize) -> Result<NodeIndex, DslError> {
+        self.indices
+            .get(name)
+            .map(|(index, _)| *index)
+            .ok_or_else(|| {
+                DslError::new(
+                    line,
+                    format!("The edge uses \"{name}\", which isn't declared before it"),
+                )
+            })
+    }
+
+    fn parse_line(&mut self, text: &str, line: usize) -> Result<(), DslError> {
+        if let Some(category) = text.strip_prefix('[') {
+            let category = category
+                .strip_suffix(']')
+                .ok_or_else(|| DslError::new(line, "The category header isn't closed"))?;
+            self.category = Some(category.trim().to_string());
+        } else if let Some((from, rest)) = text.split_once("->") {
+            let (to, weight) = rest
+                .rsplit_once(':')
+                .ok_or_else(|| DslError::new(line, "The edge has no cost"))?;
+            let edge = parse_edge(weight, line)?;
+            let (from, to) = (from.trim(), to.trim());
+            if !edge.has_valid_weights() {
+                let error = ValidationError::InvalidWeight {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                };
+                return Err(DslError::new(line, error.to_string()));
+            }
+
+            let from = self.declared(from, line)?;
+            let to = self.declared(to, line)?;
+            self.graph.add_edge(from, to, edge);
+        } else {
+            let (name, tags) = match text.split_once('[') {
+                None => (text, None),
+                Some((name, tags)) => (name, Some(tags)),
+            };
+            let name = name.trim();
+            self.declare(name, line)?;
+
+            if let Some(tags) = tags {
+                let tags = tags
+                    .trim_end()
+                    .strip_suffix(']')
+                    .ok_or_else(|| DslError::new(line, "The tag list isn't closed"))?;
+                self.tags.entry(name.to_string()).or_default().extend(
+                    tags.split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(String::from),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str, line: usize) -> Result<T, DslError> {
+    value
+        .parse()
+        .map_err(|_| DslError::new(line, format!("\"{value}\" isn't a valid {key}")))
+}
+
+// Parses the part of an edge after the colon, which is the cost optionally followed by key=value
+// pairs
+fn parse_edge(weight: &str, line: usize) -> Result<EdgeInfo, DslError> {
+    let mut parts = weight.split_whitespace();
+    let cost = parts
+        .next()
+        .ok_or_else(|| DslError::new(line, "The edge has no cost"))?;
+    let mut edge = EdgeInfo::new(parse_value("cost", cost, line)?);
+    let mut window: (Option<f64>, Option<f64>, Option<f64>) = (None, None, None);
+
+    for part in parts {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| DslError::new(line, format!("\"{part}\" isn't a key=value pair")))?;
+
+        match key {
+            "min" => edge.min = Some(parse_value(key, value, line)?),
+            "max" => edge.max = Some(parse_value(key, value, line)?),
+            "aerobrake" => edge.aerobrake = Some(parse_value(key, value, line)?),
+            "low_thrust" => edge.low_thrust = Some(parse_value(key, value, line)?),
+            "time" => edge.time = Some(parse_value(key, value, line)?),
+            "phase_angle" => window.0 = Some(parse_value(key, value, line)?),
+            "synodic_period" => window.1 = Some(parse_value(key, value, line)?),
+            "ejection_angle" => window.2 = Some(parse_value(key, value, line)?),
+            "tags" => edge.tags.extend(
+                value
+                    .split(',')
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from),
+            ),
+            _ => return Err(DslError::new(line, format!("Unknown key \"{key}\""))),
+        }
+    }
+
+    edge.window = match window {
+        (None, None, None) => None,
+        (Some(phase_angle), Some(synodic_period), ejection_angle) => Some(TransferWindow {
+            phase_angle,
+            synodic_period,
+            ejection_angle,
+        }),
+        _ => {
+            return Err(DslError::new(
+                line,
+                "A transfer window needs both a phase_angle and a synodic_period",
+            ))
+        }
+    };
+
+    Ok(edge)
+}
+
+impl DeltavMap {
+    /// Parses a map written in the compact text format
+    ///
+    /// Every line is either a category header, a node or an edge. Nodes are put into the category
+    /// of the last header, given as the path of categories starting with the root separated by
+    /// slashes. Every node is declared once and can carry tags in square brackets separated by
+    /// commas. Edges are given by the names of the two nodes, which have to be declared on an
+    /// earlier line, and the cost, optionally followed by the `min`, `max`, `aerobrake`,
+    /// `low_thrust`, `time`, `phase_angle`, `synodic_period` and `ejection_angle` of the edge and
+    /// its `tags` separated by commas, see [`EdgeInfo`]. Empty lines and lines starting with `#`
+    /// are ignored. The map is [validated](DeltavMap::validate), so edges costing zero or less
+    /// are rejected
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let map = DeltavMap::from_dsl(
+    ///     "
+    ///     [Kerbol System/Kerbin]
+    ///     Kerbin Surface [surface, atmosphere]
+    ///     Low Kerbin Orbit (80km)
+    ///     Low Kerbin Orbit (80km) -> Kerbin Surface : 3400 aerobrake=0
+    ///
+    ///     [Kerbol System/Kerbin/Mun]
+    ///     Mun Intercept
+    ///     Low Kerbin Orbit (80km) -> Mun Intercept : 860 time=26687
+    ///     ",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     map.calculate_delta_v("Kerbin Surface", "Mun Intercept").unwrap(),
+    ///     Some(4260)
+    /// );
+    /// ```
+    pub fn from_dsl(text: &str) -> Result<DeltavMap, DslError> {
+        let mut builder = MapBuilder {
+            graph: UnGraph::new_undirected(),
+            menu_tree: None,
+            indices: BTreeMap::new(),
+            tags: BTreeMap::new(),
+            category: None,
+        };
+
+        let mut last_line = 0;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                builder.parse_line(line, number + 1)?;
+                last_line = number + 1;
+            }
+        }
+
+        let map = DeltavMap {
+            menu_tree: builder
+                .menu_tree
+                .unwrap_or_else(|| MenuTree::new_category("")),
+            graph: builder.graph,
+            tags: builder.tags,
+            metadata: Metadata::default(),
+        };
+        // The lines are already checked while parsing, but a node can still have the name of a
+        // category, which is only found when validating the whole map
+        map.validate().map_err(|error| {
+            let line = match &error {
+                ValidationError::DuplicateName(name) => {
+                    builder.indices.get(name).map(|(_, line)| *line)
+                }
+                _ => None,
+            };
+            DslError::new(line.unwrap_or(last_line), error.to_string())
+        })?;
+
+        Ok(map)
+    }
+
+    /// Writes the map in the text format read by [`from_dsl`](DeltavMap::from_dsl). The metadata
+    /// of the map and tags of categories aren't written
+    pub fn to_dsl(&self) -> String {
+        let mut text = String::new();
+        let mut category = String::new();
+
+        for (path, node) in self.menu_tree.end_nodes() {
+            let path = path.join("/");
+            if path != category {
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                writeln!(text, "[{path}]").unwrap();
+                category = path;
+            }
+
+            text.push_str(node.name());
+            if let Some(tags) = self.tags.get(node.name()) {
+                let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                write!(text, " [{}]", tags.join(", ")).unwrap();
+            }
+            text.push('\n');
+        }
+
+        if self.graph.edge_count() > 0 {
+            text.push('\n');
+        }
+        for edge in self.graph.edge_references() {
+            let info = edge.weight();
+            write!(
+                text,
+                "{} -> {} : {}",
+                self.graph[edge.source()],
+                self.graph[edge.target()],
+                info.cost
+            )
+            .unwrap();
+
+            let mut pair = |key: &str, value: Option<String>| {
+                if let Some(value) = value {
+                    write!(text, " {key}={value}").unwrap();
+                }
+            };
+            pair("min", info.min.map(|v| v.to_string()));
+            pair("max", info.max.map(|v| v.to_string()));
+            pair("aerobrake", info.aerobrake.map(|v| v.to_string()));
+            pair("low_thrust", info.low_thrust.map(|v| v.to_string()));
+            pair("time", info.time.map(|v| v.to_string()));
+            pair(
+                "phase_angle",
+                info.window.map(|w| w.phase_angle.to_string()),
+            );
+            pair(
+                "synodic_period",
+                info.window.map(|w| w.synodic_period.to_string()),
+            );
+            pair(
+                "ejection_angle",
+                info.window
+                    .and_then(|w| w.ejection_angle)
+                    .map(|v| v.to_string()),
+            );
+            if !info.tags.is_empty() {
+                pair("tags", Some(info.tags().collect::<Vec<_>>().join(",")));
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, Metadata};
+
+    #[test]
+    fn test_from_dsl() {
+        let text = std::fs::read_to_string("test_res/test.deltav").unwrap();
+        let map = DeltavMap::from_dsl(&text).unwrap();
+
+        let mut expected = get_test_map();
+        expected.metadata = Metadata::default();
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_to_dsl() {
+        let text = std::fs::read_to_string("test_res/test.deltav").unwrap();
+        let written = get_test_map().to_dsl();
+        assert_eq!(DeltavMap::from_dsl(&written), DeltavMap::from_dsl(&text));
+
+        let stock = DeltavMap::new_stock();
+        let mut expected = stock.clone();
+        expected.metadata = Metadata::default();
+        assert_eq!(DeltavMap::from_dsl(&stock.to_dsl()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = DeltavMap::from_dsl("Node1 -> Node2 : 900").unwrap_err();
+        assert_eq!(error.line(), 1);
+
+        let error = DeltavMap::from_dsl("[Category1]\nNode1 -> Node2 900").unwrap_err();
+        assert_eq!(error.to_string(), "Line 2: The edge has no cost");
+
+        let error = DeltavMap::from_dsl("[Category1]\n\nNode1 -> Node2 : 900 x=1").unwrap_err();
+        assert_eq!(error.to_string(), "Line 3: Unknown key \"x\"");
+
+        let error = DeltavMap::from_dsl("[Category1]\nNode1\nNode2\nNode1 -> Node2 : many");
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "Line 4: \"many\" isn't a valid cost"
+        );
+
+        let error = DeltavMap::from_dsl("[Category1]\nNode1\nNode1 -> Nod2 : 900").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 3: The edge uses \"Nod2\", which isn't declared before it"
+        );
+
+        let error = DeltavMap::from_dsl("[Category1]\nNode1\n\n[Category2]\nNode1 [surface]");
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "Line 5: \"Node1\" is already declared on line 2"
+        );
+
+        for cost in ["-900", "0", "900 min=0", "900 aerobrake=-1"] {
+            let text = format!("[Category1]\nNode1\nNode2\nNode1 -> Node2 : {cost}");
+            let error = DeltavMap::from_dsl(&text).unwrap_err();
+            assert_eq!(error.line(), 4);
+            assert!(error.reason().contains("costs zero or less"), "{error}");
+        }
+
+        let path: Vec<String> = (0..20_000).map(|depth| format!("c{depth}")).collect();
+        let error = DeltavMap::from_dsl(&format!("[{}]\nNode1", path.join("/"))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2: The menu tree is nested more than 32 levels deep"
+        );
+
+        let error = DeltavMap::from_dsl("[Category1]\nNode1\nCategory1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 3: There are multiple nodes or categories with the name \"Category1\""
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Category1]
+            Node1 [surface, atmosphere]
+            Node2
+            Node1 -> Node2 : 900 tags=atmosphere,gravity_assist
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(map.tags("Node1").unwrap(), ["atmosphere", "surface"]);
+        let edge = map.edge_info("Node1", "Node2").unwrap().unwrap();
+        assert_eq!(
+            edge.tags().collect::<Vec<_>>(),
+            ["atmosphere", "gravity_assist"]
+        );
+        assert_eq!(DeltavMap::from_dsl(&map.to_dsl()).unwrap(), map);
+    }
+}
@@ -0,0 +1,465 @@
+use crate::options::scale_cost;
+use crate::{CostMode, Rounding};
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The tag marking edges that are only usable with a gravity assist
+pub const GRAVITY_ASSIST_TAG: &str = "gravity_assist";
+
+/// The information stored on an edge of the delta-v map
+///
+/// # Deserialization
+/// An edge can either be given as a plain number, which is its cost, or as an object:
+/// ```json
+/// {
+///   "cost": 900,
+///   "min": 850,
+///   "max": 1000,
+///   "aerobrake": 0,
+///   "low_thrust": 1.5,
+///   "time": 6524003,
+///   "window": {
+///     "phase_angle": 44.0,
+///     "synodic_period": 909.5,
+///     "ejection_angle": 138.0
+///   },
+///   "tags": ["atmosphere"]
+/// }
+/// ```
+///
+/// `min` and `max` are the costs when flying perfectly or poorly, they default to the cost.
+/// `aerobrake` is the cost of travelling from the first node of the edge to the second one when
+/// aerobraking. Travelling in the opposite direction always costs the full amount. `low_thrust` is
+/// the factor the costs are multiplied by for low thrust craft, it defaults to 1. `time` is the
+/// travel time in seconds. `window` is only used for interplanetary transfers, see
+/// [`TransferWindow`]
+///
+/// Edges tagged with [`GRAVITY_ASSIST_TAG`] are shortcuts that are only used when gravity assists
+/// are enabled in the [`CalcOptions`](crate::CalcOptions)
+#[derive(Clone, PartialEq, Debug)]
+pub struct EdgeInfo {
+    pub(crate) cost: i32,
+    pub(crate) min: Option<i32>,
+    pub(crate) max: Option<i32>,
+    pub(crate) aerobrake: Option<i32>,
+    pub(crate) low_thrust: Option<f64>,
+    pub(crate) time: Option<u64>,
+    pub(crate) window: Option<TransferWindow>,
+    pub(crate) tags: BTreeSet<String>,
+}
+
+/// When and where an interplanetary transfer can be started
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct TransferWindow {
+    pub(crate) phase_angle: f64,
+    pub(crate) synodic_period: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ejection_angle: Option<f64>,
+}
+
+impl TransferWindow {
+    /// Creates a transfer window from the phase angle in degrees and the synodic period in days
+    pub fn new(phase_angle: f64, synodic_period: f64) -> TransferWindow {
+        TransferWindow {
+            phase_angle,
+            synodic_period,
+            ejection_angle: None,
+        }
+    }
+
+    /// Sets the ejection angle in degrees
+    pub fn with_ejection_angle(mut self, angle: f64) -> TransferWindow {
+        self.ejection_angle = Some(angle);
+        self
+    }
+
+    /// The angle in degrees the target has to be ahead of the origin to start the transfer
+    pub fn phase_angle(&self) -> f64 {
+        self.phase_angle
+    }
+
+    /// The number of days between two transfer windows. A day is 6 hours long
+    pub fn synodic_period(&self) -> f64 {
+        self.synodic_period
+    }
+
+    /// The angle in degrees between the prograde direction of the origin body and the point the
+    /// ejection burn should be made at, if known
+    pub fn ejection_angle(&self) -> Option<f64> {
+        self.ejection_angle
+    }
+}
+
+impl EdgeInfo {
+    /// Creates an edge with the given cost and no tags
+    pub fn new(cost: i32) -> EdgeInfo {
+        EdgeInfo {
+            cost,
+            min: None,
+            max: None,
+            aerobrake: None,
+            low_thrust: None,
+            time: None,
+            window: None,
+            tags: BTreeSet::new(),
+        }
+    }
+
+    /// Sets the time in seconds it takes to travel along the edge
+    pub fn with_travel_time(mut self, seconds: u64) -> EdgeInfo {
+        self.time = Some(seconds);
+        self
+    }
+
+    /// Sets the transfer window of an interplanetary edge
+    pub fn with_transfer_window(mut self, window: TransferWindow) -> EdgeInfo {
+        self.window = Some(window);
+        self
+    }
+
+    /// Sets the costs of travelling along the edge in the best and the worst case
+    pub fn with_range(mut self, min: i32, max: i32) -> EdgeInfo {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets the cost of travelling from the first to the second node of the edge when aerobraking
+    pub fn with_aerobrake(mut self, cost: i32) -> EdgeInfo {
+        self.aerobrake = Some(cost);
+        self
+    }
+
+    /// Sets the factor the costs are multiplied by for craft with a low thrust to weight ratio
+    pub fn with_low_thrust_factor(mut self, factor: f64) -> EdgeInfo {
+        self.low_thrust = Some(factor);
+        self
+    }
+
+    /// Adds a tag to the edge
+    pub fn with_tag(mut self, tag: &str) -> EdgeInfo {
+        self.tags.insert(tag.to_string());
+        self
+    }
+
+    /// Returns a copy of the edge with all costs multiplied by the factor. The factor is clamped
+    /// like the one of [`CalcOptions::scale`](crate::CalcOptions::scale)
+    pub fn scaled(&self, factor: f64) -> EdgeInfo {
+        self.scaled_with(factor, Rounding::Nearest)
+    }
+
+    /// Returns a copy of the edge with all costs multiplied by the factor and rounded with the
+    /// given policy
+    pub fn scaled_with(&self, factor: f64, rounding: Rounding) -> EdgeInfo {
+        let scale = |cost: i32| scale_cost(cost, factor, rounding);
+
+        EdgeInfo {
+            cost: scale(self.cost),
+            min: self.min.map(scale),
+            max: self.max.map(scale),
+            aerobrake: self.aerobrake.map(scale),
+            ..self.clone()
+        }
+    }
+
+    /// The deltav required to travel along the edge
+    pub fn cost(&self) -> i32 {
+        self.cost
+    }
+
+    /// The deltav required to travel along the edge in the best case
+    pub fn min_cost(&self) -> i32 {
+        self.min.unwrap_or(self.cost)
+    }
+
+    /// The deltav required to travel along the edge in the worst case
+    pub fn max_cost(&self) -> i32 {
+        self.max.unwrap_or(self.cost)
+    }
+
+    /// The deltav required to travel along the edge in the given mode
+    pub fn cost_in_mode(&self, mode: CostMode) -> i32 {
+        match mode {
+            CostMode::Nominal => self.cost,
+            CostMode::Optimistic => self.min_cost(),
+            CostMode::Pessimistic => self.max_cost(),
+            CostMode::Midpoint => self.min_cost().midpoint(self.max_cost()),
+        }
+    }
+
+    /// The cost of travelling from the first to the second node of the edge when aerobraking.
+    ///
+    /// Returns `None` if aerobraking isn't possible along this edge
+    pub fn aerobrake_cost(&self) -> Option<i32> {
+        self.aerobrake
+    }
+
+    /// The factor the costs are multiplied by for craft with a low thrust to weight ratio
+    pub fn low_thrust_factor(&self) -> f64 {
+        self.low_thrust.unwrap_or(1.0)
+    }
+
+    /// The time in seconds it takes to travel along the edge, if known
+    pub fn travel_time(&self) -> Option<u64> {
+        self.time
+    }
+
+    /// The transfer window if this is an interplanetary edge
+    pub fn transfer_window(&self) -> Option<&TransferWindow> {
+        self.window.as_ref()
+    }
+
+    /// The tags of the edge
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(String::as_str)
+    }
+
+    /// Checks if the edge carries the given tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Checks if the edge is a shortcut only usable with a gravity assist
+    pub fn is_gravity_assist(&self) -> bool {
+        self.has_tag(GRAVITY_ASSIST_TAG)
+    }
+}
+
+impl From<i32> for EdgeInfo {
+    fn from(cost: i32) -> Self {
+        EdgeInfo::new(cost)
+    }
+}
+
+// The on-disk representation of an edge. Edges without any extra information are stored as
+// plain numbers to stay compatible with older maps
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+enum EdgeRepr {
+    Cost(i32),
+    Detailed {
+        cost: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        aerobrake: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        low_thrust: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window: Option<TransferWindow>,
+        #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+        tags: BTreeSet<String>,
+    },
+}
+
+// The representation of an edge in binary formats. They can't tell the variants of untagged
+// enums apart or skip fields, so all fields are always stored
+#[derive(Deserialize, Serialize)]
+struct BinaryEdge {
+    cost: i32,
+    min: Option<i32>,
+    max: Option<i32>,
+    aerobrake: Option<i32>,
+    low_thrust: Option<f64>,
+    time: Option<u64>,
+    window: Option<(f64, f64, Option<f64>)>,
+    tags: BTreeSet<String>,
+}
+
+impl Serialize for EdgeInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            EdgeRepr::from(self.clone()).serialize(serializer)
+        } else {
+            BinaryEdge {
+                cost: self.cost,
+                min: self.min,
+                max: self.max,
+                aerobrake: self.aerobrake,
+                low_thrust: self.low_thrust,
+                time: self.time,
+                window: self.window.map(|window| {
+                    (
+                        window.phase_angle,
+                        window.synodic_period,
+                        window.ejection_angle,
+                    )
+                }),
+                tags: self.tags.clone(),
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EdgeInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            EdgeRepr::deserialize(deserializer).map(EdgeInfo::from)
+        } else {
+            let edge = BinaryEdge::deserialize(deserializer)?;
+            Ok(EdgeInfo {
+                cost: edge.cost,
+                min: edge.min,
+                max: edge.max,
+                aerobrake: edge.aerobrake,
+                low_thrust: edge.low_thrust,
+                time: edge.time,
+                window: edge
+                    .window
+                    .map(
+                        |(phase_angle, synodic_period, ejection_angle)| TransferWindow {
+                            phase_angle,
+                            synodic_period,
+                            ejection_angle,
+                        },
+                    ),
+                tags: edge.tags,
+            })
+        }
+    }
+}
+
+impl From<EdgeRepr> for EdgeInfo {
+    fn from(repr: EdgeRepr) -> Self {
+        match repr {
+            EdgeRepr::Cost(cost) => EdgeInfo::new(cost),
+            EdgeRepr::Detailed {
+                cost,
+                min,
+                max,
+                aerobrake,
+                low_thrust,
+                time,
+                window,
+                tags,
+            } => EdgeInfo {
+                cost,
+                min,
+                max,
+                aerobrake,
+                low_thrust,
+                time,
+                window,
+                tags,
+            },
+        }
+    }
+}
+
+impl From<EdgeInfo> for EdgeRepr {
+    fn from(edge: EdgeInfo) -> Self {
+        if edge.min.is_none()
+            && edge.max.is_none()
+            && edge.aerobrake.is_none()
+            && edge.low_thrust.is_none()
+            && edge.time.is_none()
+            && edge.window.is_none()
+            && edge.tags.is_empty()
+        {
+            EdgeRepr::Cost(edge.cost)
+        } else {
+            EdgeRepr::Detailed {
+                cost: edge.cost,
+                min: edge.min,
+                max: edge.max,
+                aerobrake: edge.aerobrake,
+                low_thrust: edge.low_thrust,
+                time: edge.time,
+                window: edge.window,
+                tags: edge.tags,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CostMode, EdgeInfo, TransferWindow};
+
+    #[test]
+    fn test_deserialize() {
+        let edge: EdgeInfo = serde_json::from_str("900").unwrap();
+        assert_eq!(edge, EdgeInfo::new(900));
+
+        let edge: EdgeInfo =
+            serde_json::from_str(r#"{"cost": 80, "tags": ["atmosphere"]}"#).unwrap();
+        assert_eq!(edge, EdgeInfo::new(80).with_tag("atmosphere"));
+
+        let edge: EdgeInfo = serde_json::from_str(r#"{"cost": 3400, "aerobrake": 0}"#).unwrap();
+        assert_eq!(edge.aerobrake_cost(), Some(0));
+
+        let edge: EdgeInfo = serde_json::from_str(r#"{"cost": 860, "time": 26687}"#).unwrap();
+        assert_eq!(edge.travel_time(), Some(26687));
+        assert_eq!(edge.low_thrust_factor(), 1.0);
+
+        let edge: EdgeInfo = serde_json::from_str(r#"{"cost": 580, "low_thrust": 1.5}"#).unwrap();
+        assert_eq!(edge.low_thrust_factor(), 1.5);
+
+        let edge: EdgeInfo = serde_json::from_str(
+            r#"{"cost": 130, "window": {"phase_angle": 44.0, "synodic_period": 909.5}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            edge.transfer_window(),
+            Some(&TransferWindow::new(44.0, 909.5))
+        );
+        assert_eq!(edge.transfer_window().unwrap().ejection_angle(), None);
+
+        let edge: EdgeInfo = serde_json::from_str(
+            r#"{"cost": 130, "window": {"phase_angle": 44.0, "synodic_period": 909.5, "ejection_angle": 138.0}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            edge.transfer_window().unwrap().ejection_angle(),
+            Some(138.0)
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_string(&EdgeInfo::new(900)).unwrap();
+        assert_eq!(json, "900");
+
+        let json = serde_json::to_string(&EdgeInfo::new(80).with_tag("atmosphere")).unwrap();
+        assert_eq!(json, r#"{"cost":80,"tags":["atmosphere"]}"#);
+    }
+
+    #[test]
+    fn test_cost_modes() {
+        let edge = EdgeInfo::new(80).with_range(60, 120);
+
+        assert_eq!(edge.cost_in_mode(CostMode::Nominal), 80);
+        assert_eq!(edge.cost_in_mode(CostMode::Optimistic), 60);
+        assert_eq!(edge.cost_in_mode(CostMode::Pessimistic), 120);
+        assert_eq!(edge.cost_in_mode(CostMode::Midpoint), 90);
+
+        let edge = EdgeInfo::new(80);
+        assert_eq!(edge.cost_in_mode(CostMode::Pessimistic), 80);
+    }
+
+    #[test]
+    fn test_scaled() {
+        let edge = EdgeInfo::new(3400)
+            .with_range(3300, 3600)
+            .with_aerobrake(0)
+            .with_tag("atmosphere")
+            .scaled(2.5);
+
+        assert_eq!(edge.cost(), 8500);
+        assert_eq!(edge.min_cost(), 8250);
+        assert_eq!(edge.max_cost(), 9000);
+        assert_eq!(edge.aerobrake_cost(), Some(0));
+        assert!(edge.has_tag("atmosphere"));
+
+        let edge = EdgeInfo::new(3400).with_aerobrake(0).scaled(-1.0);
+        assert_eq!(edge.cost(), 34);
+        assert_eq!(edge.aerobrake_cost(), Some(0));
+        assert_eq!(EdgeInfo::new(3400).scaled(f64::NAN).cost(), 3400);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{DeltavMap, EdgeInfo, MenuNode, MenuTree, NoSuchNodeError, NodeId};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use petgraph::graph::NodeIndex;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// This error is raised when a map can't be changed as requested
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum EditError {
+    /// There is no node or category with the name
+    NoSuchNode(NoSuchNodeError),
+
+    /// There already is a node or category with the name
+    DuplicateName(String),
+
+    /// Nodes can only be added to categories, but the name belongs to an end node
+    NotACategory(String),
+
+    /// Edges can only connect end nodes, but the name belongs to a category
+    NotANode(String),
+
+    /// The root category can't be removed
+    RemoveRoot,
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EditError::NoSuchNode(e) => write!(f, "{e}"),
+            EditError::DuplicateName(name) => write!(
+                f,
+                "There already is a node or category with the name \"{name}\""
+            ),
+            EditError::NotACategory(name) => write!(f, "\"{name}\" isn't a category"),
+            EditError::NotANode(name) => write!(f, "\"{name}\" is a category, not a node"),
+            EditError::RemoveRoot => write!(f, "The root category can't be removed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for EditError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EditError::NoSuchNode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<NoSuchNodeError> for EditError {
+    fn from(e: NoSuchNodeError) -> Self {
+        EditError::NoSuchNode(e)
+    }
+}
+
+impl DeltavMap {
+    /// Adds a new node without any edges to the category with the given name and returns its id
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{DeltavMap, EdgeInfo};
+    ///
+    /// let mut map = DeltavMap::new_stock();
+    /// map.add_node("Kerbin", "High Kerbin Orbit (250km)").unwrap();
+    /// map.add_edge("Low Kerbin Orbit (80km)", "High Kerbin Orbit (250km)", EdgeInfo::new(240))
+    ///     .unwrap();
+    /// ```
+    pub fn add_node(&mut self, category: &str, name: &str) -> Result<NodeId, EditError> {
+        let name: Arc<str> = Arc::from(name);
+        let index = NodeId::from(self.graph.add_node(name.clone()));
+        let child = MenuTree::from_node(name, Some(index));
+
+        match self.add_child(category, child) {
+            Ok(()) => Ok(index),
+            Err(e) => {
+                self.graph.remove_node(index.into());
+                Err(e)
+            }
+        }
+    }
+
+    /// Adds a new empty category to the category with the given name
+    pub fn add_category(&mut self, parent: &str, name: &str) -> Result<(), EditError> {
+        self.add_child(parent, MenuTree::new_category(name))
+    }
+
+    // Appends the child to the children of the category
+    fn add_child(&mut self, parent: &str, child: MenuTree) -> Result<(), EditError> {
+        if self.menu_tree.search(child.name()).is_ok() {
+            return Err(EditError::DuplicateName(child.name().to_string()));
+        }
+
+        let parent = self.menu_tree.search(parent)?;
+        if !parent.is_category() {
+            return Err(EditError::NotACategory(parent.name().to_string()));
+        }
+        self.menu_tree.push_child(parent.position, child);
+
+        Ok(())
+    }
+
+    /// Adds an edge between the two nodes
+    pub fn add_edge(&mut self, from: &str, to: &str, edge: EdgeInfo) -> Result<(), EditError> {
+        let from = self.end_node(from)?;
+        let to = self.end_node(to)?;
+        self.graph.add_edge(from, to, edge);
+
+        Ok(())
+    }
+
+    /// Removes the node or category with the given name. Removing a category removes all nodes in
+    /// it, and removing a node removes all its edges and its tags
+    pub fn remove(&mut self, name: &str) -> Result<(), EditError> {
+        if self.menu_tree.name() == name {
+            return Err(EditError::RemoveRoot);
+        }
+        let removed = self
+            .menu_tree
+            .remove_child(name)
+            .ok_or_else(|| NoSuchNodeError {
+                name: name.to_string(),
+            })?;
+
+        // The last node of the graph takes the place of a removed one. Removing the highest
+        // indices first means the moved node is never one that still has to be removed
+        let mut indices: Vec<NodeId> = removed.iter().filter_map(MenuNode::id).collect();
+        indices.sort_by_key(|index| core::cmp::Reverse(index.index()));
+        for index in indices {
+            let last = NodeId::new(self.graph.node_count() - 1);
+            self.graph.remove_node(index.into());
+            self.menu_tree.replace_index(last, index);
+        }
+        for node in removed.iter() {
+            self.tags.remove(node.name());
+        }
+
+        Ok(())
+    }
+
+    /// Renames the node or category. The name is changed in the graph and the tags as well
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), EditError> {
+        if name != new_name && self.menu_tree.search(new_name).is_ok() {
+            return Err(EditError::DuplicateName(new_name.to_string()));
+        }
+
+        let node = self.menu_tree.search(name)?;
+        let (position, id) = (node.position, node.id());
+        let shared: Arc<str> = Arc::from(new_name);
+        self.menu_tree.set_name(position, shared.clone());
+        if let Some(id) = id {
+            self.graph[NodeIndex::from(id)] = shared;
+        }
+
+        if let Some(tags) = self.tags.remove(name) {
+            self.tags.insert(new_name.to_string(), tags);
+        }
+
+        Ok(())
+    }
+
+    // Returns the graph index of the end node with the given name
+    fn end_node(&self, name: &str) -> Result<NodeIndex, EditError> {
+        match self.menu_tree.search(name)?.id() {
+            Some(id) => Ok(id.into()),
+            None => Err(EditError::NotANode(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, EdgeInfo, EditError};
+
+    #[test]
+    fn test_add() {
+        let mut map = get_test_map();
+        map.add_node("Category2", "Node5").unwrap();
+        map.add_edge("Node2", "Node5", EdgeInfo::new(10)).unwrap();
+
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.calculate_delta_v("Node1", "Node5").unwrap(), Some(910));
+
+        map.add_category("Category1", "Category3").unwrap();
+        map.add_node("Category3", "Node6").unwrap();
+        assert_eq!(
+            map.menu_tree().search("Category3").unwrap().iter().count(),
+            2
+        );
+
+        assert_eq!(
+            map.add_node("Category1", "Node1"),
+            Err(EditError::DuplicateName(String::from("Node1")))
+        );
+        assert_eq!(
+            map.add_node("Node1", "Node7"),
+            Err(EditError::NotACategory(String::from("Node1")))
+        );
+        assert_eq!(
+            map.add_edge("Category2", "Node1", EdgeInfo::new(10)),
+            Err(EditError::NotANode(String::from("Category2")))
+        );
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = get_test_map();
+        map.remove("Node1").unwrap();
+        assert_eq!(map.validate(), Ok(()));
+        assert!(map.calculate_delta_v("Node1", "Node4").is_err());
+        assert_eq!(map.calculate_delta_v("Node2", "Node4").unwrap(), Some(130));
+
+        let mut map = get_test_map();
+        map.remove("Category2").unwrap();
+        assert_eq!(map.validate(), Ok(()));
+        assert!(map.nodes_with_tag("atmosphere").is_empty());
+        assert_eq!(map.calculate_delta_v("Node3", "Node4").unwrap(), Some(50));
+
+        assert_eq!(map.remove("Category1"), Err(EditError::RemoveRoot));
+        assert!(map.remove("Node1").is_err());
+    }
+
+    #[test]
+    fn test_remove_last_category() {
+        let mut map = DeltavMap::from_dsl(
+            "
+            [Kerbol/Kerbin]
+            Kerbin Surface
+            Low Kerbin Orbit
+            Kerbin Surface -> Low Kerbin Orbit : 3400
+
+            [Kerbol/Mun]
+            Mun Intercept
+            Low Mun Orbit
+            Mun Surface
+            Low Kerbin Orbit -> Mun Intercept : 860
+            Mun Intercept -> Low Mun Orbit : 310
+            Low Mun Orbit -> Mun Surface : 580
+            ",
+        )
+        .unwrap();
+        map.remove("Mun").unwrap();
+
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.graph.node_count(), 2);
+        let edges: Vec<_> = map.edges().map(|(from, to, _)| (from, to)).collect();
+        assert_eq!(edges, vec![("Kerbin Surface", "Low Kerbin Orbit")]);
+        assert!(map.menu_tree().search("Mun Surface").is_err());
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut map = get_test_map();
+        map.rename("Node2", "Node5").unwrap();
+        map.rename("Category2", "Category3").unwrap();
+
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.tags("Node5").unwrap(), ["atmosphere"]);
+        assert_eq!(map.calculate_delta_v("Node1", "Node5").unwrap(), Some(900));
+        assert!(map.menu_tree().search("Category3").is_ok());
+        assert_eq!(
+            map.rename("Node5", "Node1"),
+            Err(EditError::DuplicateName(String::from("Node1")))
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Stats of the engines that ship with the stock game.
+//!
+//! This module is only available with the `engines` feature.
+//!
+//! # Example
+//! ```
+//! use deltav_calc::engines;
+//! use deltav_calc::rocketry::Vessel;
+//!
+//! let terrier = engines::find("Terrier").unwrap();
+//! let vessel = Vessel::new(vec![terrier.stage(2.0, 2.5)]);
+//! ```
+
+use crate::rocketry::Stage;
+
+/// The stats of an engine
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Engine {
+    name: &'static str,
+    designation: &'static str,
+    isp_vacuum: f64,
+    isp_sea_level: f64,
+    thrust: f64,
+    mass: f64,
+}
+
+impl Engine {
+    /// The nickname of the engine, for example `Terrier`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The designation of the engine, for example `LV-909`
+    pub fn designation(&self) -> &'static str {
+        self.designation
+    }
+
+    /// The specific impulse in seconds in a vacuum
+    pub fn isp_vacuum(&self) -> f64 {
+        self.isp_vacuum
+    }
+
+    /// The specific impulse in seconds at sea level on Kerbin
+    pub fn isp_sea_level(&self) -> f64 {
+        self.isp_sea_level
+    }
+
+    /// The thrust in kN in a vacuum
+    pub fn thrust(&self) -> f64 {
+        self.thrust
+    }
+
+    /// The mass of the engine in t
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// Creates a stage using this engine in a vacuum. The dry mass has to include the engine
+    pub fn stage(&self, dry_mass: f64, propellant_mass: f64) -> Stage {
+        Stage::new(self.isp_vacuum, dry_mass, propellant_mass)
+    }
+
+    /// Creates a stage using this engine at sea level. The dry mass has to include the engine
+    pub fn stage_sea_level(&self, dry_mass: f64, propellant_mass: f64) -> Stage {
+        Stage::new(self.isp_sea_level, dry_mass, propellant_mass)
+    }
+}
+
+// Shortens the definition of the engine table
+const fn engine(
+    name: &'static str,
+    designation: &'static str,
+    isp_vacuum: f64,
+    isp_sea_level: f64,
+    thrust: f64,
+    mass: f64,
+) -> Engine {
+    Engine {
+        name,
+        designation,
+        isp_vacuum,
+        isp_sea_level,
+        thrust,
+        mass,
+    }
+}
+
+/// All stock engines
+pub const ENGINES: [Engine; 15] = [
+    engine("Ant", "LV-1", 315.0, 80.0, 2.0, 0.02),
+    engine("Spark", "48-7S", 320.0, 265.0, 20.0, 0.13),
+    engine("Puff", "O-10", 250.0, 120.0, 20.0, 0.09),
+    engine("Terrier", "LV-909", 345.0, 85.0, 60.0, 0.5),
+    engine("Reliant", "LV-T30", 310.0, 265.0, 240.0, 1.25),
+    engine("Swivel", "LV-T45", 320.0, 250.0, 215.0, 1.5),
+    engine("Cheetah", "LV-T91", 355.0, 150.0, 125.0, 1.0),
+    engine("Dart", "T-1", 340.0, 290.0, 180.0, 1.0),
+    engine("Poodle", "RE-L10", 350.0, 90.0, 250.0, 1.75),
+    engine("Skipper", "RE-I5", 320.0, 280.0, 650.0, 3.0),
+    engine("Mainsail", "RE-M3", 310.0, 285.0, 1500.0, 6.0),
+    engine("Vector", "KS-25", 315.0, 295.0, 1000.0, 4.0),
+    engine("Rhino", "KR-2L+", 340.0, 205.0, 2000.0, 9.0),
+    engine("Nerv", "LV-N", 800.0, 185.0, 60.0, 3.0),
+    engine("Dawn", "IX-6315", 4200.0, 100.0, 2.0, 0.25),
+];
+
+/// Finds an engine by its nickname or designation, ignoring the case
+pub fn find(name: &str) -> Option<&'static Engine> {
+    ENGINES.iter().find(|engine| {
+        engine.name.eq_ignore_ascii_case(name) || engine.designation.eq_ignore_ascii_case(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engines;
+
+    #[test]
+    fn test_find() {
+        let terrier = engines::find("Terrier").unwrap();
+        assert_eq!(terrier.designation(), "LV-909");
+        assert_eq!(terrier.isp_vacuum(), 345.0);
+
+        assert_eq!(engines::find("lv-909"), Some(terrier));
+        assert_eq!(engines::find("Warp Drive"), None);
+    }
+
+    #[test]
+    fn test_stage() {
+        let stage = engines::find("Nerv").unwrap().stage(5.0, 4.0);
+        assert_eq!(stage.isp(), 800.0);
+        assert_eq!(stage.wet_mass(), 9.0);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{DeltavFormat, DeltavMap, MenuTreeVisitor, NodePayload, Route};
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+// The layout of route drawings, which list the nodes from top to bottom
+const MARGIN: usize = 30;
+const ROUTE_WIDTH: usize = 420;
+const ROUTE_STEP: usize = 56;
+const NODE_RADIUS: usize = 6;
+// A generous estimate of the width of a bold character
+const CHARACTER_WIDTH: usize = 9;
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Writes the map in the DOT language of Graphviz. Categories become nested clusters and
+    /// edges are labeled with their cost
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let dot = DeltavMap::new_stock().to_dot();
+    /// assert!(dot.starts_with("graph {"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut writer = ClusterWriter {
+            text: String::from("graph {\n"),
+            depth: 1,
+            clusters: 0,
+        };
+        self.menu_tree.walk(&mut writer);
+        let mut text = writer.text;
+
+        for (from, to, edge) in self.edges() {
+            writeln!(
+                text,
+                "    \"{}\" -- \"{}\" [label=\"{}\"];",
+                escape_dot(from),
+                escape_dot(to),
+                edge.cost()
+            )
+            .unwrap();
+        }
+
+        text.push_str("}\n");
+        text
+    }
+
+    /// Draws the map as an SVG image. Every category gets a row with the nodes that are directly
+    /// in it, and edges are drawn as straight lines labeled with their cost
+    pub fn to_svg(&self) -> String {
+        let layout = self.layout();
+        let (width, height) = (layout.width(), layout.height());
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             font-family=\"sans-serif\" font-size=\"11\">"
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+        )
+        .unwrap();
+
+        for (from, to, edge) in self.edges() {
+            let (x1, y1) = layout.positions[from];
+            let (x2, y2) = layout.positions[to];
+            writeln!(
+                svg,
+                "  <line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"#999\"/>"
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" fill=\"#555\">{}</text>",
+                (x1 + x2) / 2.0,
+                (y1 + y2) / 2.0 - 3.0,
+                edge.cost()
+            )
+            .unwrap();
+        }
+
+        for (category, (x, y)) in layout.categories() {
+            writeln!(
+                svg,
+                "  <text x=\"{x}\" y=\"{y}\" font-weight=\"bold\">{}</text>",
+                escape_xml(category)
+            )
+            .unwrap();
+        }
+
+        for (node, (x, y)) in layout.nodes() {
+            writeln!(
+                svg,
+                "  <circle cx=\"{x}\" cy=\"{y}\" r=\"6\" fill=\"#36c\"/>\n  \
+                 <text x=\"{x}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                y + 20.0,
+                escape_xml(node)
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl Route {
+    /// Draws the route as an SVG image, with the nodes from top to bottom, the deltav of every
+    /// leg between them and the total at the bottom
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap};
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let route = stock_map
+    ///     .calculate_route("Kerbin Surface", "Mun Surface", &CalcOptions::new())
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let svg = route.to_svg(&DeltavFormat::new());
+    /// assert!(svg.contains("Mun Surface"));
+    /// ```
+    pub fn to_svg(&self, format: &DeltavFormat) -> String {
+        let nodes = self.nodes();
+        let x = MARGIN + NODE_RADIUS;
+        let y = |i: usize| MARGIN + NODE_RADIUS + i * ROUTE_STEP;
+        let total_y = y(nodes.len() - 1) + ROUTE_STEP / 2 + 10;
+        let longest = nodes.iter().map(|node| node.chars().count()).max();
+        let width = ROUTE_WIDTH
+            .max(x + 3 * NODE_RADIUS + longest.unwrap_or_default() * CHARACTER_WIDTH + MARGIN);
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{}\" \
+             font-family=\"sans-serif\" font-size=\"13\">",
+            total_y + MARGIN
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+        )
+        .unwrap();
+
+        for (i, leg) in self.legs.iter().enumerate() {
+            let aerobrake = if leg.aerobrake { " (aerobrake)" } else { "" };
+            writeln!(
+                svg,
+                "  <line x1=\"{x}\" y1=\"{}\" x2=\"{x}\" y2=\"{}\" stroke=\"#999\" stroke-width=\"2\"/>\n  \
+                 <text x=\"{}\" y=\"{}\" fill=\"#555\">{}{aerobrake}</text>",
+                y(i),
+                y(i + 1),
+                x + 3 * NODE_RADIUS,
+                (y(i) + y(i + 1)) / 2 + 4,
+                format.format(leg.delta_v as i64)
+            )
+            .unwrap();
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            writeln!(
+                svg,
+                "  <circle cx=\"{x}\" cy=\"{}\" r=\"{NODE_RADIUS}\" fill=\"#36c\"/>\n  \
+                 <text x=\"{}\" y=\"{}\" font-weight=\"bold\">{}</text>",
+                y(i),
+                x + 3 * NODE_RADIUS,
+                y(i) + 5,
+                escape_xml(node)
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            svg,
+            "  <text x=\"{MARGIN}\" y=\"{total_y}\" font-weight=\"bold\">Total {}</text>",
+            format.format(self.delta_v())
+        )
+        .unwrap();
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Writes a skeleton of a kOS mission script for the route. The budget of every leg is a
+    /// global constant, like `LEG_1_DV`, next to a check of the deltav the ship has left and a
+    /// comment where the burns of the leg go
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{CalcOptions, DeltavMap};
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let route = stock_map
+    ///     .calculate_route("Kerbin Surface", "Mun Surface", &CalcOptions::new())
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let script = route.to_kos();
+    /// assert!(script.contains("global LEG_1_DV is 3400."));
+    /// ```
+    pub fn to_kos(&self) -> String {
+        let mut script = String::new();
+        writeln!(
+            script,
+            "// {} -> {}\n\
+             // Generated by deltav_calc with the budget of every leg in m/s\n\
+             @lazyglobal off.\n\n\
+             global TOTAL_DV is {}.",
+            self.start(),
+            self.end(),
+            self.delta_v()
+        )
+        .unwrap();
+
+        for (i, leg) in self.legs.iter().enumerate() {
+            let number = i + 1;
+            let label = escape_kos(&format!("{} -> {}", leg.from, leg.to));
+            writeln!(
+                script,
+                "\n// Leg {number}: {label}\n\
+                 global LEG_{number}_DV is {}.\n\
+                 print \"Leg {number}: {label} (\" + LEG_{number}_DV + \" m/s)\".\n\
+                 if ship:deltav:current < LEG_{number}_DV {{\n    \
+                 print \"Warning: only \" + round(ship:deltav:current) + \" m/s left\".\n\
+                 }}",
+                leg.delta_v
+            )
+            .unwrap();
+            if leg.aerobrake {
+                writeln!(
+                    script,
+                    "// The budget assumes aerobraking in the atmosphere"
+                )
+                .unwrap();
+            }
+            writeln!(script, "// TODO: Fly the leg").unwrap();
+        }
+
+        script
+    }
+}
+
+// Writes every category of the menu tree as a cluster holding its nodes, indented by its depth
+struct ClusterWriter {
+    text: String,
+    depth: usize,
+    clusters: usize,
+}
+
+impl MenuTreeVisitor for ClusterWriter {
+    fn enter_category(&mut self, name: &str) {
+        let indent = "    ".repeat(self.depth);
+        writeln!(self.text, "{indent}subgraph cluster_{} {{", self.clusters).unwrap();
+        writeln!(self.text, "{indent}    label=\"{}\";", escape_dot(name)).unwrap();
+        self.clusters += 1;
+        self.depth += 1;
+    }
+
+    fn leave_category(&mut self, _name: &str) {
+        self.depth -= 1;
+        writeln!(self.text, "{}}}", "    ".repeat(self.depth)).unwrap();
+    }
+
+    fn visit_node(&mut self, name: &str) {
+        let indent = "    ".repeat(self.depth);
+        writeln!(self.text, "{indent}\"{}\";", escape_dot(name)).unwrap();
+    }
+}
+
+// Escapes a string so it can be put in double quotes in DOT
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Makes a string safe to put in double quotes in kerboscript, which has no escape sequences
+fn escape_kos(text: &str) -> String {
+    text.replace('"', "'")
+}
+
+// Escapes a string so it can be used as text in XML
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CalcOptions, DeltavFormat, DeltavMap};
+
+    fn get_map() -> DeltavMap {
+        DeltavMap::from_dsl(
+            "
+            [System/Planet \"A\"]
+            Surface
+            Orbit
+            Surface -> Orbit : 3000
+
+            [System/Planet \"A\"/Moon]
+            Moon <Orbit>
+            Orbit -> Moon <Orbit> : 800
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            get_map().to_dot(),
+            "graph {\n    \
+             subgraph cluster_0 {\n        \
+             label=\"System\";\n        \
+             subgraph cluster_1 {\n            \
+             label=\"Planet \\\"A\\\"\";\n            \
+             \"Surface\";\n            \
+             \"Orbit\";\n            \
+             subgraph cluster_2 {\n                \
+             label=\"Moon\";\n                \
+             \"Moon <Orbit>\";\n            \
+             }\n        \
+             }\n    \
+             }\n    \
+             \"Surface\" -- \"Orbit\" [label=\"3000\"];\n    \
+             \"Orbit\" -- \"Moon <Orbit>\" [label=\"800\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_route_svg() {
+        let route = get_map()
+            .calculate_route("Surface", "Moon <Orbit>", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        let svg = route.to_svg(&DeltavFormat::new());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"420\""));
+        assert!(svg.contains(">Moon &lt;Orbit&gt;</text>"));
+        assert!(svg.contains(">3,000 m/s</text>"));
+        assert!(svg.contains(">Total 3,800 m/s</text>"));
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 3);
+    }
+
+    #[test]
+    fn test_route_kos() {
+        let route = get_map()
+            .calculate_route("Surface", "Moon <Orbit>", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        let script = route.to_kos();
+
+        assert!(script.starts_with("// Surface -> Moon <Orbit>\n"));
+        assert!(script.contains("global TOTAL_DV is 3800.\n"));
+        assert!(script.contains(
+            "// Leg 1: Surface -> Orbit\n\
+             global LEG_1_DV is 3000.\n\
+             print \"Leg 1: Surface -> Orbit (\" + LEG_1_DV + \" m/s)\".\n"
+        ));
+        assert!(script.contains("global LEG_2_DV is 800.\n"));
+        assert_eq!(script.matches("// TODO: Fly the leg").count(), 2);
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = get_map().to_svg();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"580\""));
+        assert!(svg.contains(">System/Planet \"A\"/Moon</text>"));
+        assert!(svg.contains(">Moon &lt;Orbit&gt;</text>"));
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 3);
+
+        assert!(DeltavMap::new_stock().to_svg().ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,219 @@
+//! A C API to use maps from other languages
+//!
+//! Maps are passed around as opaque `DeltavCalcMap` pointers that are created by one of the
+//! `deltav_map_new_*` functions and have to be freed with [`deltav_map_free`]. Strings are
+//! NUL-terminated and UTF-8 encoded. The header `include/deltav_calc.h` is generated when the
+//! crate is built with the `ffi` feature. To build a library that can be linked, run
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`)
+
+use crate::DeltavMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+
+/// A map handed out to C
+pub struct DeltavCalcMap {
+    map: DeltavMap,
+}
+
+/// The result of a calculation
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DeltavStatus {
+    /// The calculation succeeded
+    Ok = 0,
+
+    /// There is no path between the nodes
+    NoPath = 1,
+
+    /// One of the nodes doesn't exist
+    NoSuchNode = 2,
+
+    /// A pointer was null or a string wasn't valid UTF-8
+    InvalidArgument = 3,
+}
+
+/// Called for every entry of the menu tree by [`deltav_map_for_each_node`]. The name is only
+/// valid during the call
+pub type DeltavNodeCallback =
+    extern "C" fn(name: *const c_char, depth: usize, is_category: bool, user_data: *mut c_void);
+
+// Converts a C string, returning `None` if it is null or not UTF-8
+unsafe fn to_str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(text).to_str().ok()
+}
+
+fn into_handle(map: DeltavMap) -> *mut DeltavCalcMap {
+    Box::into_raw(Box::new(DeltavCalcMap { map }))
+}
+
+/// Returns the stock map
+#[no_mangle]
+pub extern "C" fn deltav_map_new_stock() -> *mut DeltavCalcMap {
+    into_handle(DeltavMap::new_stock())
+}
+
+/// Reads the map file at the path. Returns null if the map can't be loaded
+///
+/// # Safety
+/// The path has to be null or a valid NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn deltav_map_new_from_path(path: *const c_char) -> *mut DeltavCalcMap {
+    match to_str(path).map(|path| DeltavMap::from_path(Path::new(path))) {
+        Some(Ok(map)) => into_handle(map),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Reads a map from the text of a JSON file. Returns null if the map can't be loaded
+///
+/// # Safety
+/// The text has to be null or a valid NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn deltav_map_new_from_json(text: *const c_char) -> *mut DeltavCalcMap {
+    match to_str(text).map(|text| DeltavMap::from_reader(text.as_bytes())) {
+        Some(Ok(map)) => into_handle(map),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Frees a map. Passing null does nothing
+///
+/// # Safety
+/// The map has to be null or a pointer returned by one of the `deltav_map_new_*` functions that
+/// hasn't been freed yet
+#[no_mangle]
+pub unsafe extern "C" fn deltav_map_free(map: *mut DeltavCalcMap) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// Calculates the delta-v required to get from the start to the end and writes it to `delta_v`
+/// if the status is [`DeltavStatus::Ok`]
+///
+/// # Safety
+/// The map has to be null or a valid map, the names null or valid NUL-terminated strings and
+/// `delta_v` null or valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn deltav_map_calculate_delta_v(
+    map: *const DeltavCalcMap,
+    start: *const c_char,
+    end: *const c_char,
+    delta_v: *mut i64,
+) -> DeltavStatus {
+    let (Some(map), Some(start), Some(end)) = (map.as_ref(), to_str(start), to_str(end)) else {
+        return DeltavStatus::InvalidArgument;
+    };
+    if delta_v.is_null() {
+        return DeltavStatus::InvalidArgument;
+    }
+
+    match map.map.calculate_delta_v(start, end) {
+        Ok(Some(cost)) => {
+            *delta_v = cost;
+            DeltavStatus::Ok
+        }
+        Ok(None) => DeltavStatus::NoPath,
+        Err(_) => DeltavStatus::NoSuchNode,
+    }
+}
+
+/// Calls the callback for every category and node of the menu tree in document order, together
+/// with its depth, which is 0 for the root
+///
+/// # Safety
+/// The map has to be null or a valid map. The user data is passed to the callback as it is
+#[no_mangle]
+pub unsafe extern "C" fn deltav_map_for_each_node(
+    map: *const DeltavCalcMap,
+    callback: DeltavNodeCallback,
+    user_data: *mut c_void,
+) -> DeltavStatus {
+    let Some(map) = map.as_ref() else {
+        return DeltavStatus::InvalidArgument;
+    };
+
+    for (depth, name, id) in map.map.menu_tree().flatten() {
+        // Names can't contain NUL when they come from C, but other map formats allow it
+        let name = CString::new(name.replace('\0', "")).unwrap_or_default();
+        callback(name.as_ptr(), depth, id.is_none(), user_data);
+    }
+
+    DeltavStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn collect(name: *const c_char, depth: usize, is_category: bool, data: *mut c_void) {
+        let names = unsafe { &mut *(data as *mut Vec<(String, usize, bool)>) };
+        let name = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        names.push((name, depth, is_category));
+    }
+
+    #[test]
+    fn test_calculate() {
+        unsafe {
+            let map = deltav_map_new_stock();
+            let mut delta_v = 0;
+
+            let status = deltav_map_calculate_delta_v(
+                map,
+                c"Kerbin Surface".as_ptr(),
+                c"Mun Surface".as_ptr(),
+                &mut delta_v,
+            );
+            assert_eq!(status, DeltavStatus::Ok);
+            assert_eq!(
+                Some(delta_v),
+                DeltavMap::new_stock()
+                    .calculate_delta_v("Kerbin Surface", "Mun Surface")
+                    .unwrap()
+            );
+
+            let status = deltav_map_calculate_delta_v(
+                map,
+                c"Kerbin Surface".as_ptr(),
+                c"Nowhere".as_ptr(),
+                &mut delta_v,
+            );
+            assert_eq!(status, DeltavStatus::NoSuchNode);
+
+            let status = deltav_map_calculate_delta_v(map, ptr::null(), ptr::null(), &mut delta_v);
+            assert_eq!(status, DeltavStatus::InvalidArgument);
+
+            deltav_map_free(map);
+        }
+    }
+
+    #[test]
+    fn test_for_each_node() {
+        unsafe {
+            let text = std::fs::read("test_res/test.json").unwrap();
+            let text = CString::new(text).unwrap();
+            let map = deltav_map_new_from_json(text.as_ptr());
+            assert!(!map.is_null());
+
+            let mut names: Vec<(String, usize, bool)> = Vec::new();
+            let status =
+                deltav_map_for_each_node(map, collect, &mut names as *mut _ as *mut c_void);
+            assert_eq!(status, DeltavStatus::Ok);
+            assert_eq!(names[0], (String::from("Category1"), 0, true));
+            assert_eq!(names[1], (String::from("Category2"), 1, true));
+            assert_eq!(names[2], (String::from("Node1"), 2, false));
+
+            deltav_map_free(map);
+        }
+
+        assert!(unsafe { deltav_map_new_from_json(c"{".as_ptr()) }.is_null());
+    }
+}
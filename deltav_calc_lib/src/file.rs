@@ -0,0 +1,532 @@
+use crate::schema::UncheckedMap;
+use crate::{DeltavMap, DslError, ValidationError, ValidationOptions};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The file formats a [`DeltavMap`] can be stored in
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum MapFormat {
+    /// JSON as described in [`DeltavMap`]
+    Json,
+
+    /// The text format described in [`from_dsl`](DeltavMap::from_dsl), using the extension
+    /// `deltav`
+    Dsl,
+
+    /// A compact binary format, using the extension `dvmap`. It is only meant to be read by this
+    /// crate and may change between versions
+    #[cfg(feature = "binary")]
+    Binary,
+
+    /// YAML with the same structure as the JSON format, using the extension `yaml` or `yml`
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl MapFormat {
+    /// Detects the format from the extension of the path
+    pub fn from_path(path: &Path) -> Option<MapFormat> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(MapFormat::Json),
+            "deltav" => Some(MapFormat::Dsl),
+            #[cfg(feature = "binary")]
+            "dvmap" => Some(MapFormat::Binary),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(MapFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// This error is raised when a map can't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read
+    Io {
+        path: Option<PathBuf>,
+        error: std::io::Error,
+    },
+
+    /// The format of the file couldn't be detected from its extension
+    UnknownFormat(PathBuf),
+
+    /// The file isn't a valid map
+    Json {
+        path: Option<PathBuf>,
+        error: serde_json::Error,
+    },
+
+    /// The file isn't a valid map in the text format
+    Dsl {
+        path: Option<PathBuf>,
+        error: DslError,
+    },
+
+    /// The file isn't a valid binary map
+    #[cfg(feature = "binary")]
+    Binary {
+        path: Option<PathBuf>,
+        error: postcard::Error,
+    },
+
+    /// The file isn't a valid map in YAML
+    #[cfg(feature = "yaml")]
+    Yaml {
+        path: Option<PathBuf>,
+        error: serde_yaml::Error,
+    },
+
+    /// The map could be read, but it is invalid
+    Invalid {
+        path: Option<PathBuf>,
+        error: ValidationError,
+    },
+}
+
+impl LoadError {
+    // Adds the path of the file the map was read from
+    fn with_path(self, path: &Path) -> LoadError {
+        match self {
+            LoadError::Io { error, .. } => LoadError::Io {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            LoadError::Json { error, .. } => LoadError::Json {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            LoadError::Dsl { error, .. } => LoadError::Dsl {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            #[cfg(feature = "binary")]
+            LoadError::Binary { error, .. } => LoadError::Binary {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml { error, .. } => LoadError::Yaml {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            LoadError::Invalid { error, .. } => LoadError::Invalid {
+                path: Some(path.to_path_buf()),
+                error,
+            },
+            error => error,
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io { path: None, error } => write!(f, "Couldn't read the map: {error}"),
+            LoadError::Io {
+                path: Some(path),
+                error,
+            } => write!(f, "Couldn't read \"{}\": {error}", path.display()),
+            LoadError::UnknownFormat(path) => write!(
+                f,
+                "Couldn't detect the format of \"{}\" from its extension",
+                path.display()
+            ),
+            LoadError::Json { path: None, error } => write!(f, "Invalid map: {error}"),
+            LoadError::Json {
+                path: Some(path),
+                error,
+            } => write!(f, "Invalid map in \"{}\": {error}", path.display()),
+            LoadError::Dsl { path: None, error } => write!(f, "Invalid map: {error}"),
+            LoadError::Dsl {
+                path: Some(path),
+                error,
+            } => write!(f, "Invalid map in \"{}\": {error}", path.display()),
+            #[cfg(feature = "binary")]
+            LoadError::Binary { path: None, error } => write!(f, "Invalid binary map: {error}"),
+            #[cfg(feature = "binary")]
+            LoadError::Binary {
+                path: Some(path),
+                error,
+            } => write!(f, "Invalid binary map in \"{}\": {error}", path.display()),
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml { path: None, error } => write!(f, "Invalid map: {error}"),
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml {
+                path: Some(path),
+                error,
+            } => write!(f, "Invalid map in \"{}\": {error}", path.display()),
+            LoadError::Invalid { path: None, error } => write!(f, "Invalid map: {error}"),
+            LoadError::Invalid {
+                path: Some(path),
+                error,
+            } => write!(f, "Invalid map in \"{}\": {error}", path.display()),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { error, .. } => Some(error),
+            LoadError::UnknownFormat(_) => None,
+            LoadError::Json { error, .. } => Some(error),
+            LoadError::Dsl { error, .. } => Some(error),
+            #[cfg(feature = "binary")]
+            LoadError::Binary { error, .. } => Some(error),
+            #[cfg(feature = "yaml")]
+            LoadError::Yaml { error, .. } => Some(error),
+            LoadError::Invalid { error, .. } => Some(error),
+        }
+    }
+}
+
+/// This error is raised when a map can't be saved
+#[derive(Debug)]
+pub enum SaveError {
+    /// The file couldn't be written
+    Io {
+        path: Option<PathBuf>,
+        error: std::io::Error,
+    },
+
+    /// The format of the file couldn't be detected from its extension
+    UnknownFormat(PathBuf),
+
+    /// The map couldn't be serialized
+    Json(serde_json::Error),
+
+    /// The map couldn't be serialized in the binary format
+    #[cfg(feature = "binary")]
+    Binary(postcard::Error),
+
+    /// The map couldn't be serialized as YAML
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io { path: None, error } => write!(f, "Couldn't write the map: {error}"),
+            SaveError::Io {
+                path: Some(path),
+                error,
+            } => write!(f, "Couldn't write \"{}\": {error}", path.display()),
+            SaveError::UnknownFormat(path) => write!(
+                f,
+                "Couldn't detect the format of \"{}\" from its extension",
+                path.display()
+            ),
+            SaveError::Json(error) => write!(f, "Couldn't serialize the map: {error}"),
+            #[cfg(feature = "binary")]
+            SaveError::Binary(error) => write!(f, "Couldn't serialize the map: {error}"),
+            #[cfg(feature = "yaml")]
+            SaveError::Yaml(error) => write!(f, "Couldn't serialize the map: {error}"),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io { error, .. } => Some(error),
+            SaveError::UnknownFormat(_) => None,
+            SaveError::Json(error) => Some(error),
+            #[cfg(feature = "binary")]
+            SaveError::Binary(error) => Some(error),
+            #[cfg(feature = "yaml")]
+            SaveError::Yaml(error) => Some(error),
+        }
+    }
+}
+
+impl DeltavMap {
+    /// Loads a map from a file, detecting the format from the extension
+    ///
+    /// # Example
+    /// ```no_run
+    /// use deltav_calc::DeltavMap;
+    /// use std::path::Path;
+    ///
+    /// let map = DeltavMap::from_path(Path::new("maps/stock.json")).unwrap();
+    /// ```
+    pub fn from_path(path: &Path) -> Result<DeltavMap, LoadError> {
+        DeltavMap::from_path_with_options(path, &ValidationOptions::new())
+    }
+
+    /// Loads a map from a file like [`from_path`](DeltavMap::from_path), validating it with the
+    /// given options
+    pub fn from_path_with_options(
+        path: &Path,
+        options: &ValidationOptions,
+    ) -> Result<DeltavMap, LoadError> {
+        let format =
+            MapFormat::from_path(path).ok_or_else(|| LoadError::UnknownFormat(path.into()))?;
+        let file = File::open(path).map_err(|error| LoadError::Io {
+            path: Some(path.into()),
+            error,
+        })?;
+
+        DeltavMap::from_reader_with_options(BufReader::new(file), format, options)
+            .map_err(|e| e.with_path(path))
+    }
+
+    /// Reads a map in JSON format
+    pub fn from_reader(reader: impl Read) -> Result<DeltavMap, LoadError> {
+        DeltavMap::from_reader_with_format(reader, MapFormat::Json)
+    }
+
+    /// Reads a map in the given format
+    pub fn from_reader_with_format(
+        reader: impl Read,
+        format: MapFormat,
+    ) -> Result<DeltavMap, LoadError> {
+        DeltavMap::from_reader_with_options(reader, format, &ValidationOptions::new())
+    }
+
+    /// Reads a map in the given format, validating it with the given options
+    pub fn from_reader_with_options(
+        reader: impl Read,
+        format: MapFormat,
+        options: &ValidationOptions,
+    ) -> Result<DeltavMap, LoadError> {
+        let map: DeltavMap = match format {
+            MapFormat::Json => {
+                // Reading everything first is faster and lets the names be borrowed while parsing
+                let mut reader = reader;
+                let mut bytes = Vec::new();
+                reader
+                    .read_to_end(&mut bytes)
+                    .map_err(|error| LoadError::Io { path: None, error })?;
+                serde_json::from_slice::<UncheckedMap>(&bytes)
+                    .map_err(|error| LoadError::Json { path: None, error })?
+                    .0
+            }
+            MapFormat::Dsl => {
+                let mut reader = reader;
+                let mut text = String::new();
+                reader
+                    .read_to_string(&mut text)
+                    .map_err(|error| LoadError::Io { path: None, error })?;
+                DeltavMap::from_dsl(&text).map_err(|error| LoadError::Dsl { path: None, error })?
+            }
+            #[cfg(feature = "binary")]
+            MapFormat::Binary => {
+                let mut reader = reader;
+                let mut bytes = Vec::new();
+                reader
+                    .read_to_end(&mut bytes)
+                    .map_err(|error| LoadError::Io { path: None, error })?;
+                postcard::from_bytes::<UncheckedMap>(&bytes)
+                    .map_err(|error| LoadError::Binary { path: None, error })?
+                    .0
+            }
+            #[cfg(feature = "yaml")]
+            MapFormat::Yaml => {
+                serde_yaml::from_reader::<_, UncheckedMap>(reader)
+                    .map_err(|error| LoadError::Yaml { path: None, error })?
+                    .0
+            }
+        };
+
+        map.validated(options)
+    }
+}
+
+impl DeltavMap {
+    /// Saves the map to a file, detecting the format from the extension. JSON is pretty-printed
+    ///
+    /// # Example
+    /// ```no_run
+    /// use deltav_calc::DeltavMap;
+    /// use std::path::Path;
+    ///
+    /// DeltavMap::new_stock().save(Path::new("stock.json")).unwrap();
+    /// ```
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        let format =
+            MapFormat::from_path(path).ok_or_else(|| SaveError::UnknownFormat(path.into()))?;
+        let io_error = |error| SaveError::Io {
+            path: Some(path.into()),
+            error,
+        };
+
+        let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+        match self.to_writer_with_format(&mut writer, format) {
+            Err(SaveError::Io { error, .. }) => return Err(io_error(error)),
+            result => result?,
+        }
+        writer.flush().map_err(io_error)
+    }
+
+    /// Writes the map as pretty-printed JSON
+    pub fn to_writer(&self, writer: impl Write) -> Result<(), SaveError> {
+        self.to_writer_with_format(writer, MapFormat::Json)
+    }
+
+    /// Writes the map as JSON without any whitespace
+    pub fn to_writer_compact(&self, writer: impl Write) -> Result<(), SaveError> {
+        serde_json::to_writer(writer, self).map_err(SaveError::Json)
+    }
+
+    /// Writes the map in the given format. JSON is pretty-printed
+    pub fn to_writer_with_format(
+        &self,
+        writer: impl Write,
+        format: MapFormat,
+    ) -> Result<(), SaveError> {
+        match format {
+            MapFormat::Json => serde_json::to_writer_pretty(writer, self).map_err(SaveError::Json),
+            MapFormat::Dsl => {
+                let mut writer = writer;
+                writer
+                    .write_all(self.to_dsl().as_bytes())
+                    .map_err(|error| SaveError::Io { path: None, error })
+            }
+            #[cfg(feature = "binary")]
+            MapFormat::Binary => postcard::to_io(self, writer)
+                .map(|_| ())
+                .map_err(SaveError::Binary),
+            #[cfg(feature = "yaml")]
+            MapFormat::Yaml => serde_yaml::to_writer(writer, self).map_err(SaveError::Yaml),
+        }
+    }
+
+    /// Reads a map from the compact binary format
+    #[cfg(feature = "binary")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<DeltavMap, LoadError> {
+        postcard::from_bytes::<UncheckedMap>(bytes)
+            .map_err(|error| LoadError::Binary { path: None, error })?
+            .0
+            .validated(&ValidationOptions::new())
+    }
+
+    // Validates a freshly loaded map
+    fn validated(self, options: &ValidationOptions) -> Result<DeltavMap, LoadError> {
+        match self.validate_with_options(options) {
+            Ok(()) => Ok(self),
+            Err(error) => Err(LoadError::Invalid { path: None, error }),
+        }
+    }
+
+    /// Serializes the map to the compact binary format
+    #[cfg(feature = "binary")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SaveError> {
+        postcard::to_allocvec(self).map_err(SaveError::Binary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, LoadError, MapFormat, SaveError};
+    use std::path::Path;
+
+    #[test]
+    fn test_from_path() {
+        let map = DeltavMap::from_path(Path::new("test_res/test.json")).unwrap();
+        assert_eq!(map, get_test_map());
+
+        let map = DeltavMap::from_path(Path::new("test_res/test.deltav")).unwrap();
+        assert_eq!(map.calculate_delta_v("Node1", "Node4").unwrap(), Some(1030));
+
+        let result = DeltavMap::from_path(Path::new("test_res/missing.json"));
+        assert!(matches!(result, Err(LoadError::Io { .. })));
+
+        let result = DeltavMap::from_path(Path::new("test_res/nodes.csv"));
+        assert!(matches!(result, Err(LoadError::UnknownFormat(_))));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let result = DeltavMap::from_reader(r#"{"menu_tree": 5}"#.as_bytes());
+        match result {
+            Err(e @ LoadError::Json { .. }) => {
+                assert!(e.to_string().starts_with("Invalid map: "))
+            }
+            _ => panic!("The map shouldn't be valid"),
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            MapFormat::from_path(Path::new("stock.JSON")),
+            Some(MapFormat::Json)
+        );
+        assert_eq!(
+            MapFormat::from_path(Path::new("stock.deltav")),
+            Some(MapFormat::Dsl)
+        );
+        assert_eq!(MapFormat::from_path(Path::new("stock")), None);
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join("deltav_calc_test_save.json");
+        get_test_map().save(&path).unwrap();
+        let map = DeltavMap::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(map, get_test_map());
+
+        let result = get_test_map().save(Path::new("test.txt"));
+        assert!(matches!(result, Err(SaveError::UnknownFormat(_))));
+    }
+
+    #[test]
+    fn test_to_writer() {
+        let mut pretty = Vec::new();
+        get_test_map().to_writer(&mut pretty).unwrap();
+        let mut compact = Vec::new();
+        get_test_map().to_writer_compact(&mut compact).unwrap();
+
+        assert!(pretty.contains(&b'\n'));
+        assert!(!compact.contains(&b'\n'));
+        assert_eq!(
+            DeltavMap::from_reader(pretty.as_slice()).unwrap(),
+            DeltavMap::from_reader(compact.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "binary")]
+    fn test_binary() {
+        let bytes = get_test_map().to_bytes().unwrap();
+        assert_eq!(DeltavMap::from_bytes(&bytes).unwrap(), get_test_map());
+
+        let stock = DeltavMap::new_stock();
+        let bytes = stock.to_bytes().unwrap();
+        let mut json = Vec::new();
+        stock.to_writer_compact(&mut json).unwrap();
+        assert!(bytes.len() < json.len());
+        assert_eq!(DeltavMap::from_bytes(&bytes).unwrap(), stock);
+
+        let result = DeltavMap::from_bytes(&bytes[..bytes.len() / 2]);
+        assert!(matches!(result, Err(LoadError::Binary { .. })));
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_yaml() {
+        assert_eq!(
+            MapFormat::from_path(Path::new("stock.yml")),
+            Some(MapFormat::Yaml)
+        );
+
+        let stock = DeltavMap::new_stock();
+        let mut yaml = Vec::new();
+        stock
+            .to_writer_with_format(&mut yaml, MapFormat::Yaml)
+            .unwrap();
+        let map = DeltavMap::from_reader_with_format(yaml.as_slice(), MapFormat::Yaml).unwrap();
+        assert_eq!(map, stock);
+
+        let result = DeltavMap::from_reader_with_format("menu_tree: 5".as_bytes(), MapFormat::Yaml);
+        assert!(matches!(result, Err(LoadError::Yaml { .. })));
+    }
+}
@@ -0,0 +1,388 @@
+use crate::math;
+use crate::{MarginProfile, Route};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// This error is raised when a [`FlightLog`] can't be read, written or updated
+#[derive(Debug)]
+pub enum FlightLogError {
+    /// The file couldn't be read or written
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+
+    /// The file isn't a valid flight log
+    Json(serde_json::Error),
+
+    /// There is no flight with the index
+    NoSuchFlight(usize),
+
+    /// The flight has no leg with the index
+    NoSuchLeg { flight: usize, leg: usize },
+}
+
+impl Display for FlightLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            FlightLogError::Io(error) => write!(f, "Couldn't access the flight log: {error}"),
+            FlightLogError::Json(error) => write!(f, "Invalid flight log: {error}"),
+            FlightLogError::NoSuchFlight(flight) => write!(f, "There is no flight {flight}"),
+            FlightLogError::NoSuchLeg { flight, leg } => {
+                write!(f, "The flight {flight} has no leg {leg}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for FlightLogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FlightLogError::Io(error) => Some(error),
+            FlightLogError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for FlightLogError {
+    fn from(error: std::io::Error) -> Self {
+        FlightLogError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for FlightLogError {
+    fn from(error: serde_json::Error) -> Self {
+        FlightLogError::Json(error)
+    }
+}
+
+/// A leg of a logged flight with the deltav that was planned and the deltav that was spent
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LoggedLeg {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) planned: i64,
+    pub(crate) aerobrake: bool,
+    pub(crate) spent: Option<i64>,
+}
+
+impl LoggedLeg {
+    /// The name of the node the leg starts at
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// The name of the node the leg ends at
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// The deltav the map planned for the leg
+    pub fn planned(&self) -> i64 {
+        self.planned
+    }
+
+    /// Checks if the plan relied on aerobraking
+    pub fn aerobrake(&self) -> bool {
+        self.aerobrake
+    }
+
+    /// The deltav that was actually spent, if it was recorded
+    pub fn spent(&self) -> Option<i64> {
+        self.spent
+    }
+
+    /// How many percent more deltav was spent than planned. Negative if less was spent and
+    /// `None` if nothing was recorded or nothing was planned
+    pub fn deviation(&self) -> Option<f64> {
+        let spent = self.spent?;
+        if self.planned <= 0 {
+            return None;
+        }
+        Some((spent - self.planned) as f64 / self.planned as f64 * 100.0)
+    }
+}
+
+/// A planned route and what was spent flying it
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Flight {
+    pub(crate) name: String,
+    pub(crate) planned_at: u64,
+    pub(crate) legs: Vec<LoggedLeg>,
+}
+
+impl Flight {
+    /// The name the flight was logged under
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// When the flight was planned in seconds since the Unix epoch
+    pub fn planned_at(&self) -> u64 {
+        self.planned_at
+    }
+
+    /// The legs in the order they are flown
+    pub fn legs(&self) -> &[LoggedLeg] {
+        &self.legs
+    }
+
+    /// The deltav planned for the whole flight
+    pub fn planned(&self) -> i64 {
+        self.legs.iter().map(|leg| leg.planned).sum()
+    }
+
+    /// The deltav spent on the legs that were recorded so far
+    pub fn spent(&self) -> i64 {
+        self.legs.iter().filter_map(|leg| leg.spent).sum()
+    }
+}
+
+/// How well the plans of a [`FlightLog`] matched the deltav that was spent
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Accuracy {
+    legs: usize,
+    mean_deviation: f64,
+    worst_overrun: f64,
+}
+
+impl Accuracy {
+    /// The number of recorded legs the statistics are based on
+    pub fn legs(&self) -> usize {
+        self.legs
+    }
+
+    /// The average of how many percent more deltav was spent than planned
+    pub fn mean_deviation(&self) -> f64 {
+        self.mean_deviation
+    }
+
+    /// The most a leg went over its plan in percent, or 0 if every leg stayed within it
+    pub fn worst_overrun(&self) -> f64 {
+        self.worst_overrun
+    }
+
+    /// The smallest margin in percent that would have covered every recorded leg
+    pub fn suggested_margin(&self) -> u32 {
+        math::ceil(self.worst_overrun) as u32
+    }
+
+    /// A margin profile with the suggested margin
+    pub fn margin_profile(&self, name: &str) -> MarginProfile {
+        MarginProfile::new(name, self.suggested_margin())
+    }
+}
+
+/// A record of planned routes and the deltav actually spent on every leg, to calibrate personal
+/// margins against the theoretical values of a map. It is stored as JSON
+///
+/// # Example
+/// ```
+/// use deltav_calc::{CalcOptions, DeltavMap, FlightLog};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let route = stock_map
+///     .calculate_route("Kerbin Surface", "Low Kerbin Orbit (80km)", &CalcOptions::new())
+///     .unwrap()
+///     .unwrap();
+///
+/// let mut log = FlightLog::new();
+/// let flight = log.add_flight("Orbiter 1", &route);
+/// log.record(flight, 0, 3600).unwrap();
+///
+/// let accuracy = log.accuracy().unwrap();
+/// assert_eq!(accuracy.suggested_margin(), 6);
+/// ```
+#[derive(Deserialize, Serialize, Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct FlightLog {
+    pub(crate) flights: Vec<Flight>,
+}
+
+impl FlightLog {
+    /// Creates an empty log
+    pub fn new() -> FlightLog {
+        FlightLog::default()
+    }
+
+    /// Reads a log from a file. A file that doesn't exist yet is an empty log
+    #[cfg(feature = "std")]
+    pub fn load(path: &Path) -> Result<FlightLog, FlightLogError> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(FlightLog::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Writes the log to a file as pretty-printed JSON
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &Path) -> Result<(), FlightLogError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        Ok(writer.flush()?)
+    }
+
+    /// The logged flights in the order they were added
+    pub fn flights(&self) -> &[Flight] {
+        &self.flights
+    }
+
+    /// Adds the planned route as a new flight and returns its index
+    #[cfg(feature = "std")]
+    pub fn add_flight(&mut self, name: &str, route: &Route) -> usize {
+        let planned_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        self.add_flight_at(name, route, planned_at)
+    }
+
+    /// Adds the planned route as a new flight like [`add_flight`](FlightLog::add_flight), planned
+    /// at the time in seconds since the Unix epoch. Without the `std` feature there is no clock,
+    /// so this is the only way to add flights
+    pub fn add_flight_at(&mut self, name: &str, route: &Route, planned_at: u64) -> usize {
+        let legs = route
+            .legs
+            .iter()
+            .map(|leg| LoggedLeg {
+                from: leg.from.clone(),
+                to: leg.to.clone(),
+                planned: leg.delta_v as i64,
+                aerobrake: leg.aerobrake,
+                spent: None,
+            })
+            .collect();
+
+        self.flights.push(Flight {
+            name: name.to_string(),
+            planned_at,
+            legs,
+        });
+        self.flights.len() - 1
+    }
+
+    /// Records the deltav spent on a leg of a flight, replacing an earlier record
+    pub fn record(&mut self, flight: usize, leg: usize, spent: i64) -> Result<(), FlightLogError> {
+        let logged = self
+            .flights
+            .get_mut(flight)
+            .ok_or(FlightLogError::NoSuchFlight(flight))?
+            .legs
+            .get_mut(leg)
+            .ok_or(FlightLogError::NoSuchLeg { flight, leg })?;
+        logged.spent = Some(spent);
+        Ok(())
+    }
+
+    /// The accuracy of the plans of all flights, or `None` if no leg was recorded yet
+    pub fn accuracy(&self) -> Option<Accuracy> {
+        self.accuracy_since(0)
+    }
+
+    /// The accuracy of the plans of the flights planned at or after the time in seconds since
+    /// the Unix epoch, to see if the flying got better over time
+    pub fn accuracy_since(&self, time: u64) -> Option<Accuracy> {
+        let deviations: Vec<f64> = self
+            .flights
+            .iter()
+            .filter(|flight| flight.planned_at >= time)
+            .flat_map(|flight| &flight.legs)
+            .filter_map(LoggedLeg::deviation)
+            .collect();
+
+        if deviations.is_empty() {
+            return None;
+        }
+
+        Some(Accuracy {
+            legs: deviations.len(),
+            mean_deviation: deviations.iter().sum::<f64>() / deviations.len() as f64,
+            worst_overrun: deviations.iter().copied().fold(0.0, f64::max),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CalcOptions, DeltavMap, FlightLog, FlightLogError, MarginProfile, Route};
+
+    fn get_route() -> Route {
+        DeltavMap::from_dsl(
+            "
+            [Test]
+            Surface
+            Orbit
+            Moon
+            Surface -> Orbit : 3400
+            Orbit -> Moon : 860
+            ",
+        )
+        .unwrap()
+        .calculate_route("Surface", "Moon", &CalcOptions::new())
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let mut log = FlightLog::new();
+        assert_eq!(log.accuracy(), None);
+
+        let flight = log.add_flight("Test", &get_route());
+        log.record(flight, 0, 3570).unwrap();
+        log.record(flight, 1, 817).unwrap();
+
+        assert_eq!(log.flights()[flight].planned(), 4260);
+        assert_eq!(log.flights()[flight].spent(), 4387);
+        assert_eq!(log.flights()[flight].legs()[0].deviation(), Some(5.0));
+
+        let accuracy = log.accuracy().unwrap();
+        assert_eq!(accuracy.legs(), 2);
+        assert!((accuracy.mean_deviation() - 0.0).abs() < 1e-9);
+        assert!((accuracy.worst_overrun() - 5.0).abs() < 1e-9);
+        assert_eq!(
+            accuracy.margin_profile("mine"),
+            MarginProfile::new("mine", 5)
+        );
+        assert_eq!(log.accuracy_since(u64::MAX), None);
+
+        assert!(matches!(
+            log.record(1, 0, 0),
+            Err(FlightLogError::NoSuchFlight(1))
+        ));
+        assert!(matches!(
+            log.record(0, 2, 0),
+            Err(FlightLogError::NoSuchLeg { flight: 0, leg: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("deltav_calc_test_flight_log.json");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(FlightLog::load(&path).unwrap(), FlightLog::new());
+
+        let mut log = FlightLog::new();
+        let flight = log.add_flight("Test", &get_route());
+        log.record(flight, 1, 900).unwrap();
+        log.save(&path).unwrap();
+
+        let loaded = FlightLog::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, log);
+        assert_eq!(loaded.flights()[0].legs()[1].spent(), Some(900));
+    }
+}
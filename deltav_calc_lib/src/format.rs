@@ -0,0 +1,174 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+/// Formats deltav values for display, for example as "3,400 m/s" or "13.7 km/s"
+///
+/// Values below [`DeltavFormat::kilometers_from`] are shown in m/s without decimals, larger ones
+/// in km/s with the configured precision. The separators can be taken from a locale with
+/// [`DeltavFormat::for_locale`]
+///
+/// # Example
+/// ```
+/// use deltav_calc::DeltavFormat;
+///
+/// let format = DeltavFormat::new();
+/// assert_eq!(format.format(3400), "3,400 m/s");
+/// assert_eq!(format.format(13_740), "13.7 km/s");
+///
+/// let german = DeltavFormat::for_locale("de-DE").precision(2);
+/// assert_eq!(german.format(13_740), "13,74 km/s");
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct DeltavFormat {
+    pub(crate) thousands_separator: Option<char>,
+    pub(crate) decimal_separator: char,
+    pub(crate) precision: u32,
+    pub(crate) kilometers_from: Option<i64>,
+}
+
+impl Default for DeltavFormat {
+    fn default() -> Self {
+        DeltavFormat {
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+            precision: 1,
+            kilometers_from: Some(10_000),
+        }
+    }
+}
+
+impl DeltavFormat {
+    /// Creates a format with English separators that switches to km/s at 10,000 m/s
+    pub fn new() -> DeltavFormat {
+        DeltavFormat::default()
+    }
+
+    /// Creates a format with the separators used by the locale, for example "de-DE" or
+    /// "fr_FR.UTF-8". Unknown locales use English separators
+    pub fn for_locale(locale: &str) -> DeltavFormat {
+        let language = locale
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let (thousands, decimal) = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => ('.', ','),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+                ('\u{202f}', ',')
+            }
+            _ => (',', '.'),
+        };
+
+        DeltavFormat::new()
+            .thousands_separator(Some(thousands))
+            .decimal_separator(decimal)
+    }
+
+    /// Sets the character between groups of three digits. `None` doesn't group the digits
+    pub fn thousands_separator(mut self, separator: Option<char>) -> DeltavFormat {
+        self.thousands_separator = separator;
+        self
+    }
+
+    /// Sets the character between the whole and the fractional part of km/s values
+    pub fn decimal_separator(mut self, separator: char) -> DeltavFormat {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Sets the number of decimals of km/s values
+    pub fn precision(mut self, precision: u32) -> DeltavFormat {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the deltav in m/s from which on values are shown in km/s. `None` always uses m/s
+    pub fn kilometers_from(mut self, threshold: Option<i64>) -> DeltavFormat {
+        self.kilometers_from = threshold;
+        self
+    }
+
+    /// Formats the deltav, which is given in m/s
+    pub fn format(&self, delta_v: i64) -> String {
+        let sign = if delta_v < 0 { "-" } else { "" };
+        let meters = delta_v.unsigned_abs();
+
+        if self
+            .kilometers_from
+            .is_none_or(|threshold| meters < threshold.unsigned_abs())
+        {
+            return format!("{sign}{} m/s", self.group(meters as u128));
+        }
+
+        // Rounds half away from zero, in integers so large values stay exact
+        let scale = 10u128.pow(self.precision);
+        let scaled = (meters as u128 * scale + 500) / 1000;
+        let whole = self.group(scaled / scale);
+
+        if self.precision == 0 {
+            format!("{sign}{whole} km/s")
+        } else {
+            let width = self.precision as usize;
+            format!(
+                "{sign}{whole}{}{:0width$} km/s",
+                self.decimal_separator,
+                scaled % scale
+            )
+        }
+    }
+
+    // Writes the digits of the number with the thousands separator between groups
+    fn group(&self, number: u128) -> String {
+        let digits = number.to_string();
+        let Some(separator) = self.thousands_separator else {
+            return digits;
+        };
+
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DeltavFormat;
+
+    #[test]
+    fn test_format() {
+        let format = DeltavFormat::new();
+        assert_eq!(format.format(0), "0 m/s");
+        assert_eq!(format.format(980), "980 m/s");
+        assert_eq!(format.format(3400), "3,400 m/s");
+        assert_eq!(format.format(-3400), "-3,400 m/s");
+        assert_eq!(format.format(13_650), "13.7 km/s");
+        assert_eq!(format.format(1_234_567), "1,234.6 km/s");
+
+        let meters = format.kilometers_from(None).thousands_separator(None);
+        assert_eq!(meters.format(1_234_567), "1234567 m/s");
+
+        let rounded = format.precision(0).kilometers_from(Some(1000));
+        assert_eq!(rounded.format(2500), "3 km/s");
+        assert_eq!(rounded.format(i64::MIN), "-9,223,372,036,854,776 km/s");
+    }
+
+    #[test]
+    fn test_locale() {
+        assert_eq!(
+            DeltavFormat::for_locale("de_DE.UTF-8").format(13_740),
+            "13,7 km/s"
+        );
+        assert_eq!(
+            DeltavFormat::for_locale("fr").format(3400),
+            "3\u{202f}400 m/s"
+        );
+        assert_eq!(DeltavFormat::for_locale("en-US"), DeltavFormat::new());
+        assert_eq!(DeltavFormat::for_locale("xx"), DeltavFormat::new());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::MenuTree;
+use alloc::vec::Vec;
+
+impl MenuTree {
+    /// Finds the nodes whose names contain the characters of the query in order, ignoring the
+    /// case and whitespace in the query, with the best matches first. Matches at the start of
+    /// words and runs of consecutive characters rank higher, then shorter names
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let results = stock_map.menu_tree().fuzzy_search("low eeloo");
+    /// assert_eq!(results[0], "Low Eeloo Orbit (10km)");
+    /// ```
+    pub fn fuzzy_search(&self, query: &str) -> Vec<&str> {
+        let query: Vec<char> = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        let mut matches: Vec<(i64, &str)> = self
+            .iter()
+            .filter(|node| node.id().is_some())
+            .filter_map(|node| Some((fuzzy_score(node.name(), &query)?, node.name())))
+            .collect();
+        matches.sort_by(|(a_score, a_name), (b_score, b_name)| {
+            b_score
+                .cmp(a_score)
+                .then(a_name.len().cmp(&b_name.len()))
+                .then(a_name.cmp(b_name))
+        });
+
+        matches.into_iter().map(|(_, name)| name).collect()
+    }
+}
+
+// Scores how well the name matches the lowercase query, or returns None if the name doesn't
+// contain all characters of the query in order
+fn fuzzy_score(name: &str, query: &[char]) -> Option<i64> {
+    let mut remaining = query.iter().peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in name.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let matched = remaining.peek().is_some_and(|&&wanted| wanted == lower);
+
+        if matched {
+            remaining.next();
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+        }
+
+        previous_matched = matched;
+        previous = Some(c);
+    }
+
+    remaining.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+    use crate::DeltavMap;
+
+    fn chars(query: &str) -> Vec<char> {
+        query.chars().collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("Mun", &chars("")), Some(0));
+        assert_eq!(fuzzy_score("Mun", &chars("mn")), Some(5));
+        assert_eq!(fuzzy_score("Mun", &chars("mu")), Some(7));
+        assert_eq!(fuzzy_score("Mun", &chars("nm")), None);
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Kerbol/Eeloo]
+            Eeloo Surface
+            Low Eeloo Orbit (10km)
+            Eeloo Surface -> Low Eeloo Orbit (10km) : 620
+
+            [Kerbol/Eve]
+            Eve Surface
+            Low Eve Orbit (100km)
+            Eve Surface -> Low Eve Orbit (100km) : 8000
+            ",
+        )
+        .unwrap();
+        let tree = map.menu_tree();
+
+        assert_eq!(
+            tree.fuzzy_search("low eeloo"),
+            vec!["Low Eeloo Orbit (10km)"]
+        );
+        assert_eq!(
+            tree.fuzzy_search("es"),
+            vec!["Eve Surface", "Eeloo Surface"]
+        );
+        assert_eq!(tree.fuzzy_search("").len(), 4);
+        assert!(tree.fuzzy_search("Kerbol").is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{DeltavMap, EdgeInfo, MenuTree, Metadata, MAX_DEPTH};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Generates random but valid maps, for example for benchmarks or to test user interfaces with
+/// large maps. The same settings always generate the same map
+///
+/// Every node is connected to the map, so there is a route between any two nodes. The names of
+/// nodes and categories are unique
+///
+/// # Example
+/// ```
+/// use deltav_calc::MapGenerator;
+///
+/// let map = MapGenerator::new()
+///     .seed(42)
+///     .node_count(500)
+///     .depth(4)
+///     .cost_range(50, 4000)
+///     .generate();
+///
+/// assert!(map.validate().is_ok());
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MapGenerator {
+    pub(crate) seed: u64,
+    pub(crate) node_count: usize,
+    pub(crate) depth: usize,
+    pub(crate) branching: usize,
+    pub(crate) min_cost: i32,
+    pub(crate) max_cost: i32,
+    pub(crate) extra_edges: usize,
+}
+
+impl Default for MapGenerator {
+    fn default() -> Self {
+        MapGenerator {
+            seed: 0,
+            node_count: 50,
+            depth: 3,
+            branching: 3,
+            min_cost: 10,
+            max_cost: 3000,
+            extra_edges: 0,
+        }
+    }
+}
+
+impl MapGenerator {
+    /// Creates a generator with the default settings
+    pub fn new() -> MapGenerator {
+        MapGenerator::default()
+    }
+
+    /// Sets the seed of the random numbers
+    pub fn seed(mut self, seed: u64) -> MapGenerator {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the number of nodes
+    pub fn node_count(mut self, count: usize) -> MapGenerator {
+        self.node_count = count;
+        self
+    }
+
+    /// Sets how many levels of categories there are below the root category, at most one less
+    /// than [`MAX_DEPTH`]
+    pub fn depth(mut self, depth: usize) -> MapGenerator {
+        self.depth = depth.min(MAX_DEPTH - 1);
+        self
+    }
+
+    /// Sets how many subcategories every category can have
+    pub fn branching(mut self, branching: usize) -> MapGenerator {
+        self.branching = branching.max(1);
+        self
+    }
+
+    /// Sets the range the costs of the edges are taken from. Costs are at least 1
+    pub fn cost_range(mut self, min: i32, max: i32) -> MapGenerator {
+        self.min_cost = min.max(1);
+        self.max_cost = max.max(self.min_cost);
+        self
+    }
+
+    /// Sets how many edges are added on top of the ones connecting the nodes, which creates
+    /// alternative routes
+    pub fn extra_edges(mut self, count: usize) -> MapGenerator {
+        self.extra_edges = count;
+        self
+    }
+
+    /// Generates the map
+    pub fn generate(&self) -> DeltavMap {
+        let mut random = SplitMix(self.seed);
+        let mut graph: UnGraph<Arc<str>, EdgeInfo> = UnGraph::new_undirected();
+        let mut menu_tree: Option<MenuTree> = None;
+
+        for node in 0..self.node_count {
+            let mut path = vec![String::from("Generated")];
+            let mut category = String::from("Category");
+            for level in 0..self.depth {
+                let child = random.below(self.branching);
+                category = match level {
+                    0 => format!("{category} {child}"),
+                    _ => format!("{category}.{child}"),
+                };
+                path.push(category.clone());
+            }
+
+            let name: Arc<str> = Arc::from(format!("Node {node}"));
+            let index = graph.add_node(name.clone());
+            let end_node = MenuTree::from_node(name, Some(index.into()));
+            MenuTree::insert_at_path(&mut menu_tree, &path.join("/"), end_node)
+                .expect("All paths start with the same root");
+
+            // Connecting every node to an earlier one keeps the map connected
+            if node > 0 {
+                let other = NodeIndex::new(random.below(node));
+                let cost = self.cost(&mut random);
+                graph.add_edge(other, index, EdgeInfo::new(cost));
+            }
+        }
+
+        // Pairs that are already connected are skipped, so there may be fewer extra edges
+        for _ in 0..self.extra_edges {
+            if self.node_count < 2 {
+                break;
+            }
+            let from = NodeIndex::new(random.below(self.node_count));
+            let to = NodeIndex::new(random.below(self.node_count));
+            if from != to && graph.find_edge(from, to).is_none() {
+                let cost = self.cost(&mut random);
+                graph.add_edge(from, to, EdgeInfo::new(cost));
+            }
+        }
+
+        DeltavMap {
+            menu_tree: menu_tree.unwrap_or_else(|| MenuTree::new_category("Generated")),
+            graph,
+            tags: BTreeMap::new(),
+            metadata: Metadata::default(),
+        }
+    }
+
+    fn cost(&self, random: &mut SplitMix) -> i32 {
+        let range = (self.max_cost - self.min_cost) as usize + 1;
+        self.min_cost + random.below(range) as i32
+    }
+}
+
+// A small pseudo random number generator, which is good enough for generating maps and keeps the
+// generated maps the same across versions
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // A number below the bound, which has to be greater than zero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MapGenerator;
+
+    #[test]
+    fn test_generate() {
+        let generator = MapGenerator::new()
+            .seed(7)
+            .node_count(200)
+            .depth(3)
+            .cost_range(100, 200)
+            .extra_edges(50);
+        let map = generator.generate();
+
+        assert!(map.validate().is_ok());
+        assert_eq!(map, generator.generate());
+        assert_ne!(map, generator.clone().seed(8).generate());
+        assert_eq!(map.menu_tree().end_nodes().len(), 200);
+        assert!(map
+            .edges()
+            .all(|(_, _, edge)| (100..=200).contains(&edge.cost())));
+        assert!(map
+            .calculate_delta_v("Node 0", "Node 199")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_empty() {
+        let map = MapGenerator::new().node_count(0).extra_edges(5).generate();
+
+        assert_eq!(map.menu_tree().name(), "Generated");
+        assert!(map.validate().is_ok());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{DeltavMap, EdgeInfo, NodePayload};
+use alloc::format;
+use alloc::vec::Vec;
+use petgraph::visit::EdgeRef;
+
+// Collects the bytes that are hashed with 64 bit FNV-1a, which unlike the hashers of the
+// standard library is guaranteed to stay the same
+struct ContentHasher(Vec<u8>);
+
+impl ContentHasher {
+    fn new() -> ContentHasher {
+        ContentHasher(Vec::new())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    // Strings are prefixed with their length, so their boundaries are part of the hash
+    fn write_str(&mut self, text: &str) {
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+
+    fn write_option(&mut self, value: Option<impl Into<f64>>) {
+        match value {
+            None => self.write(&[0]),
+            Some(value) => {
+                self.write(&[1]);
+                self.write(&value.into().to_bits().to_le_bytes());
+            }
+        }
+    }
+
+    fn write_edge(&mut self, info: &EdgeInfo) {
+        self.write(&info.cost.to_le_bytes());
+        self.write_option(info.min);
+        self.write_option(info.max);
+        self.write_option(info.aerobrake);
+        self.write_option(info.low_thrust);
+        self.write_option(info.time.map(|time| time as f64));
+
+        let window = info.window;
+        self.write_option(window.map(|w| w.phase_angle));
+        self.write_option(window.map(|w| w.synodic_period));
+        self.write_option(window.and_then(|w| w.ejection_angle));
+
+        self.write(&(info.tags.len() as u64).to_le_bytes());
+        for tag in info.tags() {
+            self.write_str(tag);
+        }
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Returns a hash of the nodes, their tags and the edges of the map. The hash doesn't depend
+    /// on the order of the nodes and edges or on the menu tree and the metadata, and stays the
+    /// same across versions of this crate, so it can be used to cache data calculated from the
+    /// map
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+
+        let mut nodes: Vec<&str> = self.graph.node_weights().map(N::name).collect();
+        nodes.sort();
+        hasher.write(&(nodes.len() as u64).to_le_bytes());
+        for node in nodes {
+            hasher.write_str(node);
+            let tags = self.tags.get(node).into_iter().flatten();
+            hasher.write(&(tags.clone().count() as u64).to_le_bytes());
+            for tag in tags {
+                hasher.write_str(tag);
+            }
+        }
+
+        // The edges are undirected, so their nodes are sorted as well unless aerobraking makes
+        // the direction matter. The edges are sorted by everything that is hashed of them, so
+        // parallel edges don't depend on the order they were added in
+        let mut edges: Vec<Vec<u8>> = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let source = self.graph[edge.source()].name();
+                let target = self.graph[edge.target()].name();
+                let (from, to) = if edge.weight().aerobrake.is_some() {
+                    (source, target)
+                } else {
+                    (source.min(target), source.max(target))
+                };
+
+                let mut edge_hasher = ContentHasher::new();
+                edge_hasher.write_str(from);
+                edge_hasher.write_str(to);
+                edge_hasher.write_edge(edge.weight());
+                edge_hasher.0
+            })
+            .collect();
+        edges.sort();
+        hasher.write(&(edges.len() as u64).to_le_bytes());
+        for edge in edges {
+            hasher.write(&edge);
+        }
+
+        hasher.finish()
+    }
+
+    /// Stores the [`content_hash`](DeltavMap::content_hash) in the metadata, so it is verified
+    /// when the map is loaded again
+    pub fn embed_content_hash(&mut self) {
+        self.metadata.content_hash = Some(format!("{:016x}", self.content_hash()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, EdgeInfo};
+
+    #[test]
+    fn test_content_hash() {
+        let map = get_test_map();
+        assert_eq!(map.content_hash(), get_test_map().content_hash());
+
+        let text = std::fs::read_to_string("test_res/test.deltav").unwrap();
+        let dsl_map = DeltavMap::from_dsl(&text).unwrap();
+        assert_eq!(map.content_hash(), dsl_map.content_hash());
+
+        let mut changed = get_test_map();
+        let edge = changed.graph.edge_indices().next().unwrap();
+        changed.graph[edge] = EdgeInfo::new(901);
+        assert_ne!(map.content_hash(), changed.content_hash());
+
+        let hash = |text| DeltavMap::from_dsl(text).unwrap().content_hash();
+        assert_ne!(
+            hash("[Kerbol]\nA\nB\nA -> B : 100 aerobrake=0"),
+            hash("[Kerbol]\nA\nB\nB -> A : 100 aerobrake=0")
+        );
+        assert_eq!(
+            hash("[Kerbol]\nA\nB\nA -> B : 100 time=10\nA -> B : 100 time=20"),
+            hash("[Kerbol]\nA\nB\nA -> B : 100 time=20\nA -> B : 100 time=10")
+        );
+    }
+}
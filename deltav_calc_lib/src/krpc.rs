@@ -0,0 +1,434 @@
+//! Reading the active vessel of a running game through the [kRPC](https://krpc.github.io/krpc/)
+//! mod, to calculate the deltav from where the vessel is
+//!
+//! # Example
+//! ```no_run
+//! use deltav_calc::krpc::KrpcClient;
+//! use deltav_calc::DeltavMap;
+//!
+//! let stock_map = DeltavMap::new_stock();
+//! let mut client = KrpcClient::connect("127.0.0.1:50000", "deltav_calc").unwrap();
+//! let delta_v = stock_map
+//!     .delta_v_from_current_vessel_to(&mut client, "Mun Surface")
+//!     .unwrap();
+//! ```
+use crate::{DeltavMap, NoSuchNodeError, Situation, VesselState};
+use prost::Message;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+// The messages of the kRPC protocol as defined in its krpc.proto, with only the fields used here
+
+#[derive(Clone, PartialEq, Message)]
+struct ConnectionRequest {
+    // 0 for RPC connections
+    #[prost(int32, tag = "1")]
+    r#type: i32,
+    #[prost(string, tag = "2")]
+    client_name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ConnectionResponse {
+    // 0 if the connection was accepted
+    #[prost(int32, tag = "1")]
+    status: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Request {
+    #[prost(message, repeated, tag = "1")]
+    calls: Vec<ProcedureCall>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ProcedureCall {
+    #[prost(string, tag = "1")]
+    service: String,
+    #[prost(string, tag = "2")]
+    procedure: String,
+    #[prost(message, repeated, tag = "3")]
+    arguments: Vec<Argument>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Argument {
+    #[prost(uint32, tag = "1")]
+    position: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Response {
+    #[prost(message, optional, tag = "1")]
+    error: Option<ErrorMessage>,
+    #[prost(message, repeated, tag = "2")]
+    results: Vec<ProcedureResult>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ProcedureResult {
+    #[prost(message, optional, tag = "1")]
+    error: Option<ErrorMessage>,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ErrorMessage {
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(string, tag = "3")]
+    description: String,
+}
+
+/// This error is raised when the vessel can't be read from the game or its location isn't on
+/// the map
+#[derive(Debug)]
+pub enum KrpcError {
+    /// The connection to the game failed
+    Io(std::io::Error),
+
+    /// The game sent something that isn't a valid kRPC message, with what is wrong with it
+    Decode(String),
+
+    /// The game refused the connection or a call, with the reason it gave
+    Server(String),
+
+    /// The game reported a situation this crate doesn't know
+    UnknownSituation(i32),
+
+    /// No node of the map matches where the vessel is
+    UnknownLocation(VesselState),
+
+    /// The target isn't a node of the map
+    NoSuchNode(NoSuchNodeError),
+}
+
+impl Display for KrpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KrpcError::Io(error) => write!(f, "Couldn't talk to the game: {error}"),
+            KrpcError::Decode(error) => write!(f, "The game sent an invalid message: {error}"),
+            KrpcError::Server(message) => write!(f, "The game refused the request: {message}"),
+            KrpcError::UnknownSituation(situation) => {
+                write!(f, "The vessel is in the unknown situation {situation}")
+            }
+            KrpcError::UnknownLocation(vessel) => write!(
+                f,
+                "No node of the map matches a vessel {:?} at {}",
+                vessel.situation, vessel.body
+            ),
+            KrpcError::NoSuchNode(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for KrpcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KrpcError::Io(error) => Some(error),
+            KrpcError::NoSuchNode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for KrpcError {
+    fn from(error: std::io::Error) -> Self {
+        KrpcError::Io(error)
+    }
+}
+
+impl From<prost::DecodeError> for KrpcError {
+    fn from(error: prost::DecodeError) -> Self {
+        KrpcError::Decode(error.to_string())
+    }
+}
+
+impl TryFrom<i32> for Situation {
+    type Error = KrpcError;
+
+    // The values of the VesselSituation enumeration of kRPC
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Situation::PreLaunch,
+            1 => Situation::Orbiting,
+            2 => Situation::SubOrbital,
+            3 => Situation::Escaping,
+            4 => Situation::Flying,
+            5 => Situation::Landed,
+            6 => Situation::Splashed,
+            7 => Situation::Docked,
+            _ => return Err(KrpcError::UnknownSituation(value)),
+        })
+    }
+}
+
+impl DeltavMap {
+    /// Calculates the deltav to get from the node the active vessel of the game is at to the
+    /// target. See [`vessel_node`](DeltavMap::vessel_node) for how the vessel is placed on the
+    /// map
+    pub fn delta_v_from_current_vessel_to(
+        &self,
+        client: &mut KrpcClient,
+        target: &str,
+    ) -> Result<Option<i64>, KrpcError> {
+        let vessel = client.active_vessel()?;
+        let Some(node) = self.vessel_node(&vessel) else {
+            return Err(KrpcError::UnknownLocation(vessel));
+        };
+
+        self.calculate_delta_v(node, target)
+            .map_err(KrpcError::NoSuchNode)
+    }
+}
+
+/// A connection to the RPC server of kRPC in a running game
+#[derive(Debug)]
+pub struct KrpcClient {
+    stream: TcpStream,
+}
+
+impl KrpcClient {
+    /// Connects to the RPC server, which listens on port 50000 by default. The client name is
+    /// shown in the kRPC window of the game
+    pub fn connect(address: impl ToSocketAddrs, client_name: &str) -> Result<Self, KrpcError> {
+        let mut client = KrpcClient {
+            stream: TcpStream::connect(address)?,
+        };
+
+        client.send(&ConnectionRequest {
+            r#type: 0,
+            client_name: client_name.to_string(),
+        })?;
+        let response: ConnectionResponse = client.receive()?;
+        if response.status != 0 {
+            return Err(KrpcError::Server(response.message));
+        }
+
+        Ok(client)
+    }
+
+    /// Reads the state of the vessel the player controls
+    pub fn active_vessel(&mut self) -> Result<VesselState, KrpcError> {
+        // Objects are sent as ids, which are passed back to the game unchanged
+        let vessel = self.call("get_ActiveVessel", &[])?;
+
+        let situation = self.call("Vessel_get_Situation", &[&vessel])?;
+        // Enumerations are sent zigzag encoded
+        let situation = prost::encoding::decode_varint(&mut situation.as_slice())?;
+        let situation = ((situation >> 1) as i32) ^ -((situation & 1) as i32);
+
+        let orbit = self.call("Vessel_get_Orbit", &[&vessel])?;
+        let body = self.call("Orbit_get_Body", &[&orbit])?;
+        let name = self.call("CelestialBody_get_Name", &[&body])?;
+        let name = decode_string(&name)?;
+        let periapsis = self.call("Orbit_get_PeriapsisAltitude", &[&orbit])?;
+        let apoapsis = self.call("Orbit_get_ApoapsisAltitude", &[&orbit])?;
+
+        Ok(VesselState {
+            body: name,
+            situation: Situation::try_from(situation)?,
+            periapsis: decode_double(&periapsis)?,
+            apoapsis: decode_double(&apoapsis)?,
+        })
+    }
+
+    // Calls a procedure of the SpaceCenter service with the encoded arguments and returns the
+    // encoded result
+    fn call(&mut self, procedure: &str, arguments: &[&[u8]]) -> Result<Vec<u8>, KrpcError> {
+        let arguments = arguments
+            .iter()
+            .enumerate()
+            .map(|(position, value)| Argument {
+                position: position as u32,
+                value: value.to_vec(),
+            });
+        self.send(&Request {
+            calls: vec![ProcedureCall {
+                service: String::from("SpaceCenter"),
+                procedure: procedure.to_string(),
+                arguments: arguments.collect(),
+            }],
+        })?;
+
+        let response: Response = self.receive()?;
+        let result = response.results.into_iter().next();
+        let error = response
+            .error
+            .or_else(|| result.as_ref().and_then(|result| result.error.clone()));
+        if let Some(error) = error {
+            return Err(KrpcError::Server(format!(
+                "{}: {}",
+                error.name, error.description
+            )));
+        }
+
+        result
+            .map(|result| result.value)
+            .ok_or_else(|| KrpcError::Server(format!("{procedure} returned nothing")))
+    }
+
+    // Sends a message prefixed with its length
+    fn send(&mut self, message: &impl Message) -> Result<(), KrpcError> {
+        self.stream
+            .write_all(&message.encode_length_delimited_to_vec())?;
+        Ok(())
+    }
+
+    // Receives a message prefixed with its length
+    fn receive<M: Message + Default>(&mut self) -> Result<M, KrpcError> {
+        let mut length = Vec::new();
+        loop {
+            let mut byte = [0];
+            self.stream.read_exact(&mut byte)?;
+            length.push(byte[0]);
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let length = prost::encoding::decode_varint(&mut length.as_slice())?;
+
+        let mut message = vec![0; length as usize];
+        self.stream.read_exact(&mut message)?;
+        Ok(M::decode(message.as_slice())?)
+    }
+}
+
+// Decodes a string, which is sent as its length followed by UTF-8
+fn decode_string(mut value: &[u8]) -> Result<String, KrpcError> {
+    let length = prost::encoding::decode_varint(&mut value)? as usize;
+    let bytes = value
+        .get(..length)
+        .ok_or_else(|| KrpcError::Decode(String::from("A string was cut off")))?;
+
+    String::from_utf8(bytes.to_vec()).map_err(|e| KrpcError::Decode(e.to_string()))
+}
+
+// Decodes a double, which is sent as 8 little endian bytes
+fn decode_double(value: &[u8]) -> Result<f64, KrpcError> {
+    let bytes = value
+        .try_into()
+        .map_err(|_| KrpcError::Decode(format!("A double has {} bytes", value.len())))?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Argument, ConnectionRequest, ConnectionResponse, KrpcClient, KrpcError, ProcedureResult,
+        Request, Response,
+    };
+    use crate::{DeltavMap, Situation, VesselState};
+    use prost::Message;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    // Reads a message prefixed with its length
+    fn receive<M: Message + Default>(stream: &mut TcpStream) -> M {
+        let mut buffer = [0; 1024];
+        let mut length = [0];
+        stream.read_exact(&mut length).unwrap();
+        let message = &mut buffer[..length[0] as usize];
+        stream.read_exact(message).unwrap();
+        M::decode(&*message).unwrap()
+    }
+
+    // Answers the calls of active_vessel like the game would for a vessel orbiting the Mun
+    fn fake_game(mut stream: TcpStream) {
+        let request: ConnectionRequest = receive(&mut stream);
+        assert_eq!(request.client_name, "test");
+        let response = ConnectionResponse {
+            status: 0,
+            message: String::new(),
+        };
+        stream
+            .write_all(&response.encode_length_delimited_to_vec())
+            .unwrap();
+
+        for _ in 0..7 {
+            let request: Request = receive(&mut stream);
+            let call = &request.calls[0];
+            assert_eq!(call.service, "SpaceCenter");
+            let value = match call.procedure.as_str() {
+                "get_ActiveVessel" => vec![7],
+                "Vessel_get_Situation" => {
+                    assert_eq!(
+                        call.arguments,
+                        [Argument {
+                            position: 0,
+                            value: vec![7]
+                        }]
+                    );
+                    vec![2]
+                }
+                "Vessel_get_Orbit" => vec![9],
+                "Orbit_get_Body" => vec![11],
+                "CelestialBody_get_Name" => [&[3], "Mun".as_bytes()].concat(),
+                "Orbit_get_PeriapsisAltitude" => 12_000f64.to_le_bytes().to_vec(),
+                "Orbit_get_ApoapsisAltitude" => 16_000f64.to_le_bytes().to_vec(),
+                procedure => panic!("Unexpected call of {procedure}"),
+            };
+            let response = Response {
+                error: None,
+                results: vec![ProcedureResult { error: None, value }],
+            };
+            stream
+                .write_all(&response.encode_length_delimited_to_vec())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_active_vessel() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let game = thread::spawn(move || fake_game(listener.accept().unwrap().0));
+
+        let mut client = KrpcClient::connect(address, "test").unwrap();
+        let vessel = client.active_vessel().unwrap();
+        game.join().unwrap();
+
+        assert_eq!(
+            vessel,
+            VesselState {
+                body: String::from("Mun"),
+                situation: Situation::Orbiting,
+                periapsis: 12_000.0,
+                apoapsis: 16_000.0,
+            }
+        );
+        assert_eq!(
+            DeltavMap::new_stock().vessel_node(&vessel),
+            Some("Low Mun Orbit (14km)")
+        );
+    }
+
+    #[test]
+    fn test_refused_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let game = thread::spawn(move || {
+            let mut stream = listener.accept().unwrap().0;
+            let _: ConnectionRequest = receive(&mut stream);
+            let response = ConnectionResponse {
+                status: 3,
+                message: String::from("Wrong connection type"),
+            };
+            stream
+                .write_all(&response.encode_length_delimited_to_vec())
+                .unwrap();
+        });
+
+        let error = KrpcClient::connect(address, "test").unwrap_err();
+        game.join().unwrap();
+        assert!(matches!(error, KrpcError::Server(message) if message == "Wrong connection type"));
+    }
+}
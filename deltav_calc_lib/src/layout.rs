@@ -0,0 +1,142 @@
+use crate::{DeltavMap, NodePayload};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// The layout of map drawings in pixels
+const ROW_HEIGHT: f64 = 90.0;
+const COLUMN_WIDTH: f64 = 170.0;
+const LABEL_WIDTH: f64 = 180.0;
+const MARGIN: f64 = 30.0;
+
+/// Where the nodes of a map are drawn in a picture of it. Every category gets a row with the
+/// nodes that are directly in it, labeled with the path of the category on the left
+///
+/// # Example
+/// ```
+/// use deltav_calc::DeltavMap;
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let layout = stock_map.layout();
+/// let (x, y) = layout.position("Kerbin Surface").unwrap();
+/// assert!(x < layout.width() && y < layout.height());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapLayout {
+    pub(crate) rows: Vec<(String, Vec<String>)>,
+    pub(crate) positions: BTreeMap<String, (f64, f64)>,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+}
+
+impl MapLayout {
+    /// The width of the picture
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// The height of the picture
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// The center of the node, or `None` if the map has no node with that name
+    pub fn position(&self, node: &str) -> Option<(f64, f64)> {
+        self.positions.get(node).copied()
+    }
+
+    /// The categories with the point their label starts at, from top to bottom
+    pub fn categories(&self) -> impl Iterator<Item = (&str, (f64, f64))> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row, (category, _))| (category.as_str(), (MARGIN, row_center(row))))
+    }
+
+    /// The nodes with their centers, row by row from top to bottom
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, (f64, f64))> {
+        self.rows
+            .iter()
+            .flat_map(|(_, nodes)| nodes)
+            .map(|node| (node.as_str(), self.positions[node]))
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Lays the map out for drawing, as it is done by [`DeltavMap::to_svg`]
+    pub fn layout(&self) -> MapLayout {
+        // The categories with nodes in document order, each with its nodes
+        let mut rows: Vec<(String, Vec<String>)> = Vec::new();
+        for (path, node) in self.menu_tree.end_nodes() {
+            let category = path.join("/");
+            match rows.last_mut() {
+                Some((last, nodes)) if *last == category => nodes.push(node.name().to_string()),
+                _ => rows.push((category, vec![node.name().to_string()])),
+            }
+        }
+
+        let mut positions = BTreeMap::new();
+        for (row, (_, nodes)) in rows.iter().enumerate() {
+            for (column, node) in nodes.iter().enumerate() {
+                let x = MARGIN + LABEL_WIDTH + column as f64 * COLUMN_WIDTH + COLUMN_WIDTH / 2.0;
+                positions.insert(node.clone(), (x, row_center(row)));
+            }
+        }
+
+        let columns = rows.iter().map(|(_, nodes)| nodes.len()).max();
+        let width = 2.0 * MARGIN + LABEL_WIDTH + columns.unwrap_or_default() as f64 * COLUMN_WIDTH;
+        let height = 2.0 * MARGIN + rows.len() as f64 * ROW_HEIGHT;
+
+        MapLayout {
+            rows,
+            positions,
+            width,
+            height,
+        }
+    }
+}
+
+// The vertical center of the row
+fn row_center(row: usize) -> f64 {
+    MARGIN + row as f64 * ROW_HEIGHT + ROW_HEIGHT / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DeltavMap;
+
+    #[test]
+    fn test_layout() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Kerbol/Kerbin]
+            Surface
+            Orbit
+            Surface -> Orbit : 3400
+
+            [Kerbol/Mun]
+            Mun Surface
+            Orbit -> Mun Surface : 1500
+            ",
+        )
+        .unwrap();
+        let layout = map.layout();
+
+        assert_eq!(layout.position("Surface"), Some((295.0, 75.0)));
+        assert_eq!(layout.position("Orbit"), Some((465.0, 75.0)));
+        assert_eq!(layout.position("Mun Surface"), Some((295.0, 165.0)));
+        assert_eq!(layout.position("Kerbol"), None);
+        assert_eq!((layout.width(), layout.height()), (580.0, 240.0));
+
+        let categories: Vec<_> = layout.categories().collect();
+        assert_eq!(
+            categories,
+            vec![
+                ("Kerbol/Kerbin", (30.0, 75.0)),
+                ("Kerbol/Mun", (30.0, 165.0))
+            ]
+        );
+        assert_eq!(layout.nodes().count(), 3);
+    }
+}
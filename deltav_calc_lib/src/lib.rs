@@ -8,17 +8,143 @@
 //! let stock_map = DeltavMap::new_stock();
 //! let deltav = stock_map.calculate_delta_v("Kerbin Surface", "Mun Surface");
 //! ```
+//!
+//! # Features
+//! - `engines`: A database of the stock engines to be used with the [`rocketry`] helpers
+//! - `csv`: Reading and writing maps as CSV files of nodes and edges
+//! - `binary`: A compact binary map format, see [`MapFormat::Binary`]
+//! - `yaml`: Reading and writing maps as YAML, see [`MapFormat::Yaml`]
+//! - `watch`: Reloading maps when their file changes, see [`DeltavMap::watch`]
+//! - `parallel`: Calculating many routes at once on all cores with
+//!   [`DeltavMap::calculate_routes`]
+//! - `discovery`: Finding the maps the user installed in the config directory, see [`discovery`]
+//! - `ffi`: A C API and header for other languages, see [`ffi`]
+//! - `krpc`: Reading the vessel of a running game through the kRPC mod, see [`krpc`]
+//! - `savefile`: Reading the vessels and their stages from a save file of the game, see
+//!   [`savefile`]
+//! - `openapi`: OpenAPI schemas of the serialized types, implementing `ToSchema` of the `utoipa`
+//!   crate for [`Route`], [`MenuTree`] and [`Metadata`]
+//! - `testing`: Implementations of `Arbitrary` from the `arbitrary` and `proptest` crates for
+//!   [`DeltavMap`] and [`MenuTree`], generating maps with the [`MapGenerator`]
+//! - `uniffi`: Kotlin and Swift bindings generated with UniFFI, see [`mobile`]
+//! - `python`: Python bindings built with pyo3, see [`python`]
+//! - `wasm`: Bindings to use maps from JavaScript, see [`wasm`]
+//! - `std` (default): Reading and writing files, [`DeltavMap::stock`] and the implementations of
+//!   `std::error::Error`. Without it the crate only needs `alloc`, so maps can be built, parsed
+//!   from strings and used for route calculations on `no_std` targets
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 extern crate core;
 
+mod batch;
+mod budget;
+mod checklist;
+mod collection;
+#[cfg(feature = "csv")]
+mod csv_io;
+#[cfg(feature = "discovery")]
+pub mod discovery;
+mod dsl;
+mod edge;
+mod edit;
+#[cfg(feature = "engines")]
+pub mod engines;
+mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod file;
+mod flightlog;
+mod format;
+mod fuzzy;
+mod generator;
+mod hash;
+#[cfg(feature = "krpc")]
+pub mod krpc;
+mod layout;
+mod location;
+mod margin;
+mod math;
 mod menutree;
+mod metadata;
+mod mission;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "openapi")]
+mod openapi;
+mod options;
+mod payload;
+#[cfg(feature = "python")]
+pub mod python;
+mod refuel;
+mod rendezvous;
+pub mod rocketry;
+mod route;
+#[cfg(feature = "savefile")]
+pub mod savefile;
+mod schema;
+#[cfg(feature = "std")]
+pub mod source;
+#[cfg(feature = "testing")]
+mod testing;
+mod validation;
+mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+mod watch;
 
-pub use crate::menutree::{MenuTree, NoSuchNodeError};
-use crate::MenuTree::{EndNode, MiddleNode};
+pub use crate::budget::Budget;
+pub use crate::checklist::{Checklist, Maneuver};
+pub use crate::collection::MapCollection;
+#[cfg(feature = "csv")]
+pub use crate::csv_io::CsvError;
+pub use crate::dsl::DslError;
+pub use crate::edge::{EdgeInfo, TransferWindow, GRAVITY_ASSIST_TAG};
+pub use crate::edit::EditError;
+#[cfg(feature = "std")]
+pub use crate::file::{LoadError, MapFormat, SaveError};
+pub use crate::flightlog::{Accuracy, Flight, FlightLog, FlightLogError, LoggedLeg};
+pub use crate::format::DeltavFormat;
+pub use crate::generator::MapGenerator;
+pub use crate::layout::MapLayout;
+pub use crate::location::{Situation, VesselState};
+pub use crate::margin::MarginProfile;
+pub use crate::menutree::{
+    MenuNode, MenuTree, MenuTreeIter, MenuTreeVisitor, NoSuchNodeError, NodeId, MAX_DEPTH,
+};
+pub use crate::metadata::Metadata;
+pub use crate::mission::{MissionError, MissionPlan, Objective, RoundTripReport};
+pub use crate::options::{CalcOptions, CostMode, Rounding, RoutingMode, DAY, MIN_SCALE};
+pub use crate::payload::NodePayload;
+pub use crate::refuel::{RefuelPlan, ISRU_TAG};
+pub use crate::rendezvous::{Rendezvous, RendezvousObjective};
+pub use crate::route::{JoinError, Leg, ReturnTrip, Route, RouteComparison, RouteCost, RouteDiff};
+pub use crate::validation::{ValidationError, ValidationOptions};
+pub use crate::warning::{RouteWarning, WarningKind};
+#[cfg(feature = "watch")]
+pub use crate::watch::{MapWatcher, WatchError};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use petgraph::algo;
-use petgraph::graph::{NodeIndex, UnGraph};
-use serde::Deserialize;
-use serde::Serialize;
+use petgraph::graph::{EdgeIndex, NodeIndex, UnGraph};
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::{EdgeFiltered, EdgeRef, NodeFiltered};
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+// The data of the stock system in the text format
+const STOCK_MAP: &str = include_str!("../maps/stock.deltav");
+
+// The data of the Real Solar System mod in the text format
+const RSS_MAP: &str = include_str!("../maps/rss.deltav");
 
 /// Represents a usable deltav map
 ///
@@ -35,84 +161,299 @@ use serde::Serialize;
 /// ```json
 /// {
 ///   "menu_tree": {
-///     "MiddleNode": {
+///     "Category": {
 ///       "name": "Category1",
 ///       "children": [
 ///         {
-///           "MiddleNode": {
+///           "Category": {
 ///             "name": "Category2",
 ///             "children": [
-///               {
-///                 "EndNode": {
-///                   "name": "Node1",
-///                   "index": 0
-///                 }
-///               },
-///               {
-///                 "EndNode": {
-///                   "name": "Node2",
-///                   "index": 1
-///                 }
-///               }
+///               { "Node": "Node1" },
+///               { "Node": "Node2" }
 ///             ]
 ///           }
 ///         },
-///         {
-///           "EndNode": {
-///             "name": "Node3",
-///             "index": 2
-///           }
-///         },
-///         {
-///           "EndNode": {
-///             "name": "Node4",
-///             "index": 3
-///           }
-///         }
+///         { "Node": "Node3" },
+///         { "Node": "Node4" }
 ///       ]
 ///     }
 ///   },
-///   "graph": {
-///     "nodes": [
-///       "Node1",
-///       "Node2",
-///       "Node3",
-///       "Node4"
-///     ],
-///     "node_holes": [],
-///     "edge_property": "undirected",
-///     "edges": [
-///       [
-///         0,
-///         1,
-///         900
-///       ],
-///       [
-///         1,
-///         2,
-///         80
-///       ],
-///       [
-///         2,
-///         3,
-///         50
-///       ]
-///     ]
+///   "edges": [
+///     ["Node1", "Node2", 900],
+///     ["Node2", "Node3", { "cost": 80, "min": 60, "max": 120 }],
+///     ["Node3", "Node4", 50]
+///   ],
+///   "tags": {
+///     "Node2": ["atmosphere"]
+///   },
+///   "metadata": {
+///     "margin_profiles": {
+///       "heavy_lander": 30
+///     }
 ///   }
 /// }
 /// ```
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct DeltavMap {
+///
+/// The `menu_tree` sorts the nodes into categories, keeping their order. The `edges` connect two
+/// nodes by name, their weights can either be plain costs or objects carrying additional
+/// information, see [`EdgeInfo`]. The `tags` section is optional and assigns arbitrary tags to the nodes. The
+/// optional `metadata` section is described in [`Metadata`].
+///
+/// Instead of the `menu_tree`, the nodes can be given as a flat list. Every node declares the path
+/// of its category, starting with the root and separated by slashes, and optionally its tags. The
+/// menu tree is built from the paths:
+/// ```json
+/// {
+///   "nodes": [
+///     { "name": "Laythe Surface", "category": "Kerbol System/Jool/Laythe", "tags": ["surface"] },
+///     { "name": "Low Laythe Orbit (60km)", "category": "Kerbol System/Jool/Laythe" }
+///   ],
+///   "edges": [
+///     ["Low Laythe Orbit (60km)", "Laythe Surface", 2900]
+///   ]
+/// }
+/// ```
+///
+/// Files in the old format, which stored petgraph's representation of the graph in a `graph`
+/// section and the indices of the nodes in the menu tree, can still be read
+///
+/// # Payloads
+/// The nodes of the graph carry a payload, which by default is just their name. Applications can
+/// attach their own data to the nodes with [`map_payloads`](DeltavMap::map_payloads), see
+/// [`NodePayload`]. Maps can only be loaded and saved with the default payload
+#[derive(Clone, Debug)]
+pub struct DeltavMap<N = Arc<str>> {
     menu_tree: MenuTree,
-    graph: UnGraph<String, i32>,
+    graph: UnGraph<N, EdgeInfo>,
+    tags: BTreeMap<String, BTreeSet<String>>,
+    metadata: Metadata,
 }
 
-impl DeltavMap {
+impl<N: NodePayload> DeltavMap<N> {
     /// The menu tree you can use to structure your menu
     pub fn menu_tree(&self) -> &MenuTree {
         &self.menu_tree
     }
 
+    /// Sorts the children of all categories of the menu tree by their names
+    pub fn sort_menu_tree_by_name(&mut self) {
+        self.menu_tree.sort_children_by_name();
+    }
+
+    /// Sorts the children of all categories of the menu tree with the comparator, see
+    /// [`MenuTree::sort_children_by`]. The order is kept when the map is saved
+    pub fn sort_menu_tree_by(
+        &mut self,
+        compare: impl FnMut(MenuNode<'_>, MenuNode<'_>) -> Ordering,
+    ) {
+        self.menu_tree.sort_children_by(compare);
+    }
+
+    /// Additional information about the map
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// The title of the map, if the metadata has one
+    pub fn title(&self) -> Option<&str> {
+        self.metadata.title()
+    }
+
+    /// The author of the map, if the metadata has one
+    pub fn author(&self) -> Option<&str> {
+        self.metadata.author()
+    }
+
+    /// The URL the map was taken from, if the metadata has one
+    pub fn source_url(&self) -> Option<&str> {
+        self.metadata.source_url()
+    }
+
+    /// The game version the map targets, if the metadata has one
+    pub fn game_version(&self) -> Option<&str> {
+        self.metadata.game_version()
+    }
+
+    /// The license of the map, if the metadata has one
+    pub fn license(&self) -> Option<&str> {
+        self.metadata.license()
+    }
+
+    /// When the map was last updated, if the metadata says so
+    pub fn last_updated(&self) -> Option<&str> {
+        self.metadata.last_updated()
+    }
+
+    /// Returns all margin profiles available for this map. Custom profiles defined in the
+    /// metadata replace builtin profiles with the same name
+    pub fn margin_profiles(&self) -> Vec<MarginProfile> {
+        let mut profiles: BTreeMap<&str, u32> = MarginProfile::BUILTIN.into_iter().collect();
+        for (name, percent) in self.metadata.margin_profiles() {
+            profiles.insert(name, *percent);
+        }
+
+        profiles
+            .into_iter()
+            .map(|(name, percent)| MarginProfile::new(name, percent))
+            .collect()
+    }
+
+    /// Returns the margin profile with the given name, if there is one
+    pub fn margin_profile(&self, name: &str) -> Option<MarginProfile> {
+        match self.metadata.margin_profiles().get(name) {
+            Some(percent) => Some(MarginProfile::new(name, *percent)),
+            None => MarginProfile::builtin(name),
+        }
+    }
+
+    /// Returns a copy of the map with the costs of all edges multiplied by the factor, for example
+    /// for rescaled systems. The factor is clamped like the one of [`CalcOptions::scale`]
+    pub fn scaled(&self, factor: f64) -> DeltavMap<N>
+    where
+        N: Clone,
+    {
+        self.scaled_with(factor, Rounding::Nearest)
+    }
+
+    /// Like [`DeltavMap::scaled`], but rounds the scaled costs with the given policy
+    pub fn scaled_with(&self, factor: f64, rounding: Rounding) -> DeltavMap<N>
+    where
+        N: Clone,
+    {
+        let mut scaled = self.clone();
+        for edge in scaled.graph.edge_weights_mut() {
+            *edge = edge.scaled_with(factor, rounding);
+        }
+
+        scaled
+    }
+
+    /// Returns the tags of the node with the given name
+    ///
+    /// Returns a [`NoSuchNodeError`] if there is no node with that name
+    pub fn tags(&self, node: &str) -> Result<Vec<&str>, NoSuchNodeError> {
+        let node = self.menu_tree.search(node)?;
+
+        Ok(match self.tags.get(node.name()) {
+            None => Vec::new(),
+            Some(tags) => tags.iter().map(String::as_str).collect(),
+        })
+    }
+
+    /// Returns the names of all nodes carrying the given tag
+    pub fn nodes_with_tag(&self, tag: &str) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .map(|(node, _)| node.as_str())
+            .collect()
+    }
+
+    /// Returns the information stored on the edge between the two nodes
+    ///
+    /// Returns a [`NoSuchNodeError`] If either node doesn't exist
+    /// Returns `None` if the nodes aren't directly connected
+    pub fn edge_info(&self, from: &str, to: &str) -> Result<Option<&EdgeInfo>, NoSuchNodeError> {
+        let from = self.node_index(from)?;
+        let to = self.node_index(to)?;
+
+        Ok(self.graph.find_edge(from, to).map(|edge| &self.graph[edge]))
+    }
+
+    /// Returns all edges of the map as the names of the two nodes and the edge between them. The
+    /// direction of an edge is the one it was added with
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// for (from, to, edge) in stock_map.edges() {
+    ///     println!("{from} -> {to}: {}", edge.cost());
+    /// }
+    /// ```
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str, &EdgeInfo)> {
+        self.graph.edge_references().map(|edge| {
+            (
+                self.graph[edge.source()].name(),
+                self.graph[edge.target()].name(),
+                edge.weight(),
+            )
+        })
+    }
+
+    /// Returns all edges of the node as the names of the two nodes and the edge between them,
+    /// with the node always coming first
+    ///
+    /// Returns a [`NoSuchNodeError`] if there is no node with that name
+    pub fn edges_of(&self, node: &str) -> Result<Vec<(&str, &str, &EdgeInfo)>, NoSuchNodeError> {
+        let index = self.node_index(node)?;
+
+        Ok(self
+            .graph
+            .edges(index)
+            .map(|edge| {
+                let other = if edge.source() == index {
+                    edge.target()
+                } else {
+                    edge.source()
+                };
+                (
+                    self.graph[index].name(),
+                    self.graph[other].name(),
+                    edge.weight(),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the edges of the minimum spanning tree of the map, which is the cheapest set of
+    /// edges connecting all nodes. For most maps this is the main chain of transfers, so
+    /// renderers can draw it differently from the shortcuts. Maps that aren't connected result
+    /// in a spanning forest
+    pub fn spanning_tree(&self) -> Vec<(&str, &str, &EdgeInfo)> {
+        let mut edges: Vec<_> = self.graph.edge_references().collect();
+        edges.sort_by_key(|edge| edge.weight().cost());
+
+        let mut components = UnionFind::new(self.graph.node_count());
+        edges
+            .into_iter()
+            .filter(|edge| components.union(edge.source().index(), edge.target().index()))
+            .map(|edge| {
+                (
+                    self.graph[edge.source()].name(),
+                    self.graph[edge.target()].name(),
+                    edge.weight(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the names of all nodes directly connected to the node together with the edges
+    /// leading to them, sorted by name
+    ///
+    /// Returns a [`NoSuchNodeError`] if there is no node with that name
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// for (name, edge) in stock_map.neighbors("Low Kerbin Orbit (80km)").unwrap() {
+    ///     println!("{name}: {}", edge.cost());
+    /// }
+    /// ```
+    pub fn neighbors(&self, node: &str) -> Result<Vec<(&str, &EdgeInfo)>, NoSuchNodeError> {
+        let mut neighbors: Vec<(&str, &EdgeInfo)> = self
+            .edges_of(node)?
+            .into_iter()
+            .map(|(_, other, edge)| (other, edge))
+            .collect();
+        neighbors.sort_by(|a, b| a.0.cmp(b.0));
+
+        Ok(neighbors)
+    }
+
     /// Calculates the deltav required to get from the start to the end
     ///
     /// Returns a [`NoSuchNodeError`] If either start or end aren't valid nodes
@@ -121,32 +462,359 @@ impl DeltavMap {
         &self,
         start: &str,
         end: &str,
-    ) -> Result<Option<i32>, NoSuchNodeError> {
-        match self.menu_tree.search(start) {
-            Err(e) => Err(e),
-            Ok(start) => {
-                return match self.menu_tree.search(end) {
-                    Err(e) => Err(e),
-                    Ok(end) => {
-                        let result: Option<(i32, Vec<NodeIndex>)> = algo::astar(
-                            &self.graph,
-                            start.index().clone(),
-                            |finish| finish == end.index().clone(),
-                            |e| *e.weight(),
-                            |_| 0,
-                        );
-
-                        match result {
-                            None => Ok(None),
-                            Some(result) => Ok(Some(result.0)),
-                        }
+    ) -> Result<Option<i64>, NoSuchNodeError> {
+        let route = self.calculate_route(start, end, &CalcOptions::default())?;
+
+        Ok(route.map(|route| route.delta_v()))
+    }
+
+    /// Calculates the cost of the cheapest route from the start to the end including its
+    /// uncertainty. The safety margin of the options is added to the worst case
+    ///
+    /// Returns a [`NoSuchNodeError`] If either start or end aren't valid nodes
+    /// Returns `None` if there is no path between the nodes that satisfies the options
+    pub fn calculate_cost(
+        &self,
+        start: &str,
+        end: &str,
+        options: &CalcOptions,
+    ) -> Result<Option<RouteCost>, NoSuchNodeError> {
+        let route = self.calculate_route(start, end, options)?;
+
+        Ok(route.map(|route| route.cost().with_margin(options)))
+    }
+
+    /// Calculates the cheapest route from the start to the end respecting the given options
+    ///
+    /// Returns a [`NoSuchNodeError`] If either start or end aren't valid nodes
+    /// Returns `None` if there is no path between the nodes that satisfies the options
+    pub fn calculate_route(
+        &self,
+        start: &str,
+        end: &str,
+        options: &CalcOptions,
+    ) -> Result<Option<Route>, NoSuchNodeError> {
+        let start = self.node_index(start)?;
+        let end = self.node_index(end)?;
+
+        let nodes = NodeFiltered::from_fn(&self.graph, |node| {
+            node == start || node == end || self.is_allowed(node, options)
+        });
+        let filtered = EdgeFiltered::from_fn(&nodes, |edge| self.is_usable(edge.weight(), options));
+        let path = match options.max_legs {
+            Some(max_legs) => self.limited_path(start, end, max_legs, options),
+            None => algo::astar(
+                &filtered,
+                start,
+                |finish| finish == end,
+                |e| self.routing_weight(e.id(), e.source(), options),
+                |_| 0,
+            )
+            .map(|(_, path)| path),
+        };
+
+        Ok(path.map(|path| self.build_route(&path, options)))
+    }
+
+    // Finds the cheapest path with at most the given number of legs
+    fn limited_path(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        max_legs: usize,
+        options: &CalcOptions,
+    ) -> Option<Vec<NodeIndex>> {
+        let allowed = |node| node == start || node == end || self.is_allowed(node, options);
+        let rounds = self.leg_rounds(start, allowed, max_legs, options);
+
+        // Fewer legs win ties, which also keeps free detours out of the path
+        let (legs, _) = rounds
+            .iter()
+            .enumerate()
+            .filter_map(|(legs, round)| round[end.index()].map(|(cost, _)| (legs, cost)))
+            .min_by_key(|(legs, cost)| (*cost, *legs))?;
+
+        let mut path = vec![end];
+        let mut node = end;
+        for round in rounds[1..=legs].iter().rev() {
+            node = round[node.index()].unwrap().1;
+            path.push(node);
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    // The cheapest paths from the start through the allowed nodes with up to the given number of
+    // legs. Every round extends the cheapest paths of the previous round by one leg, so round k
+    // holds the cost of the cheapest path with exactly k legs to every node and the node before
+    pub(crate) fn leg_rounds(
+        &self,
+        start: NodeIndex,
+        allowed: impl Fn(NodeIndex) -> bool,
+        max_legs: usize,
+        options: &CalcOptions,
+    ) -> Vec<Vec<Option<(i64, NodeIndex)>>> {
+        let mut rounds: Vec<Vec<Option<(i64, NodeIndex)>>> =
+            vec![vec![None; self.graph.node_count()]];
+        rounds[0][start.index()] = Some((0, start));
+
+        for _ in 0..max_legs {
+            let previous = rounds.last().unwrap();
+            let mut round = vec![None; self.graph.node_count()];
+
+            for (from, entry) in previous.iter().enumerate() {
+                let Some((cost, _)) = entry else { continue };
+                let from = NodeIndex::new(from);
+
+                for edge in self.graph.edges(from) {
+                    let to = if edge.source() == from {
+                        edge.target()
+                    } else {
+                        edge.source()
+                    };
+                    if !allowed(to) || !self.is_usable(edge.weight(), options) {
+                        continue;
+                    }
+
+                    let cost = cost + self.routing_weight(edge.id(), from, options);
+                    let slot = &mut round[to.index()];
+                    if slot.is_none_or(|(best, _)| cost < best) {
+                        *slot = Some((cost, from));
                     }
                 }
             }
+            rounds.push(round);
+        }
+
+        rounds
+    }
+
+    /// Calculates the cheapest route from the start to the destination and back again.
+    ///
+    /// Both directions may use aerobraking, so the way back can be cheaper than the way there.
+    ///
+    /// Returns a [`NoSuchNodeError`] If either start or destination aren't valid nodes
+    /// Returns `None` if there is no path between the nodes
+    pub fn calculate_return(
+        &self,
+        start: &str,
+        destination: &str,
+    ) -> Result<Option<ReturnTrip>, NoSuchNodeError> {
+        self.return_trip(start, destination, &CalcOptions::new().aerobrake(true))
+    }
+
+    /// Calculates everything needed to fly from home to the destination and back again, for
+    /// example landing on the Mun and returning to the surface of Kerbin.
+    ///
+    /// The safety margin of the options is added to both directions.
+    ///
+    /// Returns a [`NoSuchNodeError`] If either home or destination aren't valid nodes
+    /// Returns `None` if there is no path between the nodes that satisfies the options
+    pub fn calculate_round_trip(
+        &self,
+        home: &str,
+        destination: &str,
+        options: &CalcOptions,
+    ) -> Result<Option<RoundTripReport>, NoSuchNodeError> {
+        let trip = self.return_trip(home, destination, options)?;
+
+        Ok(trip.map(|trip| RoundTripReport {
+            trip,
+            options: options.clone(),
+        }))
+    }
+
+    // Calculates the routes to the destination and back using the same options for both
+    fn return_trip(
+        &self,
+        start: &str,
+        destination: &str,
+        options: &CalcOptions,
+    ) -> Result<Option<ReturnTrip>, NoSuchNodeError> {
+        let outbound = self.calculate_route(start, destination, options)?;
+        let inbound = self.calculate_route(destination, start, options)?;
+
+        Ok(match (outbound, inbound) {
+            (Some(outbound), Some(inbound)) => Some(ReturnTrip { outbound, inbound }),
+            _ => None,
+        })
+    }
+
+    // Checks if the node may be part of a route calculated with the given options
+    fn is_allowed(&self, node: NodeIndex, options: &CalcOptions) -> bool {
+        let name = self.graph[node].name();
+        if options.avoided_nodes.contains(name) {
+            return false;
+        }
+
+        match self.tags.get(name) {
+            None => true,
+            Some(tags) => options.avoided_tags.is_disjoint(tags),
+        }
+    }
+
+    // Checks if the edge may be part of a route calculated with the given options
+    fn is_usable(&self, edge: &EdgeInfo, options: &CalcOptions) -> bool {
+        options.gravity_assists || !edge.is_gravity_assist()
+    }
+
+    // The cost of travelling along the edge starting at the given node and if aerobraking is
+    // used to do so
+    fn travel_cost(&self, edge: EdgeIndex, from: NodeIndex, options: &CalcOptions) -> (i32, bool) {
+        let info = &self.graph[edge];
+        let forward = self.graph.edge_endpoints(edge).unwrap().0 == from;
+
+        let cost = self.adjusted_cost(info, info.cost_in_mode(options.cost_mode), options);
+
+        // Aerobraking doesn't need any thrust, so the low thrust factor doesn't apply
+        match info.aerobrake_cost().map(|cost| options.apply_scale(cost)) {
+            Some(aerobrake) if options.aerobrake && forward && aerobrake < cost => {
+                (aerobrake, true)
+            }
+            _ => (cost, false),
+        }
+    }
+
+    // Applies the low thrust factor of the edge and the scale factor to a cost of the edge
+    fn adjusted_cost(&self, info: &EdgeInfo, cost: i32, options: &CalcOptions) -> i32 {
+        let cost = if options.low_thrust {
+            options.round_cost(cost as f64 * info.low_thrust_factor())
+        } else {
+            cost
+        };
+
+        options.apply_scale(cost)
+    }
+
+    // The weight the route calculation minimizes when travelling along the edge starting at the
+    // given node
+    fn routing_weight(&self, edge: EdgeIndex, from: NodeIndex, options: &CalcOptions) -> i64 {
+        let delta_v = self.travel_cost(edge, from, options).0 as i64;
+        let time = self.graph[edge].travel_time().unwrap_or(0) as i64;
+
+        match options.routing_mode {
+            RoutingMode::DeltaV => delta_v,
+            RoutingMode::Time => time,
+            RoutingMode::Weighted(per_day) => delta_v + time * per_day as i64 / DAY as i64,
+        }
+    }
+
+    // Looks up the node in the graph. Categories have no node, so they aren't found either
+    pub(crate) fn node_index(&self, node: &str) -> Result<NodeIndex, NoSuchNodeError> {
+        match self.menu_tree.search(node)?.id() {
+            Some(id) => Ok(NodeIndex::from(id)),
+            None => Err(NoSuchNodeError {
+                name: node.to_string(),
+            }),
         }
     }
 
-    /// Returns a DeltavMap for the stock system
+    // Builds the route through the named nodes. Returns `None` if two consecutive nodes aren't
+    // connected by a usable edge or the route passes a node the options avoid. Like for calculated
+    // routes, the start and the end are always allowed
+    pub(crate) fn route_along(
+        &self,
+        nodes: &[&str],
+        options: &CalcOptions,
+    ) -> Result<Option<Route>, NoSuchNodeError> {
+        let path = nodes
+            .iter()
+            .map(|name| self.node_index(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let passed = path
+            .get(1..path.len().saturating_sub(1))
+            .unwrap_or_default();
+        if !passed.iter().all(|node| self.is_allowed(*node, options)) {
+            return Ok(None);
+        }
+
+        let connected = path.windows(2).all(|pair| {
+            self.graph
+                .edges_connecting(pair[0], pair[1])
+                .any(|edge| self.is_usable(edge.weight(), options))
+        });
+
+        Ok(connected.then(|| self.build_route(&path, options)))
+    }
+
+    // Turns a path of node indices into a route
+    fn build_route(&self, path: &[NodeIndex], options: &CalcOptions) -> Route {
+        let mut legs = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (leg, pair) in path.windows(2).enumerate() {
+            // There may be multiple edges between the nodes, the path uses the cheapest one
+            let edge = self
+                .graph
+                .edges_connecting(pair[0], pair[1])
+                .filter(|edge| self.is_usable(edge.weight(), options))
+                .map(|edge| edge.id())
+                .min_by_key(|edge| self.routing_weight(*edge, pair[0], options))
+                .unwrap();
+            let (delta_v, aerobrake) = self.travel_cost(edge, pair[0], options);
+            let edge = &self.graph[edge];
+            let to = &self.graph[pair[1]];
+
+            // Hazards of the node being entered and of the edge being travelled along
+            let kinds: BTreeSet<WarningKind> = self
+                .tags
+                .get(to.name())
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .chain(edge.tags())
+                .filter_map(WarningKind::from_tag)
+                .collect();
+            warnings.extend(kinds.into_iter().map(|kind| RouteWarning { kind, leg }));
+
+            // Aerobraking makes the cost independent of the piloting
+            let (min_delta_v, max_delta_v) = if aerobrake {
+                (delta_v, delta_v)
+            } else {
+                (
+                    self.adjusted_cost(edge, edge.min_cost(), options),
+                    self.adjusted_cost(edge, edge.max_cost(), options),
+                )
+            };
+
+            legs.push(Leg {
+                from: self.graph[pair[0]].name().to_string(),
+                to: to.name().to_string(),
+                delta_v,
+                min_delta_v,
+                max_delta_v,
+                aerobrake,
+                time: edge.travel_time(),
+            });
+        }
+
+        Route {
+            start: self.graph[path[0]].name().to_string(),
+            legs,
+            warnings,
+        }
+    }
+}
+
+impl DeltavMap {
+    /// Returns the DeltavMap for the stock system, which is only built on the first call. Use
+    /// [`new_stock`](DeltavMap::new_stock) if you need a map you can modify
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let deltav = DeltavMap::stock().calculate_delta_v("Kerbin Surface", "Mun Surface");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn stock() -> &'static DeltavMap {
+        static STOCK: std::sync::OnceLock<DeltavMap> = std::sync::OnceLock::new();
+        STOCK.get_or_init(DeltavMap::new_stock)
+    }
+
+    /// Returns a DeltavMap for the stock system. Its data is stored in `maps/stock.deltav`
     ///
     /// # Structure of the MenuTree:
     /// ```plain
@@ -224,789 +892,708 @@ impl DeltavMap {
     /// └── Kerbol Surface
     /// ```
     pub fn new_stock() -> DeltavMap {
-        let mut graph: UnGraph<String, i32> = UnGraph::new_undirected();
-
-        let menu_tree = MiddleNode {
-            name: String::from("Kerbol System"),
-            children: vec![
-                // Kerbin
-                MiddleNode {
-                    name: String::from("Kerbin"),
-                    children: vec![
-                        // Surface
-                        EndNode {
-                            name: String::from("Kerbin Surface"),
-                            index: graph.add_node(String::from("Kerbin Surface")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Kerbin Orbit (80km)"),
-                            index: graph.add_node(String::from("Low Kerbin Orbit (80km)")),
-                        },
-                        // Keostationary
-                        EndNode {
-                            name: String::from("Keostationary Orbit (2.868Mm)"),
-                            index: graph.add_node(String::from("Keostationary Orbit (2.868Mm)")),
-                        },
-                        // Capture
-                        EndNode {
-                            name: String::from("Kerbin Capture"),
-                            index: graph.add_node(String::from("Kerbin Capture")),
-                        },
-                        // Mun
-                        MiddleNode {
-                            name: String::from("Mun"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Mun Intercept"),
-                                    index: graph.add_node(String::from("Mun Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Mun Orbit (14km)"),
-                                    index: graph.add_node(String::from("Low Mun Orbit (14km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Mun Surface"),
-                                    index: graph.add_node(String::from("Mun Surface")),
-                                },
-                            ],
-                        },
-                        // Minmus
-                        MiddleNode {
-                            name: String::from("Minmus"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Minmus Intercept"),
-                                    index: graph.add_node(String::from("Minmus Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Minmus Orbit (10km)"),
-                                    index: graph.add_node(String::from("Low Minmus Orbit (10km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Minmus Surface"),
-                                    index: graph.add_node(String::from("Minmus Surface")),
-                                },
-                            ],
-                        },
-                    ],
-                },
-                // Eve
-                MiddleNode {
-                    name: String::from("Eve"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Eve Intercept"),
-                            index: graph.add_node(String::from("Eve Intercept")),
-                        },
-                        // Capture
-                        EndNode {
-                            name: String::from("Eve Capture (100km - 85Mm)"),
-                            index: graph.add_node(String::from("Eve Capture (100km - 85Mm)")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Eve Orbit (100km)"),
-                            index: graph.add_node(String::from("Low Eve Orbit (100km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Eve Surface"),
-                            index: graph.add_node(String::from("Eve Surface")),
-                        },
-                        // Gilly
-                        MiddleNode {
-                            name: String::from("Gilly"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Gilly Intercept"),
-                                    index: graph.add_node(String::from("Gilly Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Gilly Orbit (10km)"),
-                                    index: graph.add_node(String::from("Low Gilly Orbit (10km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Gilly Surface"),
-                                    index: graph.add_node(String::from("Gilly Surface")),
-                                },
-                            ],
-                        },
-                    ],
-                },
-                // Duna
-                MiddleNode {
-                    name: String::from("Duna"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Duna Intercept"),
-                            index: graph.add_node(String::from("Duna Intercept")),
-                        },
-                        // Capture
-                        EndNode {
-                            name: String::from("Duna Capture (60km - 48Mm)"),
-                            index: graph.add_node(String::from("Duna Capture (60km - 48Mm)")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Duna Orbit (60km)"),
-                            index: graph.add_node(String::from("Low Duna Orbit (60km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Duna Surface"),
-                            index: graph.add_node(String::from("Duna Surface)")),
-                        },
-                        // Ike
-                        MiddleNode {
-                            name: String::from("Ike"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Ike Intercept"),
-                                    index: graph.add_node(String::from("Ike Intercept)")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Ike Orbit (10km)"),
-                                    index: graph.add_node(String::from("Low Ike Orbit (10km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Ike Surface"),
-                                    index: graph.add_node(String::from("Ike Surface")),
-                                },
-                            ],
-                        },
-                    ],
-                },
-                // Jool
-                MiddleNode {
-                    name: String::from("Jool"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Jool Intercept"),
-                            index: graph.add_node(String::from("Jool Intercept")),
-                        },
-                        // Capture
-                        EndNode {
-                            name: String::from("Jool Capture (210km - 268Mm)"),
-                            index: graph.add_node(String::from("Jool Capture (210km - 268Mm)")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Jool Orbit (210km)"),
-                            index: graph.add_node(String::from("Low Jool Orbit (210km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Jool Surface"),
-                            index: graph.add_node(String::from("Jool Surface")),
-                        },
-                        // Pol
-                        MiddleNode {
-                            name: String::from("Pol"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Pol Intercept"),
-                                    index: graph.add_node(String::from("Pol Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Pol Orbit (10km)"),
-                                    index: graph.add_node(String::from("Low Pol Orbit (10km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Pol Surface"),
-                                    index: graph.add_node(String::from("Pol Surface")),
-                                },
-                            ],
-                        },
-                        // Bop
-                        MiddleNode {
-                            name: String::from("Bop"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Bop Intercept"),
-                                    index: graph.add_node(String::from("Bop Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Bop Orbit (30km)"),
-                                    index: graph.add_node(String::from("Low Bop Orbit (30km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Bop Surface"),
-                                    index: graph.add_node(String::from("Bop Surface")),
-                                },
-                            ],
-                        },
-                        // Tylo
-                        MiddleNode {
-                            name: String::from("Tylo"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Tylo Intercept"),
-                                    index: graph.add_node(String::from("Tylo Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Tylo Orbit (10km)"),
-                                    index: graph.add_node(String::from("Low Tylo Orbit (10km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Tylo Surface"),
-                                    index: graph.add_node(String::from("Tylo Surface")),
-                                },
-                            ],
-                        },
-                        // Vall
-                        MiddleNode {
-                            name: String::from("Vall"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Vall Intercept"),
-                                    index: graph.add_node(String::from("Vall Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Vall Orbit (15km)"),
-                                    index: graph.add_node(String::from("Low Vall Orbit (15km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Vall Surface"),
-                                    index: graph.add_node(String::from("Vall Surface")),
-                                },
-                            ],
-                        },
-                        // Laythe
-                        MiddleNode {
-                            name: String::from("Laythe"),
-                            children: vec![
-                                // Intercept
-                                EndNode {
-                                    name: String::from("Laythe Intercept"),
-                                    index: graph.add_node(String::from("Laythe Intercept")),
-                                },
-                                // Low Orbit
-                                EndNode {
-                                    name: String::from("Low Laythe Orbit (60km)"),
-                                    index: graph.add_node(String::from("Low Laythe Orbit (60km)")),
-                                },
-                                // Surface
-                                EndNode {
-                                    name: String::from("Laythe Surface"),
-                                    index: graph.add_node(String::from("Laythe Surface")),
-                                },
-                            ],
-                        },
-                    ],
-                },
-                // Dres
-                MiddleNode {
-                    name: String::from("Dres"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Dres Intercept"),
-                            index: graph.add_node(String::from("Dres Intercept")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Dres Orbit (12km)"),
-                            index: graph.add_node(String::from("Low Dres Orbit (12km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Dres Surface"),
-                            index: graph.add_node(String::from("Dres Surface")),
-                        },
-                    ],
-                },
-                // Moho
-                MiddleNode {
-                    name: String::from("Moho"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Moho Intercept"),
-                            index: graph.add_node(String::from("Moho Intercept")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Moho Orbit (20km)"),
-                            index: graph.add_node(String::from("Low Moho Orbit (20km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Moho Surface"),
-                            index: graph.add_node(String::from("Moho Surface")),
-                        },
-                    ],
-                },
-                // Eeloo
-                MiddleNode {
-                    name: String::from("Eeloo"),
-                    children: vec![
-                        // Intercept
-                        EndNode {
-                            name: String::from("Eeloo Intercept"),
-                            index: graph.add_node(String::from("Eeloo Intercept")),
-                        },
-                        // Low Orbit
-                        EndNode {
-                            name: String::from("Low Eeloo Orbit (10km)"),
-                            index: graph.add_node(String::from("Low Eeloo Orbit (10km)")),
-                        },
-                        // Surface
-                        EndNode {
-                            name: String::from("Eeloo Surface"),
-                            index: graph.add_node(String::from("Eeloo Surface")),
-                        },
-                    ],
-                },
-                // Elliptical Orbit
-                EndNode {
-                    name: String::from("Elliptical Kerbol Orbit (610km - 13,600Mm)"),
-                    index: graph
-                        .add_node(String::from("Elliptical Kerbol Orbit (610km - 13,600Mm)")),
-                },
-                // Low Orbit
-                EndNode {
-                    name: String::from("Low Kerbol Orbit (610km)"),
-                    index: graph.add_node(String::from("Low Kerbol Orbit (610km)")),
-                },
-                // Surface
-                EndNode {
-                    name: String::from("Kerbol Surface"),
-                    index: graph.add_node(String::from("Kerbol Surface")),
-                },
-            ],
-        };
+        DeltavMap::from_dsl(STOCK_MAP).expect("The stock map is invalid")
+    }
 
-        // region Kerbol
-        // region Kerbin
-        graph.add_edge(
-            menu_tree["Kerbin Surface"].index().clone(),
-            menu_tree["Low Kerbin Orbit (80km)"].index().clone(),
-            3400,
-        );
-        graph.add_edge(
-            menu_tree["Low Kerbin Orbit (80km)"].index().clone(),
-            menu_tree["Keostationary Orbit (2.868Mm)"].index().clone(),
-            1115,
-        );
-        graph.add_edge(
-            menu_tree["Low Kerbin Orbit (80km)"].index().clone(),
-            menu_tree["Kerbin Capture"].index().clone(),
-            950,
-        );
-        // region Mun
-        graph.add_edge(
-            menu_tree["Low Kerbin Orbit (80km)"].index().clone(),
-            menu_tree["Mun Intercept"].index().clone(),
-            860,
-        );
-        graph.add_edge(
-            menu_tree["Mun Intercept"].index().clone(),
-            menu_tree["Low Mun Orbit (14km)"].index().clone(),
-            280,
-        );
-        graph.add_edge(
-            menu_tree["Low Mun Orbit (14km)"].index().clone(),
-            menu_tree["Mun Surface"].index().clone(),
-            580,
-        );
-        // endregion Mun
-        // region Minmus
+    /// Returns a DeltavMap for the Real Solar System mod, which replaces the Kerbol system with the
+    /// real one. Its data is stored in `maps/rss.deltav` and its root category is `Sol`
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let rss_map = DeltavMap::new_rss();
+    /// let deltav = rss_map.calculate_delta_v("Earth Surface", "Moon Surface");
+    /// ```
+    pub fn new_rss() -> DeltavMap {
+        DeltavMap::from_dsl(RSS_MAP).expect("The RSS map is invalid")
+    }
+}
+
+// Maps are equal if they have the same nodes and categories and the same edges, regardless of
+// the order the edges were added in
+impl<N: PartialEq> PartialEq for DeltavMap<N> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.menu_tree != other.menu_tree
+            || self.tags != other.tags
+            || self.metadata != other.metadata
+            || !self.graph.node_weights().eq(other.graph.node_weights())
+            || self.graph.edge_count() != other.graph.edge_count()
+        {
+            return false;
+        }
+
+        // The edges are undirected, so their endpoints are sorted before comparing them. Only
+        // aerobraking depends on the direction the edge was added in, so those keep their order
+        fn edges<N>(map: &DeltavMap<N>) -> Vec<(NodeIndex, NodeIndex, &EdgeInfo)> {
+            map.graph
+                .edge_references()
+                .map(|edge| {
+                    let (a, b) = (edge.source(), edge.target());
+                    if edge.weight().aerobrake.is_some() {
+                        (a, b, edge.weight())
+                    } else {
+                        (a.min(b), a.max(b), edge.weight())
+                    }
+                })
+                .collect()
+        }
+        let mut unmatched = edges(other);
+        edges(self).into_iter().all(|edge| {
+            match unmatched.iter().position(|candidate| *candidate == edge) {
+                Some(position) => {
+                    unmatched.swap_remove(position);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        CalcOptions, CostMode, DeltavMap, EdgeInfo, MarginProfile, MenuNode, MenuTree, Metadata,
+        Rounding, RoutingMode, WarningKind, DAY, GRAVITY_ASSIST_TAG,
+    };
+    use petgraph::graph::UnGraph;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::fs::File;
+    use std::sync::Arc;
+
+    pub fn get_test_map() -> DeltavMap {
+        let mut graph: UnGraph<Arc<str>, EdgeInfo> = UnGraph::new_undirected();
+
+        let mut node =
+            |name: &str| MenuTree::new_node(name, graph.add_node(Arc::from(name)).into());
+        let menu_tree = MenuTree::new_category("Category1")
+            .with_child(
+                MenuTree::new_category("Category2")
+                    .with_child(node("Node1"))
+                    .with_child(node("Node2")),
+            )
+            .with_child(node("Node3"))
+            .with_child(node("Node4"));
+
+        let index = |name| menu_tree.search(name).unwrap().index();
+        graph.add_edge(index("Node1"), index("Node2"), EdgeInfo::new(900));
         graph.add_edge(
-            menu_tree["Low Kerbin Orbit (80km)"].index().clone(),
-            menu_tree["Minmus Intercept"].index().clone(),
-            930,
+            index("Node2"),
+            index("Node3"),
+            EdgeInfo::new(80).with_range(60, 120),
         );
-        graph.add_edge(
-            menu_tree["Minmus Intercept"].index().clone(),
-            menu_tree["Low Minmus Orbit (10km)"].index().clone(),
-            160,
+        graph.add_edge(index("Node3"), index("Node4"), EdgeInfo::new(50));
+
+        let tags = BTreeMap::from([(
+            String::from("Node2"),
+            BTreeSet::from([String::from("atmosphere")]),
+        )]);
+
+        let metadata = Metadata {
+            margin_profiles: BTreeMap::from([(String::from("heavy_lander"), 30)]),
+            ..Metadata::default()
+        };
+
+        DeltavMap {
+            menu_tree,
+            graph,
+            tags,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let file = File::open("test_res/test.json").unwrap();
+        let json: serde_json::Value = serde_json::from_reader(file).unwrap();
+        let deltav_map: DeltavMap = serde_json::from_value(json).unwrap();
+
+        assert_eq!(
+            deltav_map,
+            get_test_map(),
+            "The deserialized map doesn't equal the test map"
+        )
+    }
+
+    #[test]
+    fn test_eq() {
+        let test_map = get_test_map();
+
+        // The same edges added in another order and direction
+        let mut reordered = get_test_map();
+        reordered.graph.clear_edges();
+        for (from, to, cost) in [("Node4", "Node3", 50), ("Node1", "Node2", 900)] {
+            let from = reordered.node_index(from).unwrap();
+            let to = reordered.node_index(to).unwrap();
+            reordered.graph.add_edge(from, to, EdgeInfo::new(cost));
+        }
+        let node2 = reordered.node_index("Node2").unwrap();
+        let node3 = reordered.node_index("Node3").unwrap();
+        let edge = test_map.edge_info("Node2", "Node3").unwrap().unwrap();
+        reordered.graph.add_edge(node3, node2, edge.clone());
+        assert_eq!(reordered, test_map);
+
+        let mut changed = get_test_map();
+        let edge = changed.graph.edge_indices().next().unwrap();
+        changed.graph[edge] = EdgeInfo::new(901);
+        assert_ne!(changed, test_map);
+
+        // Aerobraking only works in one direction, so reversing such an edge changes the map
+        let map = |text| DeltavMap::from_dsl(text).unwrap();
+        assert_ne!(
+            map("[Kerbol]\nA\nB\nA -> B : 100 aerobrake=0"),
+            map("[Kerbol]\nA\nB\nB -> A : 100 aerobrake=0")
         );
-        graph.add_edge(
-            menu_tree["Low Minmus Orbit (10km)"].index().clone(),
-            menu_tree["Minmus Surface"].index().clone(),
-            180,
+        assert_eq!(
+            map("[Kerbol]\nA\nB\nA -> B : 100"),
+            map("[Kerbol]\nA\nB\nB -> A : 100")
         );
-        // endregion Minmus
-        // endregion Kerbin
-        // region Eve
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Eve Intercept"].index().clone(),
-            90,
+    }
+
+    #[test]
+    fn test_sort_menu_tree() {
+        let mut test_map = get_test_map();
+        test_map.sort_menu_tree_by(|a, b| b.name().cmp(a.name()));
+        let names: Vec<&str> = test_map.menu_tree.iter().map(MenuNode::name).collect();
+        assert_eq!(
+            names,
+            ["Category1", "Node4", "Node3", "Category2", "Node2", "Node1"]
         );
-        graph.add_edge(
-            menu_tree["Eve Intercept"].index().clone(),
-            menu_tree["Eve Capture (100km - 85Mm)"].index().clone(),
-            80,
-        );
-        graph.add_edge(
-            menu_tree["Eve Capture (100km - 85Mm)"].index().clone(),
-            menu_tree["Low Eve Orbit (100km)"].index().clone(),
-            1350,
-        );
-        graph.add_edge(
-            menu_tree["Low Eve Orbit (100km)"].index().clone(),
-            menu_tree["Eve Surface"].index().clone(),
-            8000,
-        );
-        // region Gilly
-        graph.add_edge(
-            menu_tree["Eve Capture (100km - 85Mm)"].index().clone(),
-            menu_tree["Gilly Intercept"].index().clone(),
-            60,
-        );
-        graph.add_edge(
-            menu_tree["Gilly Intercept"].index().clone(),
-            menu_tree["Low Gilly Orbit (10km)"].index().clone(),
-            410,
-        );
-        graph.add_edge(
-            menu_tree["Low Gilly Orbit (10km)"].index().clone(),
-            menu_tree["Gilly Surface"].index().clone(),
-            30,
-        );
-        // endregion Gilly
-        // endregion Eve
-        // region Duna
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Duna Intercept"].index().clone(),
-            130,
-        );
-        graph.add_edge(
-            menu_tree["Duna Intercept"].index().clone(),
-            menu_tree["Duna Capture (60km - 48Mm)"].index().clone(),
-            250,
-        );
-        graph.add_edge(
-            menu_tree["Duna Capture (60km - 48Mm)"].index().clone(),
-            menu_tree["Low Duna Orbit (60km)"].index().clone(),
-            360,
-        );
-        graph.add_edge(
-            menu_tree["Low Duna Orbit (60km)"].index().clone(),
-            menu_tree["Duna Surface"].index().clone(),
-            1450,
-        );
-        // region Ike
-        graph.add_edge(
-            menu_tree["Duna Capture (60km - 48Mm)"].index().clone(),
-            menu_tree["Ike Intercept"].index().clone(),
-            30,
-        );
-        graph.add_edge(
-            menu_tree["Ike Intercept"].index().clone(),
-            menu_tree["Low Ike Orbit (10km)"].index().clone(),
-            180,
-        );
-        graph.add_edge(
-            menu_tree["Low Ike Orbit (10km)"].index().clone(),
-            menu_tree["Ike Surface"].index().clone(),
-            390,
-        );
-        // endregion Ike
-        // endregion Duna
-        // region Jool
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Jool Intercept"].index().clone(),
-            980,
-        );
-        graph.add_edge(
-            menu_tree["Jool Intercept"].index().clone(),
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            160,
-        );
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Low Jool Orbit (210km)"].index().clone(),
-            2810,
-        );
-        graph.add_edge(
-            menu_tree["Low Jool Orbit (210km)"].index().clone(),
-            menu_tree["Jool Surface"].index().clone(),
-            14000,
-        );
-        // region Pol
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Pol Intercept"].index().clone(),
-            160,
-        );
-        graph.add_edge(
-            menu_tree["Pol Intercept"].index().clone(),
-            menu_tree["Low Pol Orbit (10km)"].index().clone(),
-            820,
-        );
-        graph.add_edge(
-            menu_tree["Low Pol Orbit (10km)"].index().clone(),
-            menu_tree["Pol Surface"].index().clone(),
-            130,
-        );
-        // endregion Pol
-        // region Bop
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Bop Intercept"].index().clone(),
-            220,
-        );
-        graph.add_edge(
-            menu_tree["Bop Intercept"].index().clone(),
-            menu_tree["Low Bop Orbit (30km)"].index().clone(),
-            900,
-        );
-        graph.add_edge(
-            menu_tree["Low Bop Orbit (30km)"].index().clone(),
-            menu_tree["Bop Surface"].index().clone(),
-            230,
-        );
-        // endregion Bop
-        // region Tylo
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Tylo Intercept"].index().clone(),
-            400,
-        );
-        graph.add_edge(
-            menu_tree["Tylo Intercept"].index().clone(),
-            menu_tree["Low Tylo Orbit (10km)"].index().clone(),
-            1100,
-        );
-        graph.add_edge(
-            menu_tree["Low Tylo Orbit (10km)"].index().clone(),
-            menu_tree["Tylo Surface"].index().clone(),
-            2270,
-        );
-        // endregion Tylo
-        // region Vall
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Vall Intercept"].index().clone(),
-            620,
-        );
-        graph.add_edge(
-            menu_tree["Vall Intercept"].index().clone(),
-            menu_tree["Low Vall Orbit (15km)"].index().clone(),
-            910,
-        );
-        graph.add_edge(
-            menu_tree["Low Vall Orbit (15km)"].index().clone(),
-            menu_tree["Vall Surface"].index().clone(),
-            860,
-        );
-        // endregion Vall
-        // region Laythe
-        graph.add_edge(
-            menu_tree["Jool Capture (210km - 268Mm)"].index().clone(),
-            menu_tree["Laythe Intercept"].index().clone(),
-            930,
-        );
-        graph.add_edge(
-            menu_tree["Laythe Intercept"].index().clone(),
-            menu_tree["Low Laythe Orbit (60km)"].index().clone(),
-            1070,
-        );
-        graph.add_edge(
-            menu_tree["Low Laythe Orbit (60km)"].index().clone(),
-            menu_tree["Laythe Surface"].index().clone(),
-            2900,
-        );
-        // endregion Vall
-        // endregion Jool
-        // region Dres
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Dres Intercept"].index().clone(),
-            610,
-        );
-        graph.add_edge(
-            menu_tree["Dres Intercept"].index().clone(),
-            menu_tree["Low Dres Orbit (12km)"].index().clone(),
-            1290,
-        );
-        graph.add_edge(
-            menu_tree["Low Dres Orbit (12km)"].index().clone(),
-            menu_tree["Dres Surface"].index().clone(),
-            430,
-        );
-        // endregion Dres
-        // region Moho
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Moho Intercept"].index().clone(),
-            760,
-        );
-        graph.add_edge(
-            menu_tree["Moho Intercept"].index().clone(),
-            menu_tree["Low Moho Orbit (20km)"].index().clone(),
-            2410,
-        );
-        graph.add_edge(
-            menu_tree["Low Moho Orbit (20km)"].index().clone(),
-            menu_tree["Moho Surface"].index().clone(),
-            870,
+
+        // The order is kept by the file formats
+        let json = serde_json::to_string(&test_map).unwrap();
+        let from_json: DeltavMap = serde_json::from_str(&json).unwrap();
+        let from_dsl = DeltavMap::from_dsl(&test_map.to_dsl()).unwrap();
+        for map in [from_json, from_dsl] {
+            let loaded: Vec<&str> = map.menu_tree.iter().map(MenuNode::name).collect();
+            assert_eq!(loaded, names);
+        }
+    }
+
+    #[test]
+    fn test_shared_names() {
+        let map = DeltavMap::new_stock();
+        for node in map.menu_tree.iter() {
+            if let Some(id) = node.id() {
+                let label = &map.graph[petgraph::graph::NodeIndex::from(id)];
+                assert!(Arc::ptr_eq(node.shared_name(), label));
+            }
+        }
+    }
+
+    #[test]
+    fn test_stock() {
+        let stock_map = DeltavMap::new_stock();
+
+        assert_eq!(stock_map.validate(), Ok(()));
+        assert_eq!(stock_map.graph.node_count(), 55);
+        assert_eq!(stock_map.menu_tree.name(), "Kerbol System");
+
+        assert_eq!(DeltavMap::stock(), &stock_map);
+        assert!(std::ptr::eq(DeltavMap::stock(), DeltavMap::stock()));
+    }
+
+    #[test]
+    fn test_rss() {
+        let rss_map = DeltavMap::new_rss();
+
+        assert_eq!(rss_map.validate(), Ok(()));
+        assert_eq!(rss_map.menu_tree.name(), "Sol");
+        assert_eq!(
+            rss_map.calculate_delta_v("Earth Surface", "Moon Surface"),
+            Ok(Some(15100))
         );
-        // endregion Moho
-        // region Eeloo
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Eeloo Intercept"].index().clone(),
-            1140,
+    }
+
+    #[test]
+    fn calculate_cost() {
+        let test_map = get_test_map();
+        let cost = test_map
+            .calculate_delta_v("Node1", "Node4")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(cost, 1030);
+    }
+
+    #[test]
+    fn calculate_route() {
+        let test_map = get_test_map();
+        let route = test_map
+            .calculate_route("Node1", "Node4", &CalcOptions::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(route.nodes(), vec!["Node1", "Node2", "Node3", "Node4"]);
+        assert_eq!(route.legs()[1].delta_v(), 80);
+        assert_eq!(route.delta_v(), 1030);
+
+        let options = CalcOptions::default();
+        assert!(test_map
+            .calculate_route("Category2", "Node4", &options)
+            .is_err());
+        assert!(test_map
+            .calculate_route("Node1", "Category1", &options)
+            .is_err());
+        assert!(test_map.calculate_delta_v("Category2", "Node4").is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        let test_map = get_test_map();
+
+        assert_eq!(test_map.tags("Node2").unwrap(), vec!["atmosphere"]);
+        assert!(test_map.tags("Node1").unwrap().is_empty());
+        assert!(test_map.tags("test").is_err());
+        assert_eq!(test_map.nodes_with_tag("atmosphere"), vec!["Node2"]);
+    }
+
+    #[test]
+    fn avoid_tag() {
+        let test_map = get_test_map();
+        let options = CalcOptions::new().avoid_tag("atmosphere");
+
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap();
+        assert_eq!(route, None);
+
+        let route = test_map
+            .calculate_route("Node2", "Node4", &options)
+            .unwrap();
+        assert_eq!(route.unwrap().delta_v(), 130);
+    }
+
+    #[test]
+    fn avoid_node() {
+        let test_map = get_test_map();
+        let options = CalcOptions::new().avoid_node("Node3");
+        assert_eq!(options.avoided_nodes().collect::<Vec<_>>(), vec!["Node3"]);
+
+        let route = test_map
+            .calculate_route("Node2", "Node4", &options)
+            .unwrap();
+        assert_eq!(route, None);
+
+        let route = test_map
+            .calculate_route("Node3", "Node4", &options)
+            .unwrap();
+        assert_eq!(route.unwrap().delta_v(), 50);
+    }
+
+    #[test]
+    fn route_warnings() {
+        let test_map = get_test_map();
+        let route = test_map
+            .calculate_route("Node1", "Node4", &CalcOptions::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(route.warnings().len(), 1);
+        assert_eq!(route.warnings()[0].kind(), WarningKind::AtmosphericEntry);
+        assert_eq!(route.warnings()[0].leg(), 0);
+
+        let stock_map = DeltavMap::new_stock();
+        let route = stock_map
+            .calculate_route(
+                "Tylo Surface",
+                "Low Tylo Orbit (10km)",
+                &CalcOptions::default(),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(route.warnings().len(), 1);
+        assert_eq!(route.warnings()[0].kind(), WarningKind::HighGravity);
+    }
+
+    #[test]
+    fn test_edge_info() {
+        let test_map = get_test_map();
+
+        assert_eq!(
+            test_map.edge_info("Node2", "Node1").unwrap(),
+            Some(&EdgeInfo::new(900))
         );
-        graph.add_edge(
-            menu_tree["Eeloo Intercept"].index().clone(),
-            menu_tree["Low Eeloo Orbit (10km)"].index().clone(),
-            1370,
+        assert_eq!(test_map.edge_info("Node1", "Node3").unwrap(), None);
+        assert!(test_map.edge_info("Node1", "test").is_err());
+        assert!(test_map.edge_info("Category2", "Node1").is_err());
+    }
+
+    #[test]
+    fn test_edges() {
+        let test_map = get_test_map();
+        let edges: Vec<(&str, &str, i32)> = test_map
+            .edges()
+            .map(|(from, to, edge)| (from, to, edge.cost()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("Node1", "Node2", 900),
+                ("Node2", "Node3", 80),
+                ("Node3", "Node4", 50)
+            ]
         );
-        graph.add_edge(
-            menu_tree["Low Eeloo Orbit (10km)"].index().clone(),
-            menu_tree["Eeloo Surface"].index().clone(),
-            620,
+
+        let mut edges = test_map.edges_of("Node3").unwrap();
+        edges.sort_by_key(|(_, to, _)| *to);
+        assert_eq!(edges[0].0, "Node3");
+        assert_eq!(edges[0].1, "Node2");
+        assert_eq!(edges[1].2.cost(), 50);
+        assert!(test_map.edges_of("test").is_err());
+    }
+
+    #[test]
+    fn test_max_legs() {
+        let stock_map = DeltavMap::new_stock();
+        let unlimited = stock_map
+            .calculate_route("Kerbin Surface", "Mun Surface", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        let limited = stock_map
+            .calculate_route(
+                "Kerbin Surface",
+                "Mun Surface",
+                &CalcOptions::new().max_legs(unlimited.legs().len()),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(limited.delta_v(), unlimited.delta_v());
+        assert_eq!(limited.legs().len(), unlimited.legs().len());
+
+        let test_map = get_test_map();
+        let options = CalcOptions::new().max_legs(2);
+        assert!(test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .is_none());
+        let route = test_map
+            .calculate_route("Node1", "Node3", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.nodes(), vec!["Node1", "Node2", "Node3"]);
+        assert_eq!(
+            test_map
+                .calculate_route("Node1", "Node1", &options)
+                .unwrap()
+                .unwrap()
+                .legs()
+                .len(),
+            0
         );
-        // endregion Moho
-        graph.add_edge(
-            menu_tree["Kerbin Capture"].index().clone(),
-            menu_tree["Elliptical Kerbol Orbit (610km - 13,600Mm)"]
-                .index()
-                .clone(),
-            6000,
+    }
+
+    #[test]
+    fn test_reversed() {
+        let stock_map = DeltavMap::new_stock();
+        let options = CalcOptions::new().aerobrake(true);
+        let route = stock_map
+            .calculate_route("Kerbin Surface", "Mun Surface", &options)
+            .unwrap()
+            .unwrap();
+        let reversed = route.reversed(&stock_map, &options).unwrap().unwrap();
+
+        assert_eq!(reversed.start(), "Mun Surface");
+        assert_eq!(reversed.end(), "Kerbin Surface");
+        let mut nodes = route.nodes();
+        nodes.reverse();
+        assert_eq!(reversed.nodes(), nodes);
+        // Landing on Kerbin is free with aerobraking, but launching from it isn't
+        assert!(reversed.delta_v() < route.delta_v());
+        assert!(reversed.legs().last().unwrap().aerobrake());
+    }
+
+    #[test]
+    fn test_spanning_tree() {
+        let mut test_map = get_test_map();
+        test_map
+            .add_edge("Node1", "Node4", EdgeInfo::new(2000))
+            .unwrap();
+        let tree = test_map.spanning_tree();
+
+        assert_eq!(tree.len(), 3);
+        assert!(tree
+            .iter()
+            .all(|(from, to, _)| (*from, *to) != ("Node1", "Node4")));
+
+        let stock_map = DeltavMap::new_stock();
+        assert_eq!(
+            stock_map.spanning_tree().len(),
+            stock_map.graph.node_count() - 1
         );
-        graph.add_edge(
-            menu_tree["Elliptical Kerbol Orbit (610km - 13,600Mm)"]
-                .index()
-                .clone(),
-            menu_tree["Low Kerbol Orbit (610km)"].index().clone(),
-            13700,
+    }
+
+    #[test]
+    fn test_huge_costs() {
+        let mut test_map = get_test_map();
+        test_map
+            .add_edge("Node1", "Node4", EdgeInfo::new(i32::MAX))
+            .unwrap();
+        test_map.remove("Node2").unwrap();
+
+        let expected = i32::MAX as i64 + 50;
+        assert_eq!(
+            test_map.calculate_delta_v("Node1", "Node3").unwrap(),
+            Some(expected)
         );
-        graph.add_edge(
-            menu_tree["Low Kerbol Orbit (610km)"].index().clone(),
-            menu_tree["Kerbol Surface"].index().clone(),
-            67000,
+        let options = CalcOptions::new().margin(10);
+        let route = test_map
+            .calculate_route("Node1", "Node3", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            route.cost().with_margin(&options).high,
+            expected + (expected * 10 + 99) / 100
         );
-        // endregion Kerbol
-
-        DeltavMap { menu_tree, graph }
     }
-}
 
-#[cfg(test)]
-impl PartialEq for DeltavMap {
-    fn eq(&self, other: &Self) -> bool {
-        self.menu_tree == other.menu_tree
-            && format!("{:?}", self.graph) == format!("{:?}", other.graph)
+    #[test]
+    fn test_neighbors() {
+        let test_map = get_test_map();
+        let neighbors = test_map.neighbors("Node2").unwrap();
+        let names: Vec<&str> = neighbors.iter().map(|(name, _)| *name).collect();
+
+        assert_eq!(names, vec!["Node1", "Node3"]);
+        assert_eq!(neighbors[0].1.cost, 900);
+        assert!(test_map.neighbors("Category1").is_err());
+        assert!(test_map.neighbors("test").is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::DeltavMap;
-    use crate::MenuTree::{EndNode, MiddleNode};
-    use petgraph::graph::UnGraph;
-    use std::fs::File;
+    #[test]
+    fn calculate_return() {
+        let stock_map = DeltavMap::new_stock();
+        let trip = stock_map
+            .calculate_return("Kerbin Surface", "Duna Surface")
+            .unwrap()
+            .unwrap();
 
-    fn get_test_map() -> DeltavMap {
-        let mut graph: UnGraph<String, i32> = UnGraph::new_undirected();
-
-        let menu_tree = MiddleNode {
-            name: "Category1".to_owned(),
-            children: vec![
-                MiddleNode {
-                    name: "Category2".to_owned(),
-                    children: vec![
-                        EndNode {
-                            name: String::from("Node1"),
-                            index: graph.add_node(String::from("Node1")),
-                        },
-                        EndNode {
-                            name: String::from("Node2"),
-                            index: graph.add_node(String::from("Node2")),
-                        },
-                    ],
-                },
-                EndNode {
-                    name: String::from("Node3"),
-                    index: graph.add_node(String::from("Node3")),
-                },
-                EndNode {
-                    name: String::from("Node4"),
-                    index: graph.add_node(String::from("Node4")),
-                },
-            ],
-        };
+        assert_eq!(trip.outbound().delta_v(), 4730);
+        assert_eq!(trip.inbound().delta_v(), 2190);
+        assert_eq!(trip.delta_v(), 6920);
+        assert!(trip.inbound().legs().last().unwrap().aerobrake());
 
-        graph.add_edge(
-            menu_tree["Node1"].index().clone(),
-            menu_tree["Node2"].index().clone(),
-            900,
+        // Without aerobraking both directions cost the same
+        assert_eq!(
+            stock_map
+                .calculate_delta_v("Duna Surface", "Kerbin Surface")
+                .unwrap(),
+            Some(6540)
         );
-        graph.add_edge(
-            menu_tree["Node2"].index().clone(),
-            menu_tree["Node3"].index().clone(),
-            80,
+    }
+
+    #[test]
+    fn calculate_round_trip() {
+        let stock_map = DeltavMap::new_stock();
+        let options = CalcOptions::new().aerobrake(true).margin(10);
+        let report = stock_map
+            .calculate_round_trip("Kerbin Surface", "Mun Surface", &options)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.outbound().delta_v(), 5120);
+        assert_eq!(report.inbound().delta_v(), 1720);
+        assert_eq!(report.delta_v(), 6840);
+        assert_eq!(report.outbound_budget(), 5632);
+        assert_eq!(report.inbound_budget(), 1892);
+        assert_eq!(report.total_budget(), 7524);
+    }
+
+    #[test]
+    fn margin_profiles() {
+        let test_map = get_test_map();
+
+        assert_eq!(
+            test_map.margin_profile("heavy_lander"),
+            Some(MarginProfile::new("heavy_lander", 30))
         );
-        graph.add_edge(
-            menu_tree["Node3"].index().clone(),
-            menu_tree["Node4"].index().clone(),
-            50,
+        assert_eq!(
+            test_map.margin_profile("probe"),
+            Some(MarginProfile::new("probe", 5))
         );
+        assert_eq!(test_map.margin_profile("test"), None);
+        assert_eq!(test_map.margin_profiles().len(), 4);
 
-        DeltavMap { menu_tree, graph }
+        let options =
+            CalcOptions::new().margin_profile(&test_map.margin_profile("crewed").unwrap());
+        assert_eq!(options.margin_percent(), 15);
     }
 
     #[test]
-    fn test_deserialize() {
-        let file = File::open("test_res/test.json").unwrap();
-        let json: serde_json::Value = serde_json::from_reader(file).unwrap();
-        let deltav_map: DeltavMap = serde_json::from_value(json).unwrap();
+    fn cost_modes() {
+        let test_map = get_test_map();
+
+        let route = test_map
+            .calculate_route("Node1", "Node4", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 1030);
+
+        let cost = test_map
+            .calculate_cost("Node1", "Node4", &CalcOptions::new().margin(10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cost.nominal, 1030);
+        assert_eq!(cost.low, 1010);
+        assert_eq!(cost.high, 1177);
+
+        let options = CalcOptions::new().cost_mode(CostMode::Pessimistic);
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 1070);
+    }
+
+    #[test]
+    fn transfer_windows() {
+        let stock_map = DeltavMap::new_stock();
+        let edge = stock_map
+            .edge_info("Kerbin Capture", "Duna Intercept")
+            .unwrap()
+            .unwrap();
 
+        assert_eq!(edge.transfer_window().unwrap().phase_angle(), 44.0);
         assert_eq!(
-            deltav_map,
-            get_test_map(),
-            "The deserialized map doesn't equal the test map"
-        )
+            edge.transfer_window().unwrap().ejection_angle(),
+            Some(138.0)
+        );
+        assert!(stock_map
+            .edge_info("Low Kerbin Orbit (80km)", "Mun Intercept")
+            .unwrap()
+            .unwrap()
+            .transfer_window()
+            .is_none());
     }
 
     #[test]
-    fn test_stock() {
-        let _ = DeltavMap::new_stock();
+    fn routing_modes() {
+        let mut test_map = get_test_map();
+        let node1 = test_map.node_index("Node1").unwrap();
+        let node4 = test_map.node_index("Node4").unwrap();
+        test_map
+            .graph
+            .add_edge(node1, node4, EdgeInfo::new(2000).with_travel_time(100));
+        let edge = test_map
+            .graph
+            .find_edge(node1, test_map.node_index("Node2").unwrap());
+        test_map.graph[edge.unwrap()] = EdgeInfo::new(900).with_travel_time(10 * DAY);
+
+        let route = test_map
+            .calculate_route("Node1", "Node4", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 1030);
+        assert_eq!(route.travel_time(), 10 * DAY);
+
+        let options = CalcOptions::new().routing_mode(RoutingMode::Time);
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 2000);
+        assert_eq!(route.travel_time(), 100);
+
+        // Saving 10 days is worth 50 m/s each, the detour costs 970 m/s more
+        let options = CalcOptions::new().routing_mode(RoutingMode::Weighted(50));
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 1030);
+
+        let options = CalcOptions::new().routing_mode(RoutingMode::Weighted(100));
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 2000);
     }
 
     #[test]
-    fn calculate_cost() {
+    fn gravity_assists() {
+        let mut test_map = get_test_map();
+        let node1 = test_map.node_index("Node1").unwrap();
+        let node3 = test_map.node_index("Node3").unwrap();
+        test_map.graph.add_edge(
+            node1,
+            node3,
+            EdgeInfo::new(500).with_tag(GRAVITY_ASSIST_TAG),
+        );
+
+        let route = test_map
+            .calculate_route("Node1", "Node4", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.delta_v(), 1030);
+
+        let options = CalcOptions::new().gravity_assists(true);
+        let route = test_map
+            .calculate_route("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.nodes(), vec!["Node1", "Node3", "Node4"]);
+        assert_eq!(route.delta_v(), 550);
+    }
+
+    #[test]
+    fn scaled() {
         let test_map = get_test_map();
+        let scaled = test_map.scaled(2.5);
+
+        assert_eq!(
+            scaled.calculate_delta_v("Node1", "Node4").unwrap(),
+            Some(2575)
+        );
+        assert_eq!(
+            test_map.calculate_delta_v("Node1", "Node4").unwrap(),
+            Some(1030)
+        );
+
         let cost = test_map
-            .calculate_delta_v("Node1", "Node4")
+            .calculate_cost("Node1", "Node4", &CalcOptions::new().scale(2.5))
             .unwrap()
             .unwrap();
+        assert_eq!(cost.nominal, 2575);
+        assert_eq!(cost.high, 2675);
 
-        assert_eq!(cost, 1030);
+        // 990 + 88 + 55 m/s, rounded per edge
+        let rounded = test_map.scaled_with(1.1, Rounding::NearestTen);
+        assert_eq!(
+            rounded.calculate_delta_v("Node1", "Node4").unwrap(),
+            Some(1140)
+        );
+
+        // A negative factor can't make the route through the cheapest edges win
+        let negative = test_map.scaled(-1.0);
+        assert_eq!(
+            negative.calculate_delta_v("Node1", "Node4").unwrap(),
+            Some(11)
+        );
+        assert!(negative.graph.edge_weights().all(|edge| edge.cost() > 0));
+    }
+
+    #[test]
+    fn low_thrust() {
+        let mut test_map = get_test_map();
+        let node3 = test_map.node_index("Node3").unwrap();
+        let node4 = test_map.node_index("Node4").unwrap();
+        let edge = test_map.graph.find_edge(node3, node4).unwrap();
+        test_map.graph[edge] = EdgeInfo::new(50).with_low_thrust_factor(3.0);
+
+        assert_eq!(
+            test_map.calculate_delta_v("Node1", "Node4").unwrap(),
+            Some(1030)
+        );
+
+        let options = CalcOptions::new().low_thrust(true);
+        let cost = test_map
+            .calculate_cost("Node1", "Node4", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cost.nominal, 1130);
+        assert_eq!(cost.low, 1110);
+        assert_eq!(cost.high, 1170);
     }
 }
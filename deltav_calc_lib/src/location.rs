@@ -0,0 +1,182 @@
+use crate::math;
+use crate::{DeltavMap, MenuNode};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Where a vessel is, as reported by the game or stored in a save file
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Situation {
+    PreLaunch,
+    Orbiting,
+    SubOrbital,
+    Escaping,
+    Flying,
+    Landed,
+    Splashed,
+    Docked,
+}
+
+/// The state of a vessel that decides which node of a map it is at
+#[derive(Clone, PartialEq, Debug)]
+pub struct VesselState {
+    /// The name of the body the vessel orbits or is on
+    pub body: String,
+
+    /// Whether the vessel is landed, flying, orbiting and so on
+    pub situation: Situation,
+
+    /// The altitude of the periapsis above the surface in meters
+    pub periapsis: f64,
+
+    /// The altitude of the apoapsis above the surface in meters. It is infinite or negative for
+    /// orbits that escape the body
+    pub apoapsis: f64,
+}
+
+// How many times higher than the periapsis the apoapsis has to be for an orbit to count as
+// elliptical, which places the vessel at the capture node of the body
+const ELLIPTICAL: f64 = 4.0;
+
+impl DeltavMap {
+    /// Returns the node of the map the vessel is at. Nodes are looked up in the category named
+    /// after the body: landed and suborbital vessels are at the surface, escaping vessels and
+    /// vessels on elliptical orbits at the capture and other orbiting vessels at the orbit node
+    /// whose altitude in parentheses, like "(80km)", is the closest
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{Situation, VesselState};
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let vessel = VesselState {
+    ///     body: String::from("Kerbin"),
+    ///     situation: Situation::Orbiting,
+    ///     periapsis: 75_000.0,
+    ///     apoapsis: 90_000.0,
+    /// };
+    /// let stock_map = DeltavMap::new_stock();
+    /// assert_eq!(
+    ///     stock_map.vessel_node(&vessel),
+    ///     Some("Low Kerbin Orbit (80km)")
+    /// );
+    /// ```
+    pub fn vessel_node(&self, vessel: &VesselState) -> Option<&str> {
+        let nodes: Vec<&str> = self
+            .menu_tree
+            .iter()
+            .find(|node| node.is_category() && node.name() == vessel.body)?
+            .children()
+            .filter(|child| child.id().is_some())
+            .map(MenuNode::name)
+            .collect();
+        let named = |part: &str| nodes.iter().copied().find(|node| node.contains(part));
+
+        let elliptical = !vessel.apoapsis.is_finite()
+            || vessel.apoapsis < 0.0
+            || vessel.apoapsis > ELLIPTICAL * vessel.periapsis.max(1000.0);
+        match vessel.situation {
+            Situation::PreLaunch
+            | Situation::Landed
+            | Situation::Splashed
+            | Situation::Flying
+            | Situation::SubOrbital => named("Surface"),
+            Situation::Escaping => named("Capture"),
+            Situation::Orbiting | Situation::Docked if elliptical => named("Capture"),
+            Situation::Orbiting | Situation::Docked => {
+                // Compares the altitudes by their ratio, so 80km and 100km are closer than
+                // 2,800km and 3,000km
+                let altitude = ((vessel.periapsis + vessel.apoapsis) / 2.0).max(1.0);
+                nodes
+                    .iter()
+                    .filter(|node| !node.contains("Capture") && !node.contains("Surface"))
+                    .filter_map(|node| Some((parse_altitude(node)?, *node)))
+                    .min_by(|(a, _), (b, _)| {
+                        let distance = |node: f64| math::ln(altitude / node).abs();
+                        distance(*a).total_cmp(&distance(*b))
+                    })
+                    .map(|(_, node)| node)
+            }
+        }
+    }
+}
+
+// Reads the first altitude in parentheses of a node name, like 80000 from "Low Orbit (80km)"
+// or 100000 from "Capture (100km - 85Mm)"
+fn parse_altitude(name: &str) -> Option<f64> {
+    let (_, inside) = name.split_once('(')?;
+    let text = inside.split([')', '-']).next()?.trim();
+    let digits = text.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = text.split_at(digits);
+
+    let factor = match unit.trim() {
+        "m" => 1.0,
+        "km" => 1e3,
+        "Mm" => 1e6,
+        "Gm" => 1e9,
+        _ => return None,
+    };
+    Some(number.parse::<f64>().ok()? * factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_altitude;
+    use crate::{DeltavMap, Situation, VesselState};
+
+    fn vessel(body: &str, situation: Situation, periapsis: f64, apoapsis: f64) -> VesselState {
+        VesselState {
+            body: body.to_string(),
+            situation,
+            periapsis,
+            apoapsis,
+        }
+    }
+
+    #[test]
+    fn test_vessel_node() {
+        let map = DeltavMap::new_stock();
+        let node = |vessel| map.vessel_node(&vessel);
+
+        assert_eq!(
+            node(vessel("Kerbin", Situation::PreLaunch, 0.0, 0.0)),
+            Some("Kerbin Surface")
+        );
+        assert_eq!(
+            node(vessel("Mun", Situation::Orbiting, 12_000.0, 16_000.0)),
+            Some("Low Mun Orbit (14km)")
+        );
+        assert_eq!(
+            node(vessel(
+                "Kerbin",
+                Situation::Orbiting,
+                2_800_000.0,
+                2_900_000.0
+            )),
+            Some("Keostationary Orbit (2.868Mm)")
+        );
+        assert_eq!(
+            node(vessel("Eve", Situation::Orbiting, 100_000.0, 80_000_000.0)),
+            Some("Eve Capture (100km - 85Mm)")
+        );
+        assert_eq!(
+            node(vessel("Duna", Situation::Escaping, 50_000.0, f64::INFINITY)),
+            Some("Duna Capture (60km - 48Mm)")
+        );
+        assert_eq!(node(vessel("Nowhere", Situation::Landed, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_parse_altitude() {
+        assert_eq!(parse_altitude("Low Kerbin Orbit (80km)"), Some(80_000.0));
+        assert_eq!(
+            parse_altitude("Keostationary Orbit (2.868Mm)"),
+            Some(2_868_000.0)
+        );
+        assert_eq!(
+            parse_altitude("Eve Capture (100km - 85Mm)"),
+            Some(100_000.0)
+        );
+        assert_eq!(parse_altitude("Kerbin Capture"), None);
+        assert_eq!(parse_altitude("Orbit (high)"), None);
+    }
+}
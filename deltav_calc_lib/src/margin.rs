@@ -0,0 +1,65 @@
+use alloc::string::{String, ToString};
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A named safety margin, for example 15% for crewed missions
+///
+/// # Example
+/// ```
+/// use deltav_calc::{CalcOptions, DeltavMap, MarginProfile};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let profile = stock_map.margin_profile("crewed").unwrap();
+/// let options = CalcOptions::new().margin_profile(&profile);
+/// ```
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MarginProfile {
+    name: String,
+    percent: u32,
+}
+
+impl MarginProfile {
+    /// The profiles that are available for every map
+    pub const BUILTIN: [(&'static str, u32); 3] =
+        [("probe", 5), ("crewed", 15), ("first_attempt", 25)];
+
+    /// Creates a profile with the given name and margin in percent
+    pub fn new(name: &str, percent: u32) -> MarginProfile {
+        MarginProfile {
+            name: name.to_string(),
+            percent,
+        }
+    }
+
+    /// Returns the builtin profile with the given name, if there is one
+    pub fn builtin(name: &str) -> Option<MarginProfile> {
+        MarginProfile::BUILTIN
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(name, percent)| MarginProfile::new(name, *percent))
+    }
+
+    /// The name of the profile
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The margin in percent
+    pub fn percent(&self) -> u32 {
+        self.percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MarginProfile;
+
+    #[test]
+    fn test_builtin() {
+        assert_eq!(
+            MarginProfile::builtin("crewed"),
+            Some(MarginProfile::new("crewed", 15))
+        );
+        assert_eq!(MarginProfile::builtin("test"), None);
+    }
+}
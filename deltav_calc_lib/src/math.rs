@@ -0,0 +1,41 @@
+// The float functions that are only part of std, which fall back to libm without it
+
+#[cfg(feature = "std")]
+pub(crate) fn round(value: f64) -> f64 {
+    value.round()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn round(value: f64) -> f64 {
+    libm::round(value)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn ceil(value: f64) -> f64 {
+    value.ceil()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn ceil(value: f64) -> f64 {
+    libm::ceil(value)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn ln(value: f64) -> f64 {
+    value.ln()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn ln(value: f64) -> f64 {
+    libm::log(value)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn exp(value: f64) -> f64 {
+    value.exp()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn exp(value: f64) -> f64 {
+    libm::exp(value)
+}
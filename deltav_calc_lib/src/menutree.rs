@@ -1,14 +1,52 @@
+use crate::schema::TreeEntry;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
 use petgraph::graph::NodeIndex;
-use serde::Deserialize;
-use serde::Serialize;
+use serde::de::Error as _;
+use serde::ser::{Error as _, SerializeStructVariant};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt::{Debug, Display, Formatter};
-use std::ops::Index;
+
+/// The id of a node in the graph of a [`DeltavMap`](crate::DeltavMap)
+#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[serde(transparent)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// Creates the id of the node with the given index in the graph
+    pub fn new(index: usize) -> NodeId {
+        NodeId(index as u32)
+    }
+
+    /// The index of the node in the graph
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<NodeIndex> for NodeId {
+    fn from(index: NodeIndex) -> Self {
+        NodeId::new(index.index())
+    }
+}
+
+impl From<NodeId> for NodeIndex {
+    fn from(id: NodeId) -> Self {
+        NodeIndex::new(id.index())
+    }
+}
 
 /// This error is raised when a node is searched that doesn't exist. It saves the nodes name
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct NoSuchNodeError {
-    name: String,
+    pub(crate) name: String,
 }
 
 impl NoSuchNodeError {
@@ -18,7 +56,7 @@ impl NoSuchNodeError {
 }
 
 impl Display for NoSuchNodeError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "There is no node with the name \"{}\" in the tree",
@@ -27,123 +65,685 @@ impl Display for NoSuchNodeError {
     }
 }
 
+#[cfg(feature = "std")]
 impl Error for NoSuchNodeError {}
 
+/// The most levels of categories a menu tree can have when it is read or written. Deeper trees
+/// are rejected, so a hostile map file can't overflow the stack while it is parsed
+pub const MAX_DEPTH: usize = 32;
+
+// The message of the errors about trees that are nested too deep
+pub(crate) fn depth_error() -> String {
+    format!("The menu tree is nested more than {MAX_DEPTH} levels deep")
+}
+
+// The ways the category path of a node can be invalid
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub(crate) enum PathError {
+    // The path doesn't contain any category
+    Empty,
+
+    // The path starts with another root category than the tree, which is given
+    DifferentRoot(String),
+
+    // The path has more than `MAX_DEPTH` categories
+    TooDeep,
+}
+
 /// The menu trees represent nodes in the delta-v map and the categories they are put into
-#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
-pub enum MenuTree {
-    /// A node representing a category other nodes can be put into
-    MiddleNode {
-        name: String,
-        children: Vec<MenuTree>,
-    },
+///
+/// The nodes are stored in a flat list, where every category refers to its children by their
+/// position in the list. This keeps cloning and traversing the tree cheap even for big maps. The
+/// nodes are accessed through [`MenuNode`]s, which are returned by [`search`](MenuTree::search)
+/// and [`iter`](MenuTree::iter)
+#[derive(Clone)]
+pub struct MenuTree {
+    // The nodes of the tree, starting with the root. Removing a node rebuilds the list, so every
+    // node in it is reachable from the root
+    nodes: Vec<TreeNode>,
+}
 
-    /// A node holding an index to be used in the graph
-    EndNode { name: String, index: NodeIndex },
+// A node stored in the list of a menu tree
+#[derive(Clone)]
+struct TreeNode {
+    name: Arc<str>,
+    // The id of an end node in the graph, categories have none
+    id: Option<NodeId>,
+    // The positions of the children in the list of nodes
+    children: Vec<usize>,
+}
+
+/// A category or end node of a [`MenuTree`]. End nodes hold the id of a node in the graph
+#[derive(Clone, Copy)]
+pub struct MenuNode<'a> {
+    tree: &'a MenuTree,
+    pub(crate) position: usize,
 }
 
 impl MenuTree {
-    /// Gets the id of the node. if it's a middle node it panics
-    pub(crate) fn index(&self) -> &NodeIndex {
-        match self {
-            MenuTree::MiddleNode { .. } => {
-                panic!("MiddleNodes don't have indices");
+    /// Creates a tree of a single empty category
+    pub fn new_category(name: &str) -> MenuTree {
+        MenuTree::from_node(Arc::from(name), None)
+    }
+
+    /// Creates a tree of a single end node pointing at the node of the graph with the given id
+    pub fn new_node(name: &str, id: NodeId) -> MenuTree {
+        MenuTree::from_node(Arc::from(name), Some(id))
+    }
+
+    // Creates a tree of a single node sharing the name, so the name can be the one of the graph
+    pub(crate) fn from_node(name: Arc<str>, id: Option<NodeId>) -> MenuTree {
+        MenuTree {
+            nodes: vec![TreeNode {
+                name,
+                id,
+                children: vec![],
+            }],
+        }
+    }
+
+    /// Adds the tree as the last child of this category and returns the result. If this is an end
+    /// node it panics
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{MenuTree, NodeId};
+    ///
+    /// let tree = MenuTree::new_category("Kerbin")
+    ///     .with_child(MenuTree::new_node("Kerbin Surface", NodeId::new(0)))
+    ///     .with_child(MenuTree::new_category("Mun"));
+    ///
+    /// assert_eq!(tree.search("Mun").unwrap().id(), None);
+    /// ```
+    pub fn with_child(mut self, child: MenuTree) -> MenuTree {
+        self.append(0, child);
+        self
+    }
+
+    /// The root of the tree
+    pub fn root(&self) -> MenuNode<'_> {
+        self.node(0)
+    }
+
+    // Returns the node at the position in the list of nodes
+    pub(crate) fn node(&self, position: usize) -> MenuNode<'_> {
+        MenuNode {
+            tree: self,
+            position,
+        }
+    }
+
+    // Copies the nodes of the tree below the category at the position and returns the position of
+    // the root of the copy. If the position is an end node it panics
+    fn append(&mut self, parent: usize, tree: MenuTree) -> usize {
+        if self.nodes[parent].id.is_some() {
+            panic!("EndNodes don't have children");
+        }
+
+        let offset = self.nodes.len();
+        self.nodes.extend(tree.nodes.into_iter().map(|mut node| {
+            for child in &mut node.children {
+                *child += offset;
             }
-            MenuTree::EndNode { index, .. } => index,
+            node
+        }));
+        self.nodes[parent].children.push(offset);
+
+        offset
+    }
+
+    /// Adds a node to the category at the given path below this node. Missing categories are
+    /// created. If this is an end node it panics
+    pub(crate) fn insert(&mut self, path: &[&str], node: MenuTree) {
+        let mut parent = 0;
+        for category in path {
+            let existing = self.nodes[parent].children.iter().copied().find(|&child| {
+                let child = &self.nodes[child];
+                child.id.is_none() && *child.name == **category
+            });
+            parent = match existing {
+                Some(child) => child,
+                None => self.append(parent, MenuTree::new_category(category)),
+            };
         }
+
+        self.append(parent, node);
+    }
+
+    /// Adds the tree as the last child of the category at the position. If the position is an end
+    /// node it panics
+    pub(crate) fn push_child(&mut self, parent: usize, child: MenuTree) {
+        self.append(parent, child);
+    }
+
+    /// Changes the name of the node at the position
+    pub(crate) fn set_name(&mut self, position: usize, name: Arc<str>) {
+        self.nodes[position].name = name;
+    }
+
+    /// Removes the node with the given name from the children of this node or of the categories
+    /// below it and returns it. This node itself can't be removed
+    pub(crate) fn remove_child(&mut self, search_name: &str) -> Option<MenuTree> {
+        let parent = self
+            .iter()
+            .find(|node| node.children().any(|child| child.name() == search_name))?
+            .position;
+        let slot = self.nodes[parent]
+            .children
+            .iter()
+            .position(|&child| *self.nodes[child].name == *search_name)?;
+        let child = self.nodes[parent].children.remove(slot);
+
+        let removed = self.node(child).to_tree();
+        // Copying the rest of the tree drops the removed nodes from the list
+        *self = self.root().to_tree();
+        Some(removed)
+    }
+
+    /// Changes the index of the end node pointing at `old` to `new`
+    pub(crate) fn replace_index(&mut self, old: NodeId, new: NodeId) {
+        for node in &mut self.nodes {
+            if node.id == Some(old) {
+                node.id = Some(new);
+            }
+        }
+    }
+
+    /// Adds a node to the tree at the category path, which consists of the names of the categories
+    /// starting with the root separated by slashes, like `Kerbol System/Jool/Laythe`. If there is
+    /// no tree yet, it is created. The path can have at most [`MAX_DEPTH`] categories
+    pub(crate) fn insert_at_path(
+        tree: &mut Option<MenuTree>,
+        path: &str,
+        node: MenuTree,
+    ) -> Result<(), PathError> {
+        let path: Vec<&str> = path
+            .split('/')
+            .map(str::trim)
+            .filter(|category| !category.is_empty())
+            .collect();
+        if path.len() > MAX_DEPTH {
+            return Err(PathError::TooDeep);
+        }
+        let (root, path) = path.split_first().ok_or(PathError::Empty)?;
+
+        let tree = tree.get_or_insert_with(|| MenuTree::new_category(root));
+        if tree.name() != *root {
+            return Err(PathError::DifferentRoot(tree.name().to_string()));
+        }
+
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    /// Returns all end nodes below this node together with the names of the categories they are
+    /// in, starting with the name of this node
+    pub(crate) fn end_nodes(&self) -> Vec<(Vec<&str>, MenuNode<'_>)> {
+        self.root().end_nodes()
+    }
+
+    /// Returns the names of this node and all nodes below it
+    pub(crate) fn names(&self) -> Vec<&str> {
+        self.root().names()
+    }
+
+    /// Iterates over this node and all nodes below it in document order, so every category comes
+    /// before its children
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let surfaces: Vec<&str> = stock_map
+    ///     .menu_tree()
+    ///     .iter()
+    ///     .filter(|node| node.id().is_some())
+    ///     .map(|node| node.name())
+    ///     .filter(|name| name.ends_with("Surface"))
+    ///     .collect();
+    /// ```
+    pub fn iter(&self) -> MenuTreeIter<'_> {
+        self.root().iter()
+    }
+
+    /// The id of the node in the graph, or `None` if this is a category
+    pub fn id(&self) -> Option<NodeId> {
+        self.root().id()
+    }
+
+    /// The number of levels below the root, see [`MenuNode::depth`]
+    pub fn depth(&self) -> usize {
+        self.root().depth()
+    }
+
+    /// Lists this node and all nodes below it in document order together with their depth, which
+    /// is 0 for this node, for UIs that show the tree as an indented list. Categories have no id
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// for (depth, name, _) in stock_map.menu_tree().flatten() {
+    ///     println!("{}{name}", "  ".repeat(depth));
+    /// }
+    /// ```
+    pub fn flatten(&self) -> Vec<(usize, &str, Option<NodeId>)> {
+        self.root().flatten()
+    }
+
+    /// Returns a copy of the tree that only contains the end nodes matching the predicate and the
+    /// categories leading to them. Returns `None` if no end node matches
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::DeltavMap;
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let query = "laythe";
+    /// let menu = stock_map
+    ///     .menu_tree()
+    ///     .filter(|node| node.name().to_lowercase().contains(query))
+    ///     .unwrap();
+    ///
+    /// assert!(menu.search("Laythe Surface").is_ok());
+    /// assert!(menu.search("Kerbin Surface").is_err());
+    /// ```
+    pub fn filter(&self, predicate: impl FnMut(MenuNode<'_>) -> bool) -> Option<MenuTree> {
+        self.root().filter(predicate)
+    }
+
+    /// Sorts the children of this node and of all categories below it by their names
+    pub fn sort_children_by_name(&mut self) {
+        self.sort_children_by(|a, b| a.name().cmp(b.name()));
+    }
+
+    /// Sorts the children of this node and of all categories below it with the comparator. The
+    /// sort is stable, so children that compare equal keep their order
+    pub fn sort_children_by(
+        &mut self,
+        mut compare: impl FnMut(MenuNode<'_>, MenuNode<'_>) -> Ordering,
+    ) {
+        for position in 0..self.nodes.len() {
+            let mut children = core::mem::take(&mut self.nodes[position].children);
+            children.sort_by(|&a, &b| compare(self.node(a), self.node(b)));
+            self.nodes[position].children = children;
+        }
+    }
+
+    /// Walks through this node and all nodes below it in document order, calling the visitor for
+    /// every category and end node
+    pub fn walk(&self, visitor: &mut impl MenuTreeVisitor) {
+        self.root().walk(visitor);
     }
 
     pub fn name(&self) -> &str {
-        return match self {
-            MenuTree::MiddleNode { name, .. } | MenuTree::EndNode { name, .. } => name.as_str(),
-        };
+        self.root().name()
     }
 
     /// Searches for the node with the given name.
     ///
     /// If there is no node with that name, a [`NoSuchNodeError`] will be returned
-    pub fn search(&self, search_name: &str) -> Result<&MenuTree, NoSuchNodeError> {
-        match self {
-            MenuTree::EndNode { name, .. } => {
-                if name == search_name {
-                    Ok(self)
-                } else {
-                    Err(NoSuchNodeError {
-                        name: search_name.to_string(),
-                    })
-                }
+    pub fn search(&self, search_name: &str) -> Result<MenuNode<'_>, NoSuchNodeError> {
+        self.root().search(search_name)
+    }
+}
+
+impl<'a> MenuNode<'a> {
+    fn stored(self) -> &'a TreeNode {
+        &self.tree.nodes[self.position]
+    }
+
+    /// The name of the node or category
+    pub fn name(self) -> &'a str {
+        &self.stored().name
+    }
+
+    // The name shared with the graph
+    pub(crate) fn shared_name(self) -> &'a Arc<str> {
+        &self.stored().name
+    }
+
+    /// The id of the node in the graph, or `None` if this is a category
+    pub fn id(self) -> Option<NodeId> {
+        self.stored().id
+    }
+
+    /// Gets the id of the node. if it's a middle node it panics
+    pub(crate) fn index(self) -> NodeIndex {
+        match self.id() {
+            None => panic!("MiddleNodes don't have indices"),
+            Some(id) => NodeIndex::from(id),
+        }
+    }
+
+    /// The number of levels below this node, which is 0 for end nodes and empty categories. Trees
+    /// deeper than [`MAX_DEPTH`] can't be serialized
+    pub fn depth(self) -> usize {
+        let mut deepest = 0;
+        let mut stack = vec![(0, self)];
+        while let Some((depth, node)) = stack.pop() {
+            deepest = deepest.max(depth);
+            stack.extend(node.children().map(|child| (depth + 1, child)));
+        }
+
+        deepest
+    }
+
+    /// Whether this is a category, which can have children, instead of an end node
+    pub fn is_category(self) -> bool {
+        self.id().is_none()
+    }
+
+    /// The nodes and categories directly in this category. End nodes have no children
+    pub fn children(self) -> impl DoubleEndedIterator<Item = MenuNode<'a>> + ExactSizeIterator {
+        let tree = self.tree;
+        self.stored()
+            .children
+            .iter()
+            .map(move |&position| tree.node(position))
+    }
+
+    /// Copies this node and all nodes below it into a new tree
+    pub fn to_tree(self) -> MenuTree {
+        let mut tree = MenuTree { nodes: Vec::new() };
+        // The nodes to copy with the position of the copy of their parent
+        let mut stack: Vec<(MenuNode, Option<usize>)> = vec![(self, None)];
+
+        while let Some((node, parent)) = stack.pop() {
+            let copy = tree.nodes.len();
+            tree.nodes.push(TreeNode {
+                name: node.shared_name().clone(),
+                id: node.id(),
+                children: Vec::with_capacity(node.children().len()),
+            });
+            if let Some(parent) = parent {
+                tree.nodes[parent].children.push(copy);
             }
+            stack.extend(node.children().rev().map(|child| (child, Some(copy))));
+        }
 
-            MenuTree::MiddleNode { name, children } => {
-                if name == search_name {
-                    return Ok(self);
-                }
+        tree
+    }
 
-                for child in children {
-                    let result = child.search(search_name);
+    /// Returns all end nodes below this node together with the names of the categories they are
+    /// in, starting with the name of this node
+    pub(crate) fn end_nodes(self) -> Vec<(Vec<&'a str>, MenuNode<'a>)> {
+        let mut end_nodes = Vec::new();
+        // The categories the walk is currently in, only copied for the end nodes
+        let mut path = Vec::new();
+        let mut stack = vec![Step::Visit(self)];
 
-                    match result {
-                        Ok(_) => return result,
-                        Err(_) => {}
-                    }
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(node) if node.is_category() => {
+                    path.push(node.name());
+                    stack.push(Step::Leave(node.name()));
+                    stack.extend(node.children().rev().map(Step::Visit));
                 }
+                Step::Visit(node) => end_nodes.push((path.clone(), node)),
+                Step::Leave(_) => {
+                    path.pop();
+                }
+            }
+        }
+
+        end_nodes
+    }
+
+    /// Returns the names of this node and all nodes below it
+    pub(crate) fn names(self) -> Vec<&'a str> {
+        self.iter().map(MenuNode::name).collect()
+    }
+
+    /// Iterates over this node and all nodes below it in document order, see [`MenuTree::iter`]
+    pub fn iter(self) -> MenuTreeIter<'a> {
+        MenuTreeIter {
+            tree: self.tree,
+            stack: vec![self.position],
+        }
+    }
 
-                Err(NoSuchNodeError {
-                    name: search_name.to_string(),
-                })
+    /// Lists this node and all nodes below it with their depth, see [`MenuTree::flatten`]
+    pub fn flatten(self) -> Vec<(usize, &'a str, Option<NodeId>)> {
+        let mut list = Vec::new();
+        let mut stack = vec![(0, self)];
+
+        while let Some((depth, node)) = stack.pop() {
+            list.push((depth, node.name(), node.id()));
+            stack.extend(node.children().rev().map(|child| (depth + 1, child)));
+        }
+
+        list
+    }
+
+    /// Copies this node and the end nodes below it matching the predicate, see
+    /// [`MenuTree::filter`]
+    pub fn filter(self, mut predicate: impl FnMut(MenuNode<'_>) -> bool) -> Option<MenuTree> {
+        if !self.is_category() {
+            return predicate(self).then(|| self.to_tree());
+        }
+
+        // Inserting the matching end nodes in document order recreates the categories leading to
+        // them in their original order
+        let mut filtered: Option<MenuTree> = None;
+        for (path, node) in self.end_nodes() {
+            if predicate(node) {
+                filtered
+                    .get_or_insert_with(|| MenuTree::from_node(self.shared_name().clone(), None))
+                    .insert(&path[1..], node.to_tree());
             }
         }
+
+        filtered
+    }
+
+    /// Walks through this node and all nodes below it, see [`MenuTree::walk`]
+    pub fn walk(self, visitor: &mut impl MenuTreeVisitor) {
+        let mut stack = vec![Step::Visit(self)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(node) if node.is_category() => {
+                    visitor.enter_category(node.name());
+                    stack.push(Step::Leave(node.name()));
+                    stack.extend(node.children().rev().map(Step::Visit));
+                }
+                Step::Visit(node) => visitor.visit_node(node.name()),
+                Step::Leave(name) => visitor.leave_category(name),
+            }
+        }
+    }
+
+    /// Searches for the node with the given name below this node, see [`MenuTree::search`]
+    pub fn search(self, search_name: &str) -> Result<MenuNode<'a>, NoSuchNodeError> {
+        self.iter()
+            .find(|node| node.name() == search_name)
+            .ok_or_else(|| NoSuchNodeError {
+                name: search_name.to_string(),
+            })
     }
 }
 
-impl Index<&str> for MenuTree {
-    type Output = MenuTree;
+impl Debug for MenuNode<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MenuNode")
+            .field("name", &self.name())
+            .field("id", &self.id())
+            .finish()
+    }
+}
 
-    fn index(&self, index: &str) -> &Self::Output {
-        match self.search(index) {
-            Ok(result) => result,
-            Err(e) => {
-                panic!("{e}")
+// Trees are compared by their structure, so it doesn't matter in which order the nodes are stored
+impl PartialEq for MenuTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.flatten() == other.flatten()
+    }
+}
+
+impl Eq for MenuTree {}
+
+impl Hash for MenuTree {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.flatten().hash(state);
+    }
+}
+
+impl Debug for MenuTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.flatten()).finish()
+    }
+}
+
+// The nodes are serialized nested, with categories as `MiddleNode`s holding their children and
+// nodes as `EndNode`s holding their index, which is the format the tree has always been saved in
+struct NestedNode<'a>(MenuNode<'a>);
+
+struct NestedChildren<'a>(MenuNode<'a>);
+
+impl Serialize for NestedNode<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let node = self.0;
+        match node.id() {
+            None => {
+                let mut variant =
+                    serializer.serialize_struct_variant("MenuTree", 0, "MiddleNode", 2)?;
+                variant.serialize_field("name", node.name())?;
+                variant.serialize_field("children", &NestedChildren(node))?;
+                variant.end()
             }
+            Some(id) => {
+                let mut variant =
+                    serializer.serialize_struct_variant("MenuTree", 1, "EndNode", 2)?;
+                variant.serialize_field("name", node.name())?;
+                variant.serialize_field("index", &id)?;
+                variant.end()
+            }
+        }
+    }
+}
+
+impl Serialize for NestedChildren<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.children().map(NestedNode))
+    }
+}
+
+// Serializing is recursive, so the depth is checked first
+impl Serialize for MenuTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.depth() > MAX_DEPTH {
+            return Err(S::Error::custom(depth_error()));
         }
+
+        NestedNode(self.root()).serialize(serializer)
+    }
+}
+
+// The nested entries are read like the menu trees of old map files, which rejects trees deeper
+// than `MAX_DEPTH` while parsing
+impl<'de> Deserialize<'de> for MenuTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TreeEntry::deserialize(deserializer)?
+            .build_legacy()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Callbacks for walking through a [`MenuTree`] with [`MenuTree::walk`]. All callbacks do nothing
+/// by default
+///
+/// # Example
+/// ```
+/// use deltav_calc::{DeltavMap, MenuTreeVisitor};
+///
+/// // Prints the tree with every level indented
+/// struct Printer {
+///     depth: usize,
+/// }
+///
+/// impl MenuTreeVisitor for Printer {
+///     fn enter_category(&mut self, name: &str) {
+///         println!("{}{name}", "  ".repeat(self.depth));
+///         self.depth += 1;
+///     }
+///
+///     fn leave_category(&mut self, _name: &str) {
+///         self.depth -= 1;
+///     }
+///
+///     fn visit_node(&mut self, name: &str) {
+///         println!("{}{name}", "  ".repeat(self.depth));
+///     }
+/// }
+///
+/// DeltavMap::new_stock().menu_tree().walk(&mut Printer { depth: 0 });
+/// ```
+pub trait MenuTreeVisitor {
+    /// Called when a category is entered, before its children are visited
+    fn enter_category(&mut self, _name: &str) {}
+
+    /// Called when a category is left, after all its children were visited
+    fn leave_category(&mut self, _name: &str) {}
+
+    /// Called for every end node
+    fn visit_node(&mut self, _name: &str) {}
+}
+
+// A step of walking through a menu tree
+enum Step<'a> {
+    Visit(MenuNode<'a>),
+    Leave(&'a str),
+}
+
+/// An iterator over the nodes of a [`MenuTree`] in document order, see [`MenuTree::iter`]
+#[derive(Clone, Debug)]
+pub struct MenuTreeIter<'a> {
+    tree: &'a MenuTree,
+    // The positions of the nodes that are yet to be visited, with the next one on top
+    stack: Vec<usize>,
+}
+
+impl<'a> Iterator for MenuTreeIter<'a> {
+    type Item = MenuNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.stack.pop()?);
+        self.stack
+            .extend(node.stored().children.iter().rev().copied());
+
+        Some(node)
+    }
+}
+
+impl<'a> IntoIterator for &'a MenuTree {
+    type Item = MenuNode<'a>;
+    type IntoIter = MenuTreeIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::MenuTree;
-    use crate::MenuTree::{EndNode, MiddleNode};
+    use crate::menutree::PathError;
+    use crate::{MenuNode, MenuTree, MenuTreeVisitor, NodeId};
     use petgraph::graph::NodeIndex;
     use std::fs::File;
+    use std::hash::{DefaultHasher, Hash, Hasher};
     use std::io::BufReader;
 
     pub fn get_test_tree() -> MenuTree {
-        MiddleNode {
-            name: String::from("Category1"),
-            children: vec![
-                MiddleNode {
-                    name: String::from("Category2"),
-                    children: vec![
-                        EndNode {
-                            name: String::from("Node1"),
-                            index: NodeIndex::new(0),
-                        },
-                        EndNode {
-                            name: String::from("Node2"),
-                            index: NodeIndex::new(1),
-                        },
-                    ],
-                },
-                EndNode {
-                    name: String::from("Node3"),
-                    index: NodeIndex::new(2),
-                },
-                EndNode {
-                    name: String::from("Node4"),
-                    index: NodeIndex::new(3),
-                },
-            ],
-        }
+        MenuTree::new_category("Category1")
+            .with_child(
+                MenuTree::new_category("Category2")
+                    .with_child(MenuTree::new_node("Node1", NodeId::new(0)))
+                    .with_child(MenuTree::new_node("Node2", NodeId::new(1))),
+            )
+            .with_child(MenuTree::new_node("Node3", NodeId::new(2)))
+            .with_child(MenuTree::new_node("Node4", NodeId::new(3)))
     }
 
     #[test]
@@ -175,12 +775,12 @@ pub mod tests {
     #[test]
     #[should_panic(expected = "MiddleNodes don't have indices")]
     fn test_get_index_panic() {
-        get_test_tree().index();
+        get_test_tree().root().index();
     }
 
     #[test]
     fn test_deserialize() {
-        let f = File::open("test_res/test.json").unwrap();
+        let f = File::open("test_res/legacy.json").unwrap();
         let f = BufReader::new(f);
         let json: serde_json::Value = serde_json::from_reader(f).unwrap();
         let json = json.get("menu_tree").unwrap();
@@ -190,20 +790,281 @@ pub mod tests {
     }
 
     #[test]
-    fn test_index() {
+    fn test_children() {
         let test_tree = get_test_tree();
 
-        let result = &test_tree["Category2"];
-        assert_eq!(result.name(), "Category2");
+        let category = test_tree.search("Category2").unwrap();
+        assert!(category.is_category());
+        let children: Vec<&str> = category.children().map(MenuNode::name).collect();
+        assert_eq!(children, ["Node1", "Node2"]);
+        assert_eq!(category.to_tree().names(), ["Category2", "Node1", "Node2"]);
+
+        let node = test_tree.search("Node1").unwrap();
+        assert!(!node.is_category());
+        assert_eq!(node.children().count(), 0);
+        assert_eq!(node.search("Node2").unwrap_err().cause_name(), "Node2");
+    }
+
+    #[test]
+    fn test_equality() {
+        let mut tree = get_test_tree();
+        tree.sort_children_by(|a, b| b.name().cmp(a.name()));
+        assert_ne!(tree, get_test_tree());
 
-        let result = &test_tree["Node1"];
-        assert_eq!(result.name(), "Node1");
-        assert_eq!(result.index().index(), 0);
+        // The nodes are stored in another order after sorting, which doesn't matter
+        tree.sort_children_by_name();
+        assert_eq!(tree, get_test_tree());
+        assert_eq!(tree.clone(), tree);
+        assert_eq!(format!("{tree:?}"), format!("{:?}", get_test_tree()));
     }
 
     #[test]
-    #[should_panic(expected = "There is no node with the name \"test\" in the tree")]
-    fn test_index_panic() {
-        let _ = &get_test_tree()["test"];
+    fn test_names() {
+        assert_eq!(
+            get_test_tree().names(),
+            vec!["Category1", "Category2", "Node1", "Node2", "Node3", "Node4"]
+        );
+    }
+
+    #[test]
+    fn test_iter() {
+        let tree = get_test_tree();
+        let names: Vec<&str> = tree.iter().map(MenuNode::name).collect();
+        assert_eq!(
+            names,
+            ["Category1", "Category2", "Node1", "Node2", "Node3", "Node4"]
+        );
+
+        let end_nodes = (&tree)
+            .into_iter()
+            .filter(|node| !node.is_category())
+            .count();
+        assert_eq!(end_nodes, 4);
+    }
+
+    #[test]
+    fn test_walk() {
+        struct Recorder(Vec<String>);
+
+        impl MenuTreeVisitor for Recorder {
+            fn enter_category(&mut self, name: &str) {
+                self.0.push(format!("enter {name}"));
+            }
+
+            fn leave_category(&mut self, name: &str) {
+                self.0.push(format!("leave {name}"));
+            }
+
+            fn visit_node(&mut self, name: &str) {
+                self.0.push(name.to_string());
+            }
+        }
+
+        let mut recorder = Recorder(vec![]);
+        get_test_tree().walk(&mut recorder);
+        assert_eq!(
+            recorder.0,
+            [
+                "enter Category1",
+                "enter Category2",
+                "Node1",
+                "Node2",
+                "leave Category2",
+                "Node3",
+                "Node4",
+                "leave Category1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut tree = MenuTree::new_node("Node", NodeId::new(0));
+        for depth in 0..2000 {
+            tree = MenuTree::new_category(&format!("Category{depth}")).with_child(tree);
+        }
+
+        assert_eq!(tree.search("Node").unwrap().id(), Some(NodeId::new(0)));
+        assert!(tree.search("Other").is_err());
+        assert_eq!(tree.end_nodes()[0].0.len(), 2000);
+        assert_eq!(tree.flatten()[2000], (2000, "Node", Some(NodeId::new(0))));
+
+        let filtered = tree.filter(|node| node.name() == "Node").unwrap();
+        assert_eq!(filtered.end_nodes()[0].0.len(), 2000);
+        assert!(tree.filter(|node| node.name() == "Other").is_none());
+
+        tree.sort_children_by_name();
+        tree.replace_index(NodeId::new(0), NodeId::new(1));
+        assert_eq!(tree.search("Node").unwrap().id(), Some(NodeId::new(1)));
+
+        // The path below the root down to the innermost category
+        let path: Vec<String> = (0..1999)
+            .rev()
+            .map(|depth| format!("Category{depth}"))
+            .collect();
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        let node = MenuTree::new_node("Other", NodeId::new(2));
+        tree.insert(&path, node.clone());
+        assert_eq!(tree.search("Category0").unwrap().iter().count(), 3);
+        assert_eq!(tree.remove_child("Other"), Some(node));
+        assert_eq!(
+            tree.remove_child("Node").unwrap().id(),
+            Some(NodeId::new(1))
+        );
+    }
+
+    #[test]
+    fn test_very_deep_tree() {
+        // Built with a single insertion, since every level copied by `with_child` is copied again
+        let path: Vec<String> = (1..100_000)
+            .map(|depth| format!("Category{depth}"))
+            .collect();
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        let mut tree = MenuTree::new_category("Category0");
+        tree.insert(&path, MenuTree::new_node("Node", NodeId::new(0)));
+
+        assert_eq!(tree.depth(), 100_000);
+        assert_eq!(tree.search("Node").unwrap().id(), Some(NodeId::new(0)));
+        let copy = tree.clone();
+        assert_eq!(copy, tree);
+        assert_ne!(copy, get_test_tree());
+        let hash = |tree: &MenuTree| {
+            let mut hasher = DefaultHasher::new();
+            tree.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&copy), hash(&tree));
+        assert!(format!("{copy:?}").contains("\"Category99999\""));
+        drop(copy);
+
+        let error = serde_json::to_string(&tree).unwrap_err();
+        assert!(error.to_string().contains("nested more than 32 levels"));
+        assert_eq!(tree.remove_child("Category1").unwrap().depth(), 99_999);
+        assert!(serde_json::to_string(&tree).is_ok());
+    }
+
+    #[test]
+    fn test_deserialize_depth() {
+        let nested = |depth| {
+            let mut json = serde_json::json!({"EndNode": {"name": "Node", "index": 0}});
+            for level in 0..depth {
+                json = serde_json::json!({
+                    "MiddleNode": {"name": format!("Category{level}"), "children": [json]}
+                });
+            }
+            json
+        };
+
+        let tree: MenuTree = serde_json::from_value(nested(32)).unwrap();
+        assert_eq!(tree.depth(), 32);
+        let error = serde_json::from_value::<MenuTree>(nested(33)).unwrap_err();
+        assert!(error.to_string().contains("nested more than 32 levels"));
+    }
+
+    #[test]
+    fn test_flatten() {
+        assert_eq!(
+            get_test_tree().flatten(),
+            [
+                (0, "Category1", None),
+                (1, "Category2", None),
+                (2, "Node1", Some(NodeId::new(0))),
+                (2, "Node2", Some(NodeId::new(1))),
+                (1, "Node3", Some(NodeId::new(2))),
+                (1, "Node4", Some(NodeId::new(3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let tree = get_test_tree();
+
+        let filtered = tree.filter(|node| node.name() == "Node2").unwrap();
+        assert_eq!(filtered.names(), ["Category1", "Category2", "Node2"]);
+
+        let filtered = tree.filter(|node| node.name() != "Node3").unwrap();
+        assert_eq!(
+            filtered.names(),
+            ["Category1", "Category2", "Node1", "Node2", "Node4"]
+        );
+
+        assert_eq!(tree.filter(|_| false), None);
+        assert_eq!(tree.filter(|_| true), Some(tree));
+    }
+
+    #[test]
+    fn test_sort_children() {
+        let mut tree = get_test_tree();
+        tree.sort_children_by(|a, b| b.name().cmp(a.name()));
+        assert_eq!(
+            tree.names(),
+            ["Category1", "Node4", "Node3", "Category2", "Node2", "Node1"]
+        );
+
+        tree.sort_children_by_name();
+        assert_eq!(tree, get_test_tree());
+    }
+
+    #[test]
+    fn test_node_id() {
+        let id = NodeId::new(3);
+        assert_eq!(id.index(), 3);
+        assert_eq!(NodeId::from(NodeIndex::from(id)), id);
+        assert_eq!(serde_json::to_string(&id).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut tree = MenuTree::new_category("Category1");
+        let node = |name, index| MenuTree::new_node(name, NodeId::new(index));
+
+        tree.insert(&["Category2"], node("Node1", 0));
+        tree.insert(&["Category2"], node("Node2", 1));
+        tree.insert(&[], node("Node3", 2));
+        tree.insert(&[], node("Node4", 3));
+
+        assert_eq!(tree, get_test_tree());
+    }
+
+    #[test]
+    fn test_insert_at_path() {
+        let mut tree = None;
+        let node = |name, index| MenuTree::new_node(name, NodeId::new(index));
+
+        MenuTree::insert_at_path(&mut tree, "Category1/Category2", node("Node1", 0)).unwrap();
+        MenuTree::insert_at_path(&mut tree, "Category1 / Category2/", node("Node2", 1)).unwrap();
+        MenuTree::insert_at_path(&mut tree, "Category1", node("Node3", 2)).unwrap();
+        MenuTree::insert_at_path(&mut tree, "Category1", node("Node4", 3)).unwrap();
+        assert_eq!(tree, Some(get_test_tree()));
+
+        assert_eq!(
+            MenuTree::insert_at_path(&mut tree, "", node("Node5", 4)),
+            Err(PathError::Empty)
+        );
+        assert_eq!(
+            MenuTree::insert_at_path(&mut tree, "Category2", node("Node5", 4)),
+            Err(PathError::DifferentRoot(String::from("Category1")))
+        );
+    }
+
+    #[test]
+    fn test_end_nodes() {
+        let test_tree = get_test_tree();
+        let end_nodes: Vec<(Vec<&str>, &str)> = test_tree
+            .end_nodes()
+            .into_iter()
+            .map(|(path, node)| (path, node.name()))
+            .collect();
+
+        assert_eq!(
+            end_nodes,
+            vec![
+                (vec!["Category1", "Category2"], "Node1"),
+                (vec!["Category1", "Category2"], "Node2"),
+                (vec!["Category1"], "Node3"),
+                (vec!["Category1"], "Node4"),
+            ]
+        );
     }
 }
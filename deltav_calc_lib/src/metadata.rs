@@ -0,0 +1,103 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Additional information about a [`DeltavMap`](crate::DeltavMap)
+///
+/// # Deserialization
+/// All fields of the metadata are optional:
+/// ```json
+/// {
+///   "title": "Stock Kerbol System",
+///   "author": "Jane Doe",
+///   "source_url": "https://example.com/deltav_map",
+///   "game_version": "1.12.5",
+///   "license": "CC-BY-4.0",
+///   "last_updated": "2023-01-15",
+///   "content_hash": "6c62272e07bb0142",
+///   "margin_profiles": {
+///     "heavy_lander": 30
+///   }
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Metadata {
+    #[serde(default)]
+    pub(crate) title: Option<String>,
+    #[serde(default)]
+    pub(crate) author: Option<String>,
+    #[serde(default)]
+    pub(crate) source_url: Option<String>,
+    #[serde(default)]
+    pub(crate) game_version: Option<String>,
+    #[serde(default)]
+    pub(crate) license: Option<String>,
+    #[serde(default)]
+    pub(crate) last_updated: Option<String>,
+    #[serde(default)]
+    pub(crate) content_hash: Option<String>,
+    #[serde(default)]
+    pub(crate) margin_profiles: BTreeMap<String, u32>,
+}
+
+impl Metadata {
+    /// The title of the map
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The author of the map
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// The URL the map or its data was taken from
+    pub fn source_url(&self) -> Option<&str> {
+        self.source_url.as_deref()
+    }
+
+    /// The version of the game the data of the map is meant for, like `1.12.5`
+    pub fn game_version(&self) -> Option<&str> {
+        self.game_version.as_deref()
+    }
+
+    /// The license the map is published under
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// When the map was last updated, as written in the file
+    pub fn last_updated(&self) -> Option<&str> {
+        self.last_updated.as_deref()
+    }
+
+    /// The hash of the content the map was saved with, as hexadecimal number. If it is present,
+    /// it is checked against the [`content_hash`](crate::DeltavMap::content_hash) when the map
+    /// is validated
+    pub fn content_hash(&self) -> Option<&str> {
+        self.content_hash.as_deref()
+    }
+
+    /// The custom margin profiles defined by the map, as names and margins in percent
+    pub fn margin_profiles(&self) -> &BTreeMap<String, u32> {
+        &self.margin_profiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Metadata;
+
+    #[test]
+    fn test_deserialize() {
+        let json = r#"{"title": "Test", "game_version": "1.12.5", "license": "MIT"}"#;
+        let metadata: Metadata = serde_json::from_str(json).unwrap();
+
+        assert_eq!(metadata.title(), Some("Test"));
+        assert_eq!(metadata.author(), None);
+        assert_eq!(metadata.game_version(), Some("1.12.5"));
+        assert_eq!(metadata.license(), Some("MIT"));
+        assert!(metadata.margin_profiles().is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, NodePayload, ReturnTrip, Route};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// The deltav requirements of flying to a destination and returning home, including safety margins
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoundTripReport {
+    pub(crate) trip: ReturnTrip,
+    pub(crate) options: CalcOptions,
+}
+
+impl RoundTripReport {
+    /// The route from home to the destination
+    pub fn outbound(&self) -> &Route {
+        self.trip.outbound()
+    }
+
+    /// The route from the destination back home
+    pub fn inbound(&self) -> &Route {
+        self.trip.inbound()
+    }
+
+    /// The safety margin in percent that was added to both directions
+    pub fn margin_percent(&self) -> u32 {
+        self.options.margin_percent()
+    }
+
+    /// The deltav required for both directions without any margin
+    pub fn delta_v(&self) -> i64 {
+        self.trip.delta_v()
+    }
+
+    /// The deltav to budget for the way to the destination, including the margin
+    pub fn outbound_budget(&self) -> i64 {
+        self.options.apply_margin(self.outbound().delta_v())
+    }
+
+    /// The deltav to budget for the way back home, including the margin
+    pub fn inbound_budget(&self) -> i64 {
+        self.options.apply_margin(self.inbound().delta_v())
+    }
+
+    /// The deltav to budget for the whole trip, including the margin
+    pub fn total_budget(&self) -> i64 {
+        self.outbound_budget() + self.inbound_budget()
+    }
+}
+
+impl Display for RoundTripReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "{} -> {}: {} m/s",
+            self.outbound().start(),
+            self.outbound().end(),
+            self.outbound_budget()
+        )?;
+        writeln!(
+            f,
+            "{} -> {}: {} m/s",
+            self.inbound().start(),
+            self.inbound().end(),
+            self.inbound_budget()
+        )?;
+        write!(
+            f,
+            "Total: {} m/s (including a {}% margin)",
+            self.total_budget(),
+            self.margin_percent()
+        )
+    }
+}
+
+/// This error is raised when a [`MissionPlan`] can't be evaluated against a map
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum MissionError {
+    /// One of the nodes of the plan doesn't exist in the map
+    NoSuchNode(NoSuchNodeError),
+
+    /// The body that should be landed on has no node tagged with `surface`
+    NoSurface(String),
+
+    /// There is no route between the two nodes
+    NoRoute { from: String, to: String },
+}
+
+impl Display for MissionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MissionError::NoSuchNode(e) => write!(f, "{e}"),
+            MissionError::NoSurface(body) => write!(f, "\"{body}\" has no surface to land on"),
+            MissionError::NoRoute { from, to } => {
+                write!(f, "There is no route from \"{from}\" to \"{to}\"")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MissionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MissionError::NoSuchNode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<NoSuchNodeError> for MissionError {
+    fn from(e: NoSuchNodeError) -> Self {
+        MissionError::NoSuchNode(e)
+    }
+}
+
+/// A single step of a [`MissionPlan`]
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Objective {
+    /// Fly to the node with the given name
+    Reach(String),
+
+    /// Land on the given body. This can either be the name of a surface node or the name of the
+    /// category of a body, in which case its child tagged with `surface` is used
+    Land(String),
+
+    /// Return to the start of the mission
+    Return,
+}
+
+/// A mission made up of multiple objectives that are flown one after another
+///
+/// # Example
+/// ```
+/// use deltav_calc::{CalcOptions, DeltavMap, MissionPlan};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let plan = MissionPlan::new("Kerbin Surface")
+///     .reach("Low Mun Orbit (14km)")
+///     .land("Minmus")
+///     .return_to_start();
+/// let deltav = plan.delta_v(&stock_map, &CalcOptions::new());
+/// ```
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MissionPlan {
+    start: String,
+    objectives: Vec<Objective>,
+}
+
+impl MissionPlan {
+    /// Creates a plan without objectives starting at the given node
+    pub fn new(start: &str) -> MissionPlan {
+        MissionPlan {
+            start: start.to_string(),
+            objectives: Vec::new(),
+        }
+    }
+
+    /// Adds an objective to the end of the plan
+    pub fn objective(mut self, objective: Objective) -> MissionPlan {
+        self.objectives.push(objective);
+        self
+    }
+
+    /// Adds an objective to fly to the given node
+    pub fn reach(self, node: &str) -> MissionPlan {
+        self.objective(Objective::Reach(node.to_string()))
+    }
+
+    /// Adds an objective to land on the given body
+    pub fn land(self, body: &str) -> MissionPlan {
+        self.objective(Objective::Land(body.to_string()))
+    }
+
+    /// Adds an objective to return to the start
+    pub fn return_to_start(self) -> MissionPlan {
+        self.objective(Objective::Return)
+    }
+
+    /// The name of the node the mission starts at
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    /// The objectives in the order they are flown
+    pub fn objectives(&self) -> &[Objective] {
+        &self.objectives
+    }
+
+    /// Calculates the routes between the objectives of the plan
+    ///
+    /// Returns a [`MissionError`] if an objective can't be found or reached
+    pub fn evaluate<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        options: &CalcOptions,
+    ) -> Result<Vec<Route>, MissionError> {
+        let mut position = map.menu_tree().search(&self.start)?.name().to_string();
+        let mut routes = Vec::new();
+
+        for objective in &self.objectives {
+            let target = match objective {
+                Objective::Reach(node) => node.clone(),
+                Objective::Land(body) => surface(map, body)?,
+                Objective::Return => self.start.clone(),
+            };
+
+            match map.calculate_route(&position, &target, options)? {
+                None => {
+                    return Err(MissionError::NoRoute {
+                        from: position,
+                        to: target,
+                    })
+                }
+                Some(route) => routes.push(route),
+            }
+
+            position = target;
+        }
+
+        Ok(routes)
+    }
+
+    /// Calculates the deltav required for the whole mission, including the margin of the options
+    ///
+    /// Returns a [`MissionError`] if an objective can't be found or reached
+    pub fn delta_v<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        options: &CalcOptions,
+    ) -> Result<i64, MissionError> {
+        let routes = self.evaluate(map, options)?;
+
+        Ok(options.apply_margin(routes.iter().map(Route::delta_v).sum()))
+    }
+}
+
+// Finds the node to land on for the given body
+fn surface<N: NodePayload>(map: &DeltavMap<N>, body: &str) -> Result<String, MissionError> {
+    let node = map.menu_tree().search(body)?;
+    if !node.is_category() {
+        return Ok(node.name().to_string());
+    }
+
+    node.children()
+        .filter(|child| !child.is_category())
+        .find(|child| map.tags(child.name()).unwrap().contains(&"surface"))
+        .map(|child| child.name().to_string())
+        .ok_or_else(|| MissionError::NoSurface(body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{CalcOptions, DeltavMap, MissionError, MissionPlan};
+
+    fn get_test_plan() -> MissionPlan {
+        MissionPlan::new("Kerbin Surface")
+            .reach("Low Kerbin Orbit (80km)")
+            .land("Mun")
+            .return_to_start()
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let stock_map = DeltavMap::new_stock();
+        let routes = get_test_plan()
+            .evaluate(&stock_map, &CalcOptions::new())
+            .unwrap();
+
+        assert_eq!(routes.len(), 3);
+        assert_eq!(routes[0].delta_v(), 3400);
+        assert_eq!(routes[1].end(), "Mun Surface");
+        assert_eq!(routes[1].delta_v(), 1720);
+        assert_eq!(routes[2].delta_v(), 5120);
+
+        let deltav = get_test_plan()
+            .delta_v(&stock_map, &CalcOptions::new().margin(10))
+            .unwrap();
+        assert_eq!(deltav, 11264);
+    }
+
+    #[test]
+    fn test_no_surface() {
+        let result = MissionPlan::new("Node1")
+            .land("Category2")
+            .evaluate(&get_test_map(), &CalcOptions::new());
+
+        assert_eq!(
+            result,
+            Err(MissionError::NoSurface(String::from("Category2")))
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(get_test_plan()).unwrap();
+        let deserialized: MissionPlan = serde_json::from_value(json).unwrap();
+
+        assert_eq!(deserialized, get_test_plan());
+    }
+}
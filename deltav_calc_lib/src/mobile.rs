@@ -0,0 +1,198 @@
+//! Bindings for Kotlin and Swift generated with UniFFI
+//!
+//! The bindings are generated from the compiled library with `uniffi-bindgen generate --library`.
+//! The map is exported as the [`MobileMap`] object, the menu tree as a flat list of
+//! [`MenuEntry`] records and routes as [`RouteSummary`] records
+
+use crate::{CalcOptions, DeltavMap, LoadError, NoSuchNodeError, Route};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// This error is raised by the mobile bindings
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MobileError {
+    /// The map couldn't be loaded
+    Load(LoadError),
+
+    /// A node doesn't exist
+    NoSuchNode(NoSuchNodeError),
+}
+
+impl Display for MobileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MobileError::Load(e) => write!(f, "{e}"),
+            MobileError::NoSuchNode(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for MobileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MobileError::Load(e) => Some(e),
+            MobileError::NoSuchNode(e) => Some(e),
+        }
+    }
+}
+
+impl From<LoadError> for MobileError {
+    fn from(e: LoadError) -> Self {
+        MobileError::Load(e)
+    }
+}
+
+impl From<NoSuchNodeError> for MobileError {
+    fn from(e: NoSuchNodeError) -> Self {
+        MobileError::NoSuchNode(e)
+    }
+}
+
+/// A category or node of the menu tree together with its depth, which is 0 for the root
+#[derive(Debug, Clone, Eq, PartialEq, uniffi::Record)]
+pub struct MenuEntry {
+    pub depth: u32,
+    pub name: String,
+    pub is_category: bool,
+}
+
+/// A single maneuver of a [`RouteSummary`]
+#[derive(Debug, Clone, Eq, PartialEq, uniffi::Record)]
+pub struct LegSummary {
+    pub from: String,
+    pub to: String,
+    pub delta_v: i32,
+    pub aerobrake: bool,
+    pub travel_time: Option<u64>,
+}
+
+/// The cheapest path between two nodes
+#[derive(Debug, Clone, Eq, PartialEq, uniffi::Record)]
+pub struct RouteSummary {
+    pub nodes: Vec<String>,
+    pub legs: Vec<LegSummary>,
+    pub delta_v: i64,
+    pub travel_time: u64,
+}
+
+impl From<&Route> for RouteSummary {
+    fn from(route: &Route) -> Self {
+        RouteSummary {
+            nodes: route.nodes().into_iter().map(String::from).collect(),
+            legs: route
+                .legs()
+                .iter()
+                .map(|leg| LegSummary {
+                    from: leg.from().to_string(),
+                    to: leg.to().to_string(),
+                    delta_v: leg.delta_v(),
+                    aerobrake: leg.aerobrake(),
+                    travel_time: leg.travel_time(),
+                })
+                .collect(),
+            delta_v: route.delta_v(),
+            travel_time: route.travel_time(),
+        }
+    }
+}
+
+/// A delta-v map that can be used from Kotlin and Swift
+#[derive(Debug, uniffi::Object)]
+pub struct MobileMap {
+    map: DeltavMap,
+}
+
+#[uniffi::export]
+impl MobileMap {
+    /// Returns the stock map
+    #[uniffi::constructor]
+    pub fn stock() -> Arc<MobileMap> {
+        Arc::new(MobileMap {
+            map: DeltavMap::new_stock(),
+        })
+    }
+
+    /// Reads a map from the text of a JSON file
+    #[uniffi::constructor]
+    pub fn from_json(text: String) -> Result<Arc<MobileMap>, MobileError> {
+        Ok(Arc::new(MobileMap {
+            map: DeltavMap::from_reader(text.as_bytes())?,
+        }))
+    }
+
+    /// Lists the categories and nodes of the menu tree in document order
+    pub fn menu_entries(&self) -> Vec<MenuEntry> {
+        self.map
+            .menu_tree()
+            .flatten()
+            .into_iter()
+            .map(|(depth, name, id)| MenuEntry {
+                depth: depth as u32,
+                name: name.to_string(),
+                is_category: id.is_none(),
+            })
+            .collect()
+    }
+
+    /// Calculates the delta-v required to get from the start to the end. Returns `None` if there
+    /// is no path between the nodes
+    pub fn calculate_delta_v(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Option<i64>, MobileError> {
+        Ok(self.map.calculate_delta_v(&start, &end)?)
+    }
+
+    /// Calculates the cheapest route from the start to the end. Returns `None` if there is no
+    /// path between the nodes
+    pub fn calculate_route(
+        &self,
+        start: String,
+        end: String,
+    ) -> Result<Option<RouteSummary>, MobileError> {
+        let route = self
+            .map
+            .calculate_route(&start, &end, &CalcOptions::default())?;
+
+        Ok(route.as_ref().map(RouteSummary::from))
+    }
+}
+
+impl From<DeltavMap> for MobileMap {
+    fn from(map: DeltavMap) -> Self {
+        MobileMap { map }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MenuEntry, MobileError, MobileMap};
+    use crate::tests::get_test_map;
+
+    #[test]
+    fn test_mobile_map() {
+        let map = MobileMap::from(get_test_map());
+
+        assert_eq!(
+            map.menu_entries()[2],
+            MenuEntry {
+                depth: 2,
+                name: String::from("Node1"),
+                is_category: false,
+            }
+        );
+
+        let route = map
+            .calculate_route(String::from("Node1"), String::from("Node2"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.nodes, vec!["Node1", "Node2"]);
+        assert_eq!(route.delta_v, 900);
+
+        let error = map.calculate_delta_v(String::from("Node1"), String::from("Node5"));
+        assert!(matches!(error, Err(MobileError::NoSuchNode(_))));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{Leg, MenuTree, Metadata, Route, RouteWarning, WarningKind};
+use std::collections::BTreeMap;
+use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::{Ref, RefOr};
+use utoipa::{PartialSchema, ToSchema};
+
+// The schemas are written by hand to match the serialized form, like the other integrations they
+// are kept out of the type definitions
+
+// Refers to the component of the type
+fn reference<T: ToSchema>() -> RefOr<Schema> {
+    Ref::from_schema_name(T::name()).into()
+}
+
+// A list of the type, referring to its component
+fn list_of<T: ToSchema>() -> RefOr<Schema> {
+    ArrayBuilder::new().items(reference::<T>()).into()
+}
+
+// Adds the type and the components it refers to
+fn add_component<T: ToSchema>(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+    schemas.push((T::name().into_owned(), T::schema()));
+    T::schemas(schemas);
+}
+
+impl PartialSchema for Route {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .description(Some(
+                "The cheapest path between two nodes, broken down into its legs",
+            ))
+            .property("start", String::schema())
+            .required("start")
+            .property("legs", list_of::<Leg>())
+            .required("legs")
+            .property("warnings", list_of::<RouteWarning>())
+            .required("warnings")
+            .into()
+    }
+}
+
+impl ToSchema for Route {
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        add_component::<Leg>(schemas);
+        add_component::<RouteWarning>(schemas);
+    }
+}
+
+impl PartialSchema for Leg {
+    fn schema() -> RefOr<Schema> {
+        let mut object = ObjectBuilder::new()
+            .description(Some("A single maneuver between two neighbouring nodes"));
+        for name in ["from", "to"] {
+            object = object.property(name, String::schema()).required(name);
+        }
+        for name in ["delta_v", "min_delta_v", "max_delta_v"] {
+            object = object.property(name, i32::schema()).required(name);
+        }
+
+        object
+            .property("aerobrake", bool::schema())
+            .required("aerobrake")
+            .property("time", Option::<u64>::schema())
+            .into()
+    }
+}
+
+impl ToSchema for Leg {}
+
+impl PartialSchema for RouteWarning {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .description(Some("A hazard encountered while flying a leg"))
+            .property("kind", reference::<WarningKind>())
+            .required("kind")
+            .property("leg", usize::schema())
+            .required("leg")
+            .into()
+    }
+}
+
+impl ToSchema for RouteWarning {
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        add_component::<WarningKind>(schemas);
+    }
+}
+
+impl PartialSchema for WarningKind {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .enum_values(Some(WarningKind::ALL.map(|kind| format!("{kind:?}"))))
+            .into()
+    }
+}
+
+impl ToSchema for WarningKind {}
+
+impl PartialSchema for MenuTree {
+    fn schema() -> RefOr<Schema> {
+        let category = ObjectBuilder::new()
+            .property("name", String::schema())
+            .required("name")
+            .property("children", list_of::<MenuTree>())
+            .required("children");
+        let node = ObjectBuilder::new()
+            .property("name", String::schema())
+            .required("name")
+            .property("index", u32::schema())
+            .required("index");
+
+        OneOfBuilder::new()
+            .description(Some(
+                "A category with the nodes and categories in it, or a node",
+            ))
+            .item(
+                ObjectBuilder::new()
+                    .property("MiddleNode", category)
+                    .required("MiddleNode"),
+            )
+            .item(
+                ObjectBuilder::new()
+                    .property("EndNode", node)
+                    .required("EndNode"),
+            )
+            .into()
+    }
+}
+
+impl ToSchema for MenuTree {}
+
+impl PartialSchema for Metadata {
+    fn schema() -> RefOr<Schema> {
+        let mut object =
+            ObjectBuilder::new().description(Some("Additional information about a map"));
+        for name in [
+            "title",
+            "author",
+            "source_url",
+            "game_version",
+            "license",
+            "last_updated",
+            "content_hash",
+        ] {
+            object = object.property(name, Option::<String>::schema());
+        }
+
+        object
+            .property("margin_profiles", BTreeMap::<String, u32>::schema())
+            .into()
+    }
+}
+
+impl ToSchema for Metadata {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        CalcOptions, DeltavMap, Leg, MenuTree, Metadata, Route, RouteWarning, WarningKind,
+    };
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+    use utoipa::{PartialSchema, ToSchema};
+
+    // Returns the names of the properties of an object schema
+    fn properties<T: PartialSchema>() -> BTreeSet<String> {
+        let schema = serde_json::to_value(T::schema()).unwrap();
+        schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    // Returns the keys of the serialized value
+    fn keys(value: &Value) -> BTreeSet<String> {
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+
+    #[test]
+    fn test_schemas_match_serialization() {
+        let map = DeltavMap::new_stock();
+        let route = map
+            .calculate_route("Kerbin Surface", "Mun Surface", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+        let value = serde_json::to_value(&route).unwrap();
+
+        assert_eq!(properties::<Route>(), keys(&value));
+        assert_eq!(properties::<Leg>(), keys(&value["legs"][0]));
+        let warning = RouteWarning {
+            kind: WarningKind::HighGravity,
+            leg: 0,
+        };
+        assert_eq!(
+            properties::<RouteWarning>(),
+            keys(&serde_json::to_value(warning).unwrap())
+        );
+        assert_eq!(
+            properties::<Metadata>(),
+            keys(&serde_json::to_value(Metadata::default()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_components() {
+        let mut schemas = Vec::new();
+        Route::schemas(&mut schemas);
+        let names: Vec<&str> = schemas.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Leg", "RouteWarning", "WarningKind"]);
+
+        let tree = serde_json::to_value(MenuTree::schema()).unwrap();
+        assert_eq!(
+            tree["oneOf"][0]["properties"]["MiddleNode"]["properties"]["children"]["items"]["$ref"],
+            "#/components/schemas/MenuTree"
+        );
+    }
+}
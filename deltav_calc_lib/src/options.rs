@@ -0,0 +1,326 @@
+use crate::math;
+use crate::MarginProfile;
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+
+/// Which cost of edges with a range of costs is used
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Default, Debug)]
+pub enum CostMode {
+    /// The nominal cost of the edges
+    #[default]
+    Nominal,
+
+    /// The best case cost of the edges
+    Optimistic,
+
+    /// The worst case cost of the edges
+    Pessimistic,
+
+    /// The cost in the middle between the best and the worst case
+    Midpoint,
+}
+
+/// The length of a day in seconds
+pub const DAY: u64 = 6 * 60 * 60;
+
+/// The smallest factor costs can be scaled by, smaller factors are raised to it
+pub const MIN_SCALE: f64 = 0.01;
+
+// Multiplies a cost by a scale factor. The factor is at least MIN_SCALE, or 1 if it is NaN, and
+// positive costs stay at least 1 m/s, so scaled costs can't turn negative and no leg becomes free
+pub(crate) fn scale_cost(cost: i32, factor: f64, rounding: Rounding) -> i32 {
+    let factor = if factor.is_nan() {
+        1.0
+    } else {
+        factor.max(MIN_SCALE)
+    };
+    let scaled = rounding.round(cost as f64 * factor) as i32;
+
+    if cost > 0 {
+        scaled.max(1)
+    } else {
+        scaled
+    }
+}
+
+/// What the route calculation minimizes
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Default, Debug)]
+pub enum RoutingMode {
+    /// The deltav of the route
+    #[default]
+    DeltaV,
+
+    /// The travel time of the route. Edges without a travel time count as instantaneous
+    Time,
+
+    /// The deltav plus the travel time weighted by the given deltav per day, so a route is allowed
+    /// to cost that much more deltav for every day it saves
+    Weighted(u32),
+}
+
+/// How results that aren't whole numbers are rounded, for example after adding a margin or
+/// scaling the costs
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Rounding {
+    /// To the nearest whole m/s, halves are rounded away from zero
+    Nearest,
+
+    /// To the nearest multiple of 10 m/s, like most community charts
+    NearestTen,
+
+    /// Up to the next whole m/s
+    Ceiling,
+}
+
+impl Rounding {
+    /// Rounds the value to a whole number according to the policy
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Nearest => math::round(value),
+            Rounding::NearestTen => math::round(value / 10.0) * 10.0,
+            Rounding::Ceiling => math::ceil(value),
+        }
+    }
+
+    // Divides the numbers exactly and rounds the result according to the policy. The denominator
+    // has to be positive
+    fn divide(self, numerator: i128, denominator: i128) -> i128 {
+        match self {
+            Rounding::Nearest => {
+                let half_up = (2 * numerator.abs() + denominator) / (2 * denominator);
+                half_up * numerator.signum()
+            }
+            Rounding::NearestTen => Rounding::Nearest.divide(numerator, denominator * 10) * 10,
+            Rounding::Ceiling => -(-numerator).div_euclid(denominator),
+        }
+    }
+}
+
+/// Options influencing how routes are calculated
+///
+/// # Example
+/// ```
+/// use deltav_calc::{CalcOptions, DeltavMap};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let options = CalcOptions::new().avoid_tag("atmosphere");
+/// let route = stock_map.calculate_route("Low Kerbin Orbit (80km)", "Mun Surface", &options);
+/// ```
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct CalcOptions {
+    pub(crate) avoided_tags: BTreeSet<String>,
+    pub(crate) avoided_nodes: BTreeSet<String>,
+    pub(crate) aerobrake: bool,
+    pub(crate) margin: u32,
+    pub(crate) cost_mode: CostMode,
+    pub(crate) routing_mode: RoutingMode,
+    pub(crate) gravity_assists: bool,
+    pub(crate) scale: Option<f64>,
+    pub(crate) low_thrust: bool,
+    pub(crate) max_legs: Option<usize>,
+    pub(crate) rounding: Option<Rounding>,
+}
+
+impl CalcOptions {
+    /// Creates the default options
+    pub fn new() -> CalcOptions {
+        CalcOptions::default()
+    }
+
+    /// Excludes all nodes carrying the given tag from the route.
+    ///
+    /// The start and the end of the route are always allowed, even if they carry the tag
+    pub fn avoid_tag(mut self, tag: &str) -> CalcOptions {
+        self.avoided_tags.insert(tag.to_string());
+        self
+    }
+
+    /// Excludes the node from the route, for example a station that isn't built yet.
+    ///
+    /// The start and the end of the route are always allowed, even if they are avoided
+    pub fn avoid_node(mut self, node: &str) -> CalcOptions {
+        self.avoided_nodes.insert(node.to_string());
+        self
+    }
+
+    /// Sets if the craft can use aerobraking to reduce the cost of entering an atmosphere
+    pub fn aerobrake(mut self, aerobrake: bool) -> CalcOptions {
+        self.aerobrake = aerobrake;
+        self
+    }
+
+    /// Checks if the route may use aerobraking
+    pub fn uses_aerobrake(&self) -> bool {
+        self.aerobrake
+    }
+
+    /// Sets which cost of edges with a range of costs is used
+    pub fn cost_mode(mut self, mode: CostMode) -> CalcOptions {
+        self.cost_mode = mode;
+        self
+    }
+
+    /// Sets what the route calculation minimizes
+    pub fn routing_mode(mut self, mode: RoutingMode) -> CalcOptions {
+        self.routing_mode = mode;
+        self
+    }
+
+    /// Sets if edges that require a gravity assist may be used
+    pub fn gravity_assists(mut self, gravity_assists: bool) -> CalcOptions {
+        self.gravity_assists = gravity_assists;
+        self
+    }
+
+    /// Sets if the craft has a low thrust to weight ratio, which makes edges with a low thrust
+    /// factor more expensive
+    pub fn low_thrust(mut self, low_thrust: bool) -> CalcOptions {
+        self.low_thrust = low_thrust;
+        self
+    }
+
+    /// Limits the route to the given number of legs, for simpler flight plans. The cheapest route
+    /// within the limit is used, even if a longer route would be cheaper
+    pub fn max_legs(mut self, legs: usize) -> CalcOptions {
+        self.max_legs = Some(legs);
+        self
+    }
+
+    /// Multiplies the costs of all edges by the factor, for example for rescaled systems.
+    ///
+    /// Factors below [`MIN_SCALE`] are raised to it and NaN leaves the costs unchanged. Positive
+    /// costs stay at least 1 m/s, so the scaled costs are still usable for finding routes
+    pub fn scale(mut self, factor: f64) -> CalcOptions {
+        self.scale = Some(factor);
+        self
+    }
+
+    /// Multiplies the deltav by the scale factor
+    pub fn apply_scale(&self, delta_v: i32) -> i32 {
+        match self.scale {
+            None => delta_v,
+            Some(factor) => scale_cost(delta_v, factor, self.rounding.unwrap_or(Rounding::Nearest)),
+        }
+    }
+
+    /// Sets how scaled costs and totals with a margin are rounded. By default scaled costs are
+    /// rounded to the nearest m/s and margins are rounded up
+    pub fn rounding(mut self, rounding: Rounding) -> CalcOptions {
+        self.rounding = Some(rounding);
+        self
+    }
+
+    // Rounds a cost that was multiplied by a factor
+    pub(crate) fn round_cost(&self, cost: f64) -> i32 {
+        self.rounding.unwrap_or(Rounding::Nearest).round(cost) as i32
+    }
+
+    /// Sets the safety margin in percent that is added on top of calculated totals
+    pub fn margin(mut self, percent: u32) -> CalcOptions {
+        self.margin = percent;
+        self
+    }
+
+    /// Uses the margin of the given profile
+    pub fn margin_profile(self, profile: &MarginProfile) -> CalcOptions {
+        self.margin(profile.percent())
+    }
+
+    /// The safety margin in percent
+    pub fn margin_percent(&self) -> u32 {
+        self.margin
+    }
+
+    /// Adds the safety margin to the deltav. The result is rounded up unless a different rounding
+    /// is set
+    pub fn apply_margin(&self, delta_v: i64) -> i64 {
+        if self.margin == 0 {
+            return delta_v;
+        }
+
+        let rounding = self.rounding.unwrap_or(Rounding::Ceiling);
+        let total = rounding.divide(delta_v as i128 * (100 + self.margin as i128), 100);
+        total.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// The tags whose nodes are excluded from the route
+    pub fn avoided_tags(&self) -> impl Iterator<Item = &str> {
+        self.avoided_tags.iter().map(String::as_str)
+    }
+
+    /// The nodes that are excluded from the route
+    pub fn avoided_nodes(&self) -> impl Iterator<Item = &str> {
+        self.avoided_nodes.iter().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CalcOptions, Rounding};
+
+    #[test]
+    fn test_margin() {
+        assert_eq!(CalcOptions::new().apply_margin(3400), 3400);
+        assert_eq!(CalcOptions::new().margin(10).apply_margin(3400), 3740);
+        assert_eq!(CalcOptions::new().margin(10).apply_margin(3401), 3742);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(CalcOptions::new().apply_scale(3400), 3400);
+        assert_eq!(CalcOptions::new().scale(2.5).apply_scale(3400), 8500);
+
+        // Factors that would make the costs negative, free or NaN are clamped
+        assert_eq!(CalcOptions::new().scale(-2.0).apply_scale(3400), 34);
+        assert_eq!(CalcOptions::new().scale(0.0).apply_scale(50), 1);
+        assert_eq!(CalcOptions::new().scale(f64::NAN).apply_scale(3400), 3400);
+        assert_eq!(CalcOptions::new().scale(0.0).apply_scale(0), 0);
+    }
+
+    #[test]
+    fn test_rounding() {
+        let options = CalcOptions::new().margin(10);
+        assert_eq!(
+            options
+                .clone()
+                .rounding(Rounding::Nearest)
+                .apply_margin(3401),
+            3741
+        );
+        assert_eq!(
+            options.clone().rounding(Rounding::Nearest).apply_margin(-5),
+            -6
+        );
+        assert_eq!(
+            options
+                .clone()
+                .rounding(Rounding::NearestTen)
+                .apply_margin(3401),
+            3740
+        );
+        assert_eq!(
+            options
+                .clone()
+                .rounding(Rounding::Ceiling)
+                .apply_margin(-3401),
+            -3741
+        );
+
+        let scaled = CalcOptions::new().scale(1.1);
+        assert_eq!(scaled.apply_scale(3401), 3741);
+        assert_eq!(
+            scaled.clone().rounding(Rounding::Ceiling).apply_scale(3401),
+            3742
+        );
+        assert_eq!(
+            scaled.rounding(Rounding::NearestTen).apply_scale(3401),
+            3740
+        );
+        assert_eq!(
+            CalcOptions::new()
+                .rounding(Rounding::NearestTen)
+                .apply_scale(3401),
+            3401
+        );
+    }
+}
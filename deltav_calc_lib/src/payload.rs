@@ -0,0 +1,119 @@
+use crate::{DeltavMap, NoSuchNodeError};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// Data stored in the nodes of a [`DeltavMap`]. Every payload knows the name of its node, which
+/// is used to look it up
+///
+/// # Example
+/// ```
+/// use deltav_calc::{DeltavMap, NodePayload};
+///
+/// struct Body {
+///     name: String,
+///     wiki: String,
+/// }
+///
+/// impl NodePayload for Body {
+///     fn name(&self) -> &str {
+///         &self.name
+///     }
+/// }
+///
+/// let map = DeltavMap::new_stock().map_payloads(|name| Body {
+///     wiki: format!("https://wiki.kerbalspaceprogram.com/wiki/{name}"),
+///     name: name.to_string(),
+/// });
+/// let body = map.payload("Mun Surface").unwrap();
+/// ```
+pub trait NodePayload {
+    /// The name of the node
+    fn name(&self) -> &str;
+}
+
+impl NodePayload for String {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl NodePayload for Arc<str> {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Returns the payload of the node with the given name
+    ///
+    /// Returns a [`NoSuchNodeError`] if there is no node with that name or if it is a category
+    pub fn payload(&self, node: &str) -> Result<&N, NoSuchNodeError> {
+        match self.menu_tree.search(node)?.id() {
+            Some(id) => Ok(&self.graph[NodeIndex::from(id)]),
+            None => Err(NoSuchNodeError {
+                name: node.to_string(),
+            }),
+        }
+    }
+
+    /// Replaces the payloads of all nodes. The new payloads have to keep the names of the nodes,
+    /// otherwise [`validate`](DeltavMap::validate) fails
+    pub fn map_payloads<M: NodePayload>(self, mut f: impl FnMut(N) -> M) -> DeltavMap<M> {
+        // The nodes and edges are added in the same order, so the indices stay the same
+        let (nodes, edges) = self.graph.into_nodes_edges();
+        let mut graph = UnGraph::with_capacity(nodes.len(), edges.len());
+        for node in nodes {
+            graph.add_node(f(node.weight));
+        }
+        for edge in edges {
+            graph.add_edge(edge.source(), edge.target(), edge.weight);
+        }
+
+        DeltavMap {
+            menu_tree: self.menu_tree,
+            graph,
+            tags: self.tags,
+            metadata: self.metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::NodePayload;
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Payload {
+        name: String,
+        number: usize,
+    }
+
+    impl NodePayload for Payload {
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    #[test]
+    fn test_map_payloads() {
+        let mut number = 0;
+        let map = get_test_map().map_payloads(|name| {
+            number += 1;
+            Payload {
+                name: name.to_string(),
+                number,
+            }
+        });
+
+        assert_eq!(map.payload("Node3").unwrap().number, 3);
+        assert!(map.payload("Category1").is_err());
+        assert_eq!(map.calculate_delta_v("Node1", "Node4").unwrap(), Some(1030));
+        assert_eq!(map.validate(), Ok(()));
+
+        let map = map.map_payloads(|payload| Arc::from(payload.name));
+        assert_eq!(map, get_test_map());
+    }
+}
@@ -0,0 +1,286 @@
+//! Python bindings built with pyo3
+//!
+//! The bindings are exported as the `deltav_calc` Python module. To build an importable module,
+//! run `maturin build --features python,pyo3/extension-module`
+//!
+//! ```python
+//! from deltav_calc import DeltavMap
+//!
+//! stock_map = DeltavMap.stock()
+//! route = stock_map.calculate_route("Kerbin Surface", "Mun Surface")
+//! print(route.nodes, route.delta_v)
+//! ```
+
+use crate::menutree::depth_error;
+use crate::{CalcOptions, DeltavMap, Leg, MenuNode, NoSuchNodeError, Route, MAX_DEPTH};
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+impl From<NoSuchNodeError> for PyErr {
+    fn from(error: NoSuchNodeError) -> Self {
+        PyKeyError::new_err(error.to_string())
+    }
+}
+
+/// A delta-v map that can be used from Python
+#[pyclass(
+    name = "DeltavMap",
+    module = "deltav_calc",
+    frozen,
+    skip_from_py_object
+)]
+#[derive(Debug, Clone)]
+pub struct PyDeltavMap {
+    map: DeltavMap,
+}
+
+#[pymethods]
+impl PyDeltavMap {
+    /// Returns the stock map
+    #[staticmethod]
+    fn stock() -> PyDeltavMap {
+        PyDeltavMap {
+            map: DeltavMap::new_stock(),
+        }
+    }
+
+    /// Reads the map file at the path
+    #[staticmethod]
+    fn from_path(path: PathBuf) -> PyResult<PyDeltavMap> {
+        let map = DeltavMap::from_path(&path).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(PyDeltavMap { map })
+    }
+
+    /// Reads a map from the text of a JSON file
+    #[staticmethod]
+    fn from_json(text: &str) -> PyResult<PyDeltavMap> {
+        let map = DeltavMap::from_reader(text.as_bytes())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(PyDeltavMap { map })
+    }
+
+    /// The menu tree of the map. Raises a `ValueError` if it is nested deeper than `MAX_DEPTH`
+    #[getter]
+    fn menu_tree(&self) -> PyResult<PyMenuNode> {
+        // The nodes are converted recursively
+        let tree = self.map.menu_tree();
+        if tree.depth() > MAX_DEPTH {
+            return Err(PyValueError::new_err(depth_error()));
+        }
+
+        Ok(PyMenuNode::from(tree.root()))
+    }
+
+    /// Calculates the delta-v required to get from the start to the end. Returns `None` if there
+    /// is no path between the nodes and raises a `KeyError` if either node doesn't exist
+    fn calculate_delta_v(&self, start: &str, end: &str) -> PyResult<Option<i64>> {
+        Ok(self.map.calculate_delta_v(start, end)?)
+    }
+
+    /// Calculates the cheapest route from the start to the end. Returns `None` if there is no
+    /// path between the nodes and raises a `KeyError` if either node doesn't exist
+    fn calculate_route(&self, start: &str, end: &str) -> PyResult<Option<PyRoute>> {
+        let route = self
+            .map
+            .calculate_route(start, end, &CalcOptions::default())?;
+
+        Ok(route.map(|route| PyRoute { route }))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DeltavMap({:?})", self.map.menu_tree().name())
+    }
+}
+
+impl From<DeltavMap> for PyDeltavMap {
+    fn from(map: DeltavMap) -> Self {
+        PyDeltavMap { map }
+    }
+}
+
+/// A category or node of the menu tree. Nodes have no children
+#[pyclass(
+    name = "MenuNode",
+    module = "deltav_calc",
+    frozen,
+    skip_from_py_object,
+    get_all
+)]
+#[derive(Debug, Clone)]
+pub struct PyMenuNode {
+    name: String,
+    is_category: bool,
+    children: Vec<PyMenuNode>,
+}
+
+impl From<MenuNode<'_>> for PyMenuNode {
+    fn from(node: MenuNode) -> Self {
+        PyMenuNode {
+            name: node.name().to_string(),
+            is_category: node.is_category(),
+            children: node.children().map(PyMenuNode::from).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyMenuNode {
+    fn __repr__(&self) -> String {
+        format!("MenuNode({:?})", self.name)
+    }
+}
+
+/// The cheapest path between two nodes
+#[pyclass(name = "Route", module = "deltav_calc", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct PyRoute {
+    route: Route,
+}
+
+#[pymethods]
+impl PyRoute {
+    /// The name of the node the route starts at
+    #[getter]
+    fn start(&self) -> &str {
+        self.route.start()
+    }
+
+    /// The name of the node the route ends at
+    #[getter]
+    fn end(&self) -> &str {
+        self.route.end()
+    }
+
+    /// The names of all nodes along the route, including the start and the end
+    #[getter]
+    fn nodes(&self) -> Vec<&str> {
+        self.route.nodes()
+    }
+
+    /// The legs of the route in order
+    #[getter]
+    fn legs(&self) -> Vec<PyLeg> {
+        self.route
+            .legs()
+            .iter()
+            .map(|leg| PyLeg { leg: leg.clone() })
+            .collect()
+    }
+
+    /// The delta-v of the whole route
+    #[getter]
+    fn delta_v(&self) -> i64 {
+        self.route.delta_v()
+    }
+
+    /// The travel time of the whole route in seconds
+    #[getter]
+    fn travel_time(&self) -> u64 {
+        self.route.travel_time()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Route({:?} -> {:?}, delta_v={})",
+            self.route.start(),
+            self.route.end(),
+            self.route.delta_v()
+        )
+    }
+}
+
+/// A single maneuver between two neighbouring nodes of a route
+#[pyclass(name = "Leg", module = "deltav_calc", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct PyLeg {
+    leg: Leg,
+}
+
+#[pymethods]
+impl PyLeg {
+    /// The name of the node the leg starts at
+    #[getter(from_node)]
+    fn leg_from(&self) -> &str {
+        self.leg.from()
+    }
+
+    /// The name of the node the leg ends at
+    #[getter(to_node)]
+    fn leg_to(&self) -> &str {
+        self.leg.to()
+    }
+
+    /// The delta-v of the leg
+    #[getter]
+    fn delta_v(&self) -> i32 {
+        self.leg.delta_v()
+    }
+
+    /// Whether the leg is flown by aerobraking
+    #[getter]
+    fn aerobrake(&self) -> bool {
+        self.leg.aerobrake()
+    }
+
+    /// The travel time of the leg in seconds, if the map knows it
+    #[getter]
+    fn travel_time(&self) -> Option<u64> {
+        self.leg.travel_time()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Leg({:?} -> {:?}, delta_v={})",
+            self.leg.from(),
+            self.leg.to(),
+            self.leg.delta_v()
+        )
+    }
+}
+
+/// The `deltav_calc` Python module
+#[pymodule]
+#[pyo3(name = "deltav_calc")]
+pub fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyDeltavMap>()?;
+    module.add_class::<PyMenuNode>()?;
+    module.add_class::<PyRoute>()?;
+    module.add_class::<PyLeg>()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PyDeltavMap, PyMenuNode};
+    use crate::tests::get_test_map;
+    use pyo3::exceptions::PyKeyError;
+    use pyo3::Python;
+
+    #[test]
+    fn test_menu_node() {
+        let map = get_test_map();
+        let tree = PyMenuNode::from(map.menu_tree().root());
+
+        assert!(tree.is_category);
+        assert_eq!(tree.children[0].children[0].name, "Node1");
+        assert!(!tree.children[0].children[0].is_category);
+    }
+
+    #[test]
+    fn test_route() {
+        let map = PyDeltavMap::from(get_test_map());
+        let route = map.calculate_route("Node1", "Node2").unwrap().unwrap();
+        assert_eq!(route.nodes(), vec!["Node1", "Node2"]);
+        assert_eq!(route.legs()[0].delta_v(), 900);
+
+        Python::initialize();
+        Python::attach(|py| {
+            let error = map.calculate_delta_v("Node1", "Node5").unwrap_err();
+            assert!(error.is_instance_of::<PyKeyError>(py));
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, NodePayload, Route};
+use alloc::vec;
+use alloc::vec::Vec;
+use petgraph::algo;
+use petgraph::graph::DiGraph;
+
+/// The tag marking nodes where a craft can refuel
+pub const ISRU_TAG: &str = "has_isru";
+
+/// A route split into segments that each fit into the deltav capacity of a craft, with a refuel
+/// stop between every two segments
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RefuelPlan {
+    segments: Vec<Route>,
+}
+
+impl RefuelPlan {
+    /// The segments of the plan in the order they are flown
+    pub fn segments(&self) -> &[Route] {
+        &self.segments
+    }
+
+    /// The names of the nodes the craft has to refuel at
+    pub fn refuel_stops(&self) -> Vec<&str> {
+        self.segments
+            .iter()
+            .take(self.segments.len().saturating_sub(1))
+            .map(Route::end)
+            .collect()
+    }
+
+    /// The total deltav required to fly all segments
+    pub fn delta_v(&self) -> i64 {
+        self.segments.iter().map(Route::delta_v).sum()
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Plans the cheapest way from the start to the end for a craft that can only fly the given
+    /// amount of deltav without refueling. Nodes tagged with [`ISRU_TAG`] are used as refuel stops.
+    ///
+    /// Returns a [`NoSuchNodeError`] If either start or end aren't valid nodes
+    /// Returns `None` if the end can't be reached with the capacity
+    /// Returns a plan without segments if the start is the end
+    pub fn plan_refuel_stops(
+        &self,
+        start: &str,
+        end: &str,
+        capacity: i32,
+        options: &CalcOptions,
+    ) -> Result<Option<RefuelPlan>, NoSuchNodeError> {
+        let start = self.menu_tree().search(start)?.name();
+        let end = self.menu_tree().search(end)?.name();
+        if start == end {
+            return Ok(Some(RefuelPlan { segments: vec![] }));
+        }
+
+        let mut stops = vec![start];
+        stops.extend(
+            self.nodes_with_tag(ISRU_TAG)
+                .into_iter()
+                .filter(|stop| *stop != start && *stop != end),
+        );
+        stops.push(end);
+
+        // Every segment between two stops that fits into the capacity is an edge
+        let mut segments: DiGraph<&str, Route> = DiGraph::new();
+        let indices: Vec<_> = stops.iter().map(|stop| segments.add_node(*stop)).collect();
+        for (from, from_index) in stops.iter().zip(&indices) {
+            for (to, to_index) in stops.iter().zip(&indices) {
+                if from == to {
+                    continue;
+                }
+
+                if let Some(route) = self.calculate_route(from, to, options)? {
+                    if route.delta_v() <= i64::from(capacity) {
+                        segments.add_edge(*from_index, *to_index, route);
+                    }
+                }
+            }
+        }
+
+        let end_index = *indices.last().unwrap();
+        let result = algo::astar(
+            &segments,
+            indices[0],
+            |finish| finish == end_index,
+            |e| e.weight().delta_v(),
+            |_| 0,
+        );
+
+        Ok(result.map(|(_, path)| RefuelPlan {
+            segments: path
+                .windows(2)
+                .map(|pair| {
+                    let edge = segments.find_edge(pair[0], pair[1]).unwrap();
+                    segments[edge].clone()
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CalcOptions, DeltavMap};
+
+    #[test]
+    fn test_refuel_stops() {
+        let stock_map = DeltavMap::new_stock();
+        let plan = stock_map
+            .plan_refuel_stops("Kerbin Surface", "Duna Surface", 5000, &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(plan.refuel_stops(), vec!["Minmus Surface"]);
+        assert_eq!(plan.segments()[0].delta_v(), 4670);
+        assert_eq!(plan.delta_v(), 9080);
+    }
+
+    #[test]
+    fn test_no_stops_needed() {
+        let stock_map = DeltavMap::new_stock();
+        let plan = stock_map
+            .plan_refuel_stops("Kerbin Surface", "Duna Surface", 7000, &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+
+        assert!(plan.refuel_stops().is_empty());
+        assert_eq!(plan.delta_v(), 6540);
+    }
+
+    #[test]
+    fn test_same_start_and_end() {
+        let stock_map = DeltavMap::new_stock();
+        let plan = stock_map
+            .plan_refuel_stops("Mun Surface", "Mun Surface", 0, &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+
+        assert!(plan.segments().is_empty());
+        assert!(plan.refuel_stops().is_empty());
+        assert_eq!(plan.delta_v(), 0);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let stock_map = DeltavMap::new_stock();
+        let plan = stock_map
+            .plan_refuel_stops("Kerbin Surface", "Duna Surface", 3000, &CalcOptions::new())
+            .unwrap();
+
+        assert_eq!(plan, None);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, NodePayload, Route};
+use alloc::collections::BTreeMap;
+use petgraph::algo;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{EdgeFiltered, EdgeRef, NodeFiltered};
+
+/// What the meeting node of a rendezvous minimizes
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Default, Debug)]
+pub enum RendezvousObjective {
+    /// The deltav both craft spend together
+    #[default]
+    Total,
+
+    /// The deltav of the craft that has to spend more, so neither craft runs dry
+    Maximum,
+}
+
+/// Where two craft meet and how they get there, see [`DeltavMap::rendezvous`]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Rendezvous {
+    pub(crate) first: Route,
+    pub(crate) second: Route,
+}
+
+impl Rendezvous {
+    /// The name of the node the craft meet at
+    pub fn node(&self) -> &str {
+        self.first.end()
+    }
+
+    /// The route of the first craft to the meeting node
+    pub fn first(&self) -> &Route {
+        &self.first
+    }
+
+    /// The route of the second craft to the meeting node
+    pub fn second(&self) -> &Route {
+        &self.second
+    }
+
+    /// The deltav both craft spend together
+    pub fn delta_v(&self) -> i64 {
+        self.first.delta_v() + self.second.delta_v()
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Finds the node where two craft starting at different nodes should meet, for example for a
+    /// rescue mission. The meeting node minimizes the cost of both routes according to the
+    /// objective. Ties are broken by the name of the node
+    ///
+    /// Returns a [`NoSuchNodeError`] If either start isn't a valid node
+    /// Returns `None` if there is no node both craft can reach
+    ///
+    /// # Example
+    /// ```
+    /// use deltav_calc::{CalcOptions, DeltavMap, RendezvousObjective};
+    ///
+    /// let stock_map = DeltavMap::new_stock();
+    /// let meeting = stock_map
+    ///     .rendezvous(
+    ///         "Mun Surface",
+    ///         "Minmus Surface",
+    ///         RendezvousObjective::Maximum,
+    ///         &CalcOptions::new(),
+    ///     )
+    ///     .unwrap()
+    ///     .unwrap();
+    /// println!("Meet at {}", meeting.node());
+    /// ```
+    pub fn rendezvous(
+        &self,
+        first: &str,
+        second: &str,
+        objective: RendezvousObjective,
+        options: &CalcOptions,
+    ) -> Result<Option<Rendezvous>, NoSuchNodeError> {
+        let first_costs = self.costs_from(self.node_index(first)?, options);
+        let second_costs = self.costs_from(self.node_index(second)?, options);
+
+        let meeting = first_costs
+            .iter()
+            .filter_map(|(node, first_cost)| {
+                let second_cost = second_costs.get(node)?;
+                let cost = match objective {
+                    RendezvousObjective::Total => first_cost + second_cost,
+                    RendezvousObjective::Maximum => *first_cost.max(second_cost),
+                };
+                Some((cost, self.graph[*node].name()))
+            })
+            .min();
+        let Some((_, meeting)) = meeting else {
+            return Ok(None);
+        };
+
+        let first = self.calculate_route(first, meeting, options)?;
+        let second = self.calculate_route(second, meeting, options)?;
+
+        Ok(first
+            .zip(second)
+            .map(|(first, second)| Rendezvous { first, second }))
+    }
+
+    // The cost of the cheapest route from the start to every node that can be reached with the
+    // options. Nodes that are avoided or too many legs away can't be reached
+    fn costs_from(&self, start: NodeIndex, options: &CalcOptions) -> BTreeMap<NodeIndex, i64> {
+        let allowed = |node| node == start || self.is_allowed(node, options);
+
+        if let Some(max_legs) = options.max_legs {
+            let mut costs = BTreeMap::new();
+            for round in self.leg_rounds(start, allowed, max_legs, options) {
+                for (node, entry) in round.into_iter().enumerate() {
+                    if let Some((cost, _)) = entry {
+                        let best = costs.entry(NodeIndex::new(node)).or_insert(cost);
+                        *best = cost.min(*best);
+                    }
+                }
+            }
+            return costs;
+        }
+
+        let nodes = NodeFiltered::from_fn(&self.graph, allowed);
+        let filtered = EdgeFiltered::from_fn(&nodes, |edge| self.is_usable(edge.weight(), options));
+
+        algo::dijkstra(&filtered, start, None, |e| {
+            self.routing_weight(e.id(), e.source(), options)
+        })
+        .into_iter()
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{CalcOptions, DeltavMap, RendezvousObjective};
+
+    #[test]
+    fn test_rendezvous() {
+        let test_map = get_test_map();
+        let options = CalcOptions::new();
+
+        // Node1 - 900 - Node2 - 80 - Node3 - 50 - Node4
+        let total = test_map
+            .rendezvous("Node1", "Node4", RendezvousObjective::Total, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(total.delta_v(), 1030);
+        assert_eq!(total.node(), "Node1");
+
+        let maximum = test_map
+            .rendezvous("Node1", "Node4", RendezvousObjective::Maximum, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(maximum.node(), "Node2");
+        assert_eq!(maximum.first().delta_v(), 900);
+        assert_eq!(maximum.second().delta_v(), 130);
+
+        let same = test_map
+            .rendezvous("Node3", "Node3", RendezvousObjective::Total, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(same.node(), "Node3");
+        assert_eq!(same.delta_v(), 0);
+
+        let stock_map = DeltavMap::new_stock();
+        assert!(stock_map
+            .rendezvous("Node1", "Mun Surface", RendezvousObjective::Total, &options)
+            .is_err());
+        assert!(stock_map
+            .rendezvous(
+                "Kerbin",
+                "Mun Surface",
+                RendezvousObjective::Total,
+                &options
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_rendezvous_max_legs() {
+        let map = DeltavMap::from_dsl(
+            "
+            [Kerbol]
+            A
+            X
+            Y
+            B
+            A -> X : 10
+            X -> Y : 10
+            Y -> B : 10
+            A -> B : 100
+            ",
+        )
+        .unwrap();
+
+        // The cheapest meeting nodes are two legs away from one of the craft
+        let options = CalcOptions::new().max_legs(1);
+        let meeting = map
+            .rendezvous("A", "B", RendezvousObjective::Maximum, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(meeting.node(), "A");
+        assert_eq!(meeting.second().legs().len(), 1);
+        assert_eq!(meeting.delta_v(), 100);
+    }
+}
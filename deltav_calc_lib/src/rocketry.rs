@@ -0,0 +1,299 @@
+//! Helpers around the Tsiolkovsky rocket equation.
+//!
+//! Masses can be given in any unit as long as all masses of a calculation use the same one.
+//! Delta-v is given in m/s and the specific impulse in seconds.
+//!
+//! # Example
+//! ```
+//! use deltav_calc::rocketry;
+//! use deltav_calc::DeltavMap;
+//!
+//! let stock_map = DeltavMap::new_stock();
+//! let cost = stock_map
+//!     .calculate_delta_v("Low Kerbin Orbit (80km)", "Mun Surface")
+//!     .unwrap()
+//!     .unwrap();
+//!
+//! // The propellant a 2t lander with a Terrier engine needs to get there
+//! let propellant = rocketry::propellant_mass(345.0, 2.0, cost as f64);
+//! ```
+
+use crate::math;
+use crate::Route;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The standard gravity used by the game to convert between specific impulse and exhaust velocity
+pub const G0: f64 = 9.80665;
+
+/// The effective exhaust velocity in m/s of an engine with the given specific impulse
+pub fn exhaust_velocity(isp: f64) -> f64 {
+    isp * G0
+}
+
+/// The deltav a craft with the given specific impulse has when it burns all propellant, going
+/// from the wet to the dry mass
+pub fn delta_v(isp: f64, wet_mass: f64, dry_mass: f64) -> f64 {
+    exhaust_velocity(isp) * math::ln(wet_mass / dry_mass)
+}
+
+/// The mass a craft needs to have before a burn to end up with the dry mass after burning the
+/// given deltav
+pub fn wet_mass(isp: f64, dry_mass: f64, delta_v: f64) -> f64 {
+    dry_mass * math::exp(delta_v / exhaust_velocity(isp))
+}
+
+/// The propellant mass required to burn the given deltav with a craft that weighs the dry mass
+/// after the burn
+pub fn propellant_mass(isp: f64, dry_mass: f64, delta_v: f64) -> f64 {
+    wet_mass(isp, dry_mass, delta_v) - dry_mass
+}
+
+/// A stage of a [`Vessel`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stage {
+    isp: f64,
+    dry_mass: f64,
+    propellant_mass: f64,
+}
+
+impl Stage {
+    /// Creates a stage with the given specific impulse, the mass of the stage without propellant
+    /// and the mass of its propellant
+    pub fn new(isp: f64, dry_mass: f64, propellant_mass: f64) -> Stage {
+        Stage {
+            isp,
+            dry_mass,
+            propellant_mass,
+        }
+    }
+
+    /// The specific impulse of the stage's engines
+    pub fn isp(&self) -> f64 {
+        self.isp
+    }
+
+    /// The mass of the stage without propellant
+    pub fn dry_mass(&self) -> f64 {
+        self.dry_mass
+    }
+
+    /// The mass of the stage's propellant
+    pub fn propellant_mass(&self) -> f64 {
+        self.propellant_mass
+    }
+
+    /// The mass of the stage including its propellant
+    pub fn wet_mass(&self) -> f64 {
+        self.dry_mass + self.propellant_mass
+    }
+}
+
+/// How much of a stage was used to fly a route
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StageUsage {
+    delta_v: f64,
+    propellant_mass: f64,
+}
+
+impl StageUsage {
+    /// The deltav the stage provided
+    pub fn delta_v(&self) -> f64 {
+        self.delta_v
+    }
+
+    /// The propellant the stage burned
+    pub fn propellant_mass(&self) -> f64 {
+        self.propellant_mass
+    }
+}
+
+/// The result of checking if a [`Vessel`] can fly a route
+#[derive(Clone, PartialEq, Debug)]
+pub struct FeasibilityReport {
+    stages: Vec<StageUsage>,
+    runs_dry: Option<usize>,
+}
+
+impl FeasibilityReport {
+    /// The usage of every stage of the vessel, in firing order
+    pub fn stages(&self) -> &[StageUsage] {
+        &self.stages
+    }
+
+    /// The index of the leg the vessel runs out of propellant on, if it does
+    pub fn runs_dry(&self) -> Option<usize> {
+        self.runs_dry
+    }
+
+    /// Checks if the vessel can fly the whole route
+    pub fn is_feasible(&self) -> bool {
+        self.runs_dry.is_none()
+    }
+}
+
+/// A craft made up of stages that are fired and dropped one after another
+///
+/// # Example
+/// ```
+/// use deltav_calc::rocketry::{Stage, Vessel};
+/// use deltav_calc::{CalcOptions, DeltavMap};
+///
+/// let stock_map = DeltavMap::new_stock();
+/// let route = stock_map
+///     .calculate_route("Low Kerbin Orbit (80km)", "Mun Surface", &CalcOptions::new())
+///     .unwrap()
+///     .unwrap();
+///
+/// let vessel = Vessel::new(vec![Stage::new(345.0, 2.0, 2.5)]);
+/// let report = vessel.check_route(&route);
+/// assert!(report.is_feasible());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Vessel {
+    stages: Vec<Stage>,
+}
+
+impl Vessel {
+    /// Creates a vessel from its stages in firing order
+    pub fn new(stages: Vec<Stage>) -> Vessel {
+        Vessel { stages }
+    }
+
+    /// The stages of the vessel in firing order
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// The total deltav of the vessel
+    pub fn delta_v(&self) -> f64 {
+        let mut mass: f64 = self.stages.iter().map(Stage::wet_mass).sum();
+        let mut total = 0.0;
+
+        for stage in &self.stages {
+            total += delta_v(stage.isp, mass, mass - stage.propellant_mass);
+            mass -= stage.wet_mass();
+        }
+
+        total
+    }
+
+    /// Flies the route leg by leg, firing the stages in order, and reports how much every stage
+    /// was used and where the vessel runs out of propellant
+    pub fn check_route(&self, route: &Route) -> FeasibilityReport {
+        let mut usages = vec![
+            StageUsage {
+                delta_v: 0.0,
+                propellant_mass: 0.0,
+            };
+            self.stages.len()
+        ];
+        let mut mass: f64 = self.stages.iter().map(Stage::wet_mass).sum();
+        let mut stage = 0;
+        let mut propellant = self.stages.first().map_or(0.0, Stage::propellant_mass);
+
+        for (leg_index, leg) in route.legs().iter().enumerate() {
+            let mut required = leg.delta_v() as f64;
+
+            while required > 0.0 {
+                if stage == self.stages.len() {
+                    return FeasibilityReport {
+                        stages: usages,
+                        runs_dry: Some(leg_index),
+                    };
+                }
+
+                let isp = self.stages[stage].isp;
+                let available = delta_v(isp, mass, mass - propellant);
+
+                if available >= required {
+                    let burned = mass - mass / math::exp(required / exhaust_velocity(isp));
+                    usages[stage].delta_v += required;
+                    usages[stage].propellant_mass += burned;
+                    mass -= burned;
+                    propellant -= burned;
+                    required = 0.0;
+                } else {
+                    // Burn the stage empty and drop it
+                    usages[stage].delta_v += available;
+                    usages[stage].propellant_mass += propellant;
+                    required -= available;
+                    mass -= propellant + self.stages[stage].dry_mass;
+                    stage += 1;
+                    propellant = self.stages.get(stage).map_or(0.0, Stage::propellant_mass);
+                }
+            }
+        }
+
+        FeasibilityReport {
+            stages: usages,
+            runs_dry: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rocketry;
+    use crate::rocketry::{Stage, Vessel};
+    use crate::{CalcOptions, DeltavMap};
+
+    #[test]
+    fn test_delta_v() {
+        let delta_v = rocketry::delta_v(345.0, 4.0, 2.0);
+        assert!((delta_v - 2345.0).abs() < 1.0, "{delta_v}");
+    }
+
+    #[test]
+    fn test_propellant_mass() {
+        let propellant = rocketry::propellant_mass(345.0, 2.0, 2345.0);
+        assert!((propellant - 2.0).abs() < 0.01, "{propellant}");
+
+        let delta_v = rocketry::delta_v(345.0, 2.0 + propellant, 2.0);
+        assert!((delta_v - 2345.0).abs() < 0.001, "{delta_v}");
+    }
+
+    #[test]
+    fn test_vessel_delta_v() {
+        let vessel = Vessel::new(vec![Stage::new(345.0, 2.0, 2.0)]);
+        assert!((vessel.delta_v() - 2345.0).abs() < 1.0);
+
+        // The lower stage has to carry the upper one
+        let vessel = Vessel::new(vec![
+            Stage::new(345.0, 2.0, 2.0),
+            Stage::new(345.0, 2.0, 2.0),
+        ]);
+        let expected = rocketry::delta_v(345.0, 8.0, 6.0) + rocketry::delta_v(345.0, 4.0, 2.0);
+        assert!((vessel.delta_v() - expected).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_check_route() {
+        let stock_map = DeltavMap::new_stock();
+        let route = stock_map
+            .calculate_route("Kerbin Surface", "Mun Surface", &CalcOptions::new())
+            .unwrap()
+            .unwrap();
+
+        // Enough to reach orbit but not the Mun
+        let vessel = Vessel::new(vec![
+            Stage::new(300.0, 4.0, 8.0),
+            Stage::new(345.0, 1.0, 0.5),
+        ]);
+        let report = vessel.check_route(&route);
+        assert!(!report.is_feasible());
+        assert_eq!(report.runs_dry(), Some(1));
+        assert!((report.stages()[0].propellant_mass() - 8.0).abs() < 0.001);
+        assert!((report.stages()[1].propellant_mass() - 0.5).abs() < 0.001);
+
+        let vessel = Vessel::new(vec![
+            Stage::new(300.0, 4.0, 8.0),
+            Stage::new(345.0, 1.0, 4.0),
+        ]);
+        let report = vessel.check_route(&route);
+        assert!(report.is_feasible());
+        let used: f64 = report.stages().iter().map(|stage| stage.delta_v()).sum();
+        assert!((used - 5120.0).abs() < 0.001);
+        assert!(report.stages()[1].propellant_mass() < 4.0);
+    }
+}
@@ -0,0 +1,608 @@
+use crate::{CalcOptions, DeltavFormat, DeltavMap, NoSuchNodeError, NodePayload, RouteWarning};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Write};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// The cost of a route including its uncertainty
+#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct RouteCost {
+    /// The nominal deltav
+    pub nominal: i64,
+
+    /// The deltav in the best case
+    pub low: i64,
+
+    /// The deltav in the worst case
+    pub high: i64,
+}
+
+impl RouteCost {
+    /// Adds the safety margin of the options to the worst case
+    pub fn with_margin(self, options: &CalcOptions) -> RouteCost {
+        RouteCost {
+            high: options.apply_margin(self.high),
+            ..self
+        }
+    }
+}
+
+impl Display for RouteCost {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.low == self.high {
+            write!(f, "{} m/s", self.nominal)
+        } else {
+            write!(f, "{} m/s ({} - {} m/s)", self.nominal, self.low, self.high)
+        }
+    }
+}
+
+/// A single maneuver between two neighbouring nodes of a [`Route`]
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Leg {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) delta_v: i32,
+    pub(crate) min_delta_v: i32,
+    pub(crate) max_delta_v: i32,
+    pub(crate) aerobrake: bool,
+    pub(crate) time: Option<u64>,
+}
+
+impl Leg {
+    /// The name of the node the leg starts at
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// The name of the node the leg ends at
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// The deltav required to fly this leg
+    pub fn delta_v(&self) -> i32 {
+        self.delta_v
+    }
+
+    /// The deltav required to fly this leg in the best case
+    pub fn min_delta_v(&self) -> i32 {
+        self.min_delta_v
+    }
+
+    /// The deltav required to fly this leg in the worst case
+    pub fn max_delta_v(&self) -> i32 {
+        self.max_delta_v
+    }
+
+    /// Checks if the leg relies on aerobraking
+    pub fn aerobrake(&self) -> bool {
+        self.aerobrake
+    }
+
+    /// The time in seconds it takes to fly this leg, if known
+    pub fn travel_time(&self) -> Option<u64> {
+        self.time
+    }
+}
+
+impl Display for Route {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.write_table(f, &DeltavFormat::new().kilometers_from(None))
+    }
+}
+
+/// The same route flown on two maps, see [`Route::recost`]
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RouteComparison {
+    pub(crate) original: Route,
+    pub(crate) recosted: Route,
+}
+
+impl RouteComparison {
+    /// The route on the original map
+    pub fn original(&self) -> &Route {
+        &self.original
+    }
+
+    /// The route on the other map
+    pub fn recosted(&self) -> &Route {
+        &self.recosted
+    }
+
+    /// How much more deltav every leg costs on the other map, in the order of the legs
+    pub fn leg_deltas(&self) -> Vec<i64> {
+        self.original
+            .legs
+            .iter()
+            .zip(&self.recosted.legs)
+            .map(|(original, recosted)| recosted.delta_v as i64 - original.delta_v as i64)
+            .collect()
+    }
+
+    /// How much more deltav the whole route costs on the other map
+    pub fn delta(&self) -> i64 {
+        self.recosted.delta_v() - self.original.delta_v()
+    }
+}
+
+/// The differences between two routes, see [`Route::compare`]
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RouteDiff {
+    pub(crate) delta_v: i64,
+    pub(crate) travel_time: i64,
+    pub(crate) removed: Vec<Leg>,
+    pub(crate) added: Vec<Leg>,
+}
+
+impl RouteDiff {
+    /// How much more deltav the other route costs. Negative if it is cheaper
+    pub fn delta_v(&self) -> i64 {
+        self.delta_v
+    }
+
+    /// How many seconds longer the other route takes. Negative if it is faster
+    pub fn travel_time(&self) -> i64 {
+        self.travel_time
+    }
+
+    /// The legs of the first route the other route doesn't fly
+    pub fn removed_legs(&self) -> &[Leg] {
+        &self.removed
+    }
+
+    /// The legs of the other route the first route doesn't fly
+    pub fn added_legs(&self) -> &[Leg] {
+        &self.added
+    }
+
+    /// Checks if both routes fly the same legs
+    pub fn same_legs(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+/// This error is raised when two routes are joined that don't meet at the same node
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct JoinError {
+    end: String,
+    start: String,
+}
+
+impl JoinError {
+    /// The node the first route ends at
+    pub fn end(&self) -> &str {
+        &self.end
+    }
+
+    /// The node the second route starts at
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+}
+
+impl Display for JoinError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "The route ends at \"{}\", but the next route starts at \"{}\"",
+            self.end, self.start
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for JoinError {}
+
+/// The cheapest path between two nodes of a [`DeltavMap`](crate::DeltavMap), broken down into
+/// its legs
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Route {
+    pub(crate) start: String,
+    pub(crate) legs: Vec<Leg>,
+    pub(crate) warnings: Vec<RouteWarning>,
+}
+
+impl Route {
+    /// The name of the node the route starts at
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    /// The name of the node the route ends at
+    pub fn end(&self) -> &str {
+        match self.legs.last() {
+            None => &self.start,
+            Some(leg) => &leg.to,
+        }
+    }
+
+    /// The legs of the route in the order they have to be flown
+    pub fn legs(&self) -> &[Leg] {
+        &self.legs
+    }
+
+    /// The names of all nodes the route passes through, including the start and the end
+    pub fn nodes(&self) -> Vec<&str> {
+        let mut nodes = vec![self.start.as_str()];
+        nodes.extend(self.legs.iter().map(|leg| leg.to.as_str()));
+        nodes
+    }
+
+    /// The hazards encountered along the route, ordered by the leg they occur on
+    pub fn warnings(&self) -> &[RouteWarning] {
+        &self.warnings
+    }
+
+    /// The total deltav required to fly the route. Totals are summed as `i64`, so they can't
+    /// overflow even for maps with huge costs
+    pub fn delta_v(&self) -> i64 {
+        self.legs.iter().map(|leg| leg.delta_v as i64).sum()
+    }
+
+    /// The total time in seconds it takes to fly the route. Legs with an unknown travel time
+    /// aren't included
+    pub fn travel_time(&self) -> u64 {
+        self.legs.iter().filter_map(|leg| leg.time).sum()
+    }
+
+    /// Formats the route as a table like its [`Display`] implementation, but with the costs
+    /// formatted by the given format
+    pub fn to_table(&self, format: &DeltavFormat) -> String {
+        let mut table = String::new();
+        self.write_table(&mut table, format)
+            .expect("Writing to a string can't fail");
+        table
+    }
+
+    // Writes one aligned row per leg and the total
+    fn write_table(&self, f: &mut impl Write, format: &DeltavFormat) -> core::fmt::Result {
+        let labels: Vec<String> = self
+            .legs
+            .iter()
+            .map(|leg| format!("{} -> {}", leg.from, leg.to))
+            .collect();
+        let costs: Vec<String> = self
+            .legs
+            .iter()
+            .map(|leg| format.format(leg.delta_v as i64))
+            .collect();
+        let total = format.format(self.delta_v());
+
+        let label_width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .chain([5])
+            .max()
+            .unwrap_or_default();
+        let cost_width = costs
+            .iter()
+            .chain([&total])
+            .map(|cost| cost.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        for ((leg, label), cost) in self.legs.iter().zip(labels).zip(costs) {
+            write!(f, "{label:<label_width$}  {cost:>cost_width$}")?;
+            if leg.aerobrake {
+                write!(f, " (aerobraking)")?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{:<label_width$}  {total:>cost_width$}", "Total")
+    }
+
+    /// The total deltav required to fly the route, including the best and the worst case
+    pub fn cost(&self) -> RouteCost {
+        RouteCost {
+            nominal: self.delta_v(),
+            low: self.legs.iter().map(|leg| leg.min_delta_v as i64).sum(),
+            high: self.legs.iter().map(|leg| leg.max_delta_v as i64).sum(),
+        }
+    }
+
+    /// Appends the other route, which has to start where this route ends. The warnings of the
+    /// other route are moved to the legs they occur on in the combined route
+    ///
+    /// Returns a [`JoinError`] if the other route starts at another node
+    pub fn join(mut self, other: Route) -> Result<Route, JoinError> {
+        if self.end() != other.start {
+            return Err(JoinError {
+                end: self.end().to_string(),
+                start: other.start,
+            });
+        }
+
+        let offset = self.legs.len();
+        self.legs.extend(other.legs);
+        self.warnings
+            .extend(other.warnings.into_iter().map(|warning| RouteWarning {
+                leg: warning.leg + offset,
+                ..warning
+            }));
+
+        Ok(self)
+    }
+
+    /// Calculates the costs of the route again for the map and the options, for example for a
+    /// route that was saved and loaded again. The route passes through the same nodes
+    ///
+    /// Returns a [`NoSuchNodeError`] if the map doesn't contain the nodes of the route
+    /// Returns `None` if the route can't be flown with the options, for example because it passes
+    /// a node they avoid
+    pub fn recalculate<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        options: &CalcOptions,
+    ) -> Result<Option<Route>, NoSuchNodeError> {
+        map.route_along(&self.nodes(), options)
+    }
+
+    /// Flies the same nodes on another map, for example to see how a rescaled system changes the
+    /// route, and compares the costs of the legs
+    ///
+    /// Returns a [`NoSuchNodeError`] if the other map doesn't contain the nodes of the route
+    /// Returns `None` if the route can't be flown on the other map with the options, for example
+    /// because it passes a node they avoid
+    pub fn recost<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        options: &CalcOptions,
+    ) -> Result<Option<RouteComparison>, NoSuchNodeError> {
+        Ok(self
+            .recalculate(map, options)?
+            .map(|recosted| RouteComparison {
+                original: self.clone(),
+                recosted,
+            }))
+    }
+
+    /// Compares the route with another route, for example to show how much a detour saves. Legs
+    /// are the same if they connect the same nodes in the same direction
+    pub fn compare(&self, other: &Route) -> RouteDiff {
+        let same = |leg: &Leg, legs: &[Leg]| {
+            legs.iter()
+                .any(|other| other.from == leg.from && other.to == leg.to)
+        };
+
+        RouteDiff {
+            delta_v: other.delta_v() - self.delta_v(),
+            travel_time: other.travel_time() as i64 - self.travel_time() as i64,
+            removed: self
+                .legs
+                .iter()
+                .filter(|leg| !same(leg, &other.legs))
+                .cloned()
+                .collect(),
+            added: other
+                .legs
+                .iter()
+                .filter(|leg| !same(leg, &self.legs))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the route flown in the opposite direction through the same nodes. The costs are
+    /// calculated again with the options, since edges can be cheaper in one direction, for example
+    /// with aerobraking
+    ///
+    /// Returns a [`NoSuchNodeError`] if the map doesn't contain the nodes of the route
+    /// Returns `None` if the route can't be flown back with the options, for example because it
+    /// passes a node they avoid
+    pub fn reversed<N: NodePayload>(
+        &self,
+        map: &DeltavMap<N>,
+        options: &CalcOptions,
+    ) -> Result<Option<Route>, NoSuchNodeError> {
+        let mut nodes = self.nodes();
+        nodes.reverse();
+
+        map.route_along(&nodes, options)
+    }
+}
+
+/// A trip to a destination and back to the start
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ReturnTrip {
+    pub(crate) outbound: Route,
+    pub(crate) inbound: Route,
+}
+
+impl ReturnTrip {
+    /// The route from the start to the destination
+    pub fn outbound(&self) -> &Route {
+        &self.outbound
+    }
+
+    /// The route from the destination back to the start
+    pub fn inbound(&self) -> &Route {
+        &self.inbound
+    }
+
+    /// The total deltav required to fly both routes
+    pub fn delta_v(&self) -> i64 {
+        self.outbound.delta_v() + self.inbound.delta_v()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::route::{Leg, Route, RouteCost};
+    use crate::tests::get_test_map;
+    use crate::DeltavFormat;
+    use crate::{CalcOptions, RouteWarning, WarningKind};
+
+    fn get_test_route() -> Route {
+        Route {
+            start: String::from("Node1"),
+            legs: vec![
+                Leg {
+                    from: String::from("Node1"),
+                    to: String::from("Node2"),
+                    delta_v: 900,
+                    min_delta_v: 900,
+                    max_delta_v: 900,
+                    aerobrake: false,
+                    time: Some(1000),
+                },
+                Leg {
+                    from: String::from("Node2"),
+                    to: String::from("Node3"),
+                    delta_v: 80,
+                    min_delta_v: 60,
+                    max_delta_v: 120,
+                    aerobrake: false,
+                    time: None,
+                },
+            ],
+            warnings: vec![],
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let route = get_test_route();
+
+        assert_eq!(route.start(), "Node1");
+        assert_eq!(route.end(), "Node3");
+        assert_eq!(route.nodes(), vec!["Node1", "Node2", "Node3"]);
+        assert_eq!(route.delta_v(), 980);
+        assert_eq!(route.travel_time(), 1000);
+
+        let cost = route.cost();
+        assert_eq!(
+            cost,
+            RouteCost {
+                nominal: 980,
+                low: 960,
+                high: 1020,
+            }
+        );
+        assert_eq!(cost.with_margin(&CalcOptions::new().margin(10)).high, 1122);
+        assert_eq!(cost.to_string(), "980 m/s (960 - 1020 m/s)");
+    }
+
+    #[test]
+    fn test_join() {
+        let route = get_test_route();
+        let mut next = get_test_route();
+        next.start = String::from("Node3");
+        next.legs.truncate(1);
+        next.legs[0].from = String::from("Node3");
+        next.warnings = vec![RouteWarning {
+            kind: WarningKind::AtmosphericEntry,
+            leg: 0,
+        }];
+
+        let joined = route.clone().join(next).unwrap();
+        assert_eq!(joined.nodes(), vec!["Node1", "Node2", "Node3", "Node2"]);
+        assert_eq!(joined.delta_v(), 1880);
+        assert_eq!(joined.warnings()[0].leg(), 2);
+
+        let error = route.clone().join(route).unwrap_err();
+        assert_eq!(error.end(), "Node3");
+        assert_eq!(error.start(), "Node1");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let route = get_test_route();
+        let json = serde_json::to_string(&route).unwrap();
+        let loaded: Route = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, route);
+
+        let map = get_test_map();
+        let recalculated = loaded.recalculate(&map, &CalcOptions::new()).unwrap();
+        assert_eq!(recalculated.unwrap().nodes(), route.nodes());
+
+        let mut other = route.clone();
+        other.legs[1].to = String::from("Node4");
+        assert_eq!(other.recalculate(&map, &CalcOptions::new()), Ok(None));
+
+        // Only the nodes the route passes are avoided, not its start and end
+        let options = CalcOptions::new().avoid_node("Node2");
+        assert_eq!(route.recalculate(&map, &options), Ok(None));
+        let options = CalcOptions::new().avoid_node("Node1").avoid_node("Node3");
+        assert!(route.recalculate(&map, &options).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut route = get_test_route();
+        route.legs[1].aerobrake = true;
+
+        assert_eq!(
+            route.to_string(),
+            "Node1 -> Node2  900 m/s\n\
+             Node2 -> Node3   80 m/s (aerobraking)\n\
+             Total           980 m/s"
+        );
+    }
+
+    #[test]
+    fn test_to_table() {
+        let mut route = get_test_route();
+        route.legs[0].delta_v = 12_345;
+
+        assert_eq!(
+            route.to_table(&DeltavFormat::for_locale("de")),
+            "Node1 -> Node2  12,3 km/s\n\
+             Node2 -> Node3     80 m/s\n\
+             Total           12,4 km/s"
+        );
+    }
+
+    #[test]
+    fn test_recost() {
+        let route = get_test_route();
+        let scaled = get_test_map().scaled(2.0);
+        let comparison = route.recost(&scaled, &CalcOptions::new()).unwrap().unwrap();
+
+        assert_eq!(comparison.recosted().delta_v(), 1960);
+        assert_eq!(comparison.leg_deltas(), vec![900, 80]);
+        assert_eq!(comparison.delta(), 980);
+
+        // Node2 is tagged as having an atmosphere
+        let options = CalcOptions::new().avoid_tag("atmosphere");
+        assert_eq!(route.recost(&scaled, &options), Ok(None));
+        assert_eq!(route.reversed(&scaled, &options), Ok(None));
+        assert!(route
+            .reversed(&scaled, &CalcOptions::new())
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_compare() {
+        let route = get_test_route();
+        let mut other = get_test_route();
+        other.legs[1].to = String::from("Node4");
+        other.legs[1].delta_v = 50;
+        other.legs[1].time = Some(500);
+
+        let diff = route.compare(&other);
+        assert_eq!(diff.delta_v(), -30);
+        assert_eq!(diff.travel_time(), 500);
+        assert_eq!(diff.removed_legs(), &route.legs[1..]);
+        assert_eq!(diff.added_legs(), &other.legs[1..]);
+        assert!(!diff.same_legs());
+        assert!(route.compare(&route).same_legs());
+    }
+
+    #[test]
+    fn test_empty_route() {
+        let route = Route {
+            start: String::from("Node1"),
+            legs: vec![],
+            warnings: vec![],
+        };
+
+        assert_eq!(route.end(), "Node1");
+        assert_eq!(route.delta_v(), 0);
+    }
+}
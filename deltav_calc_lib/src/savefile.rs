@@ -0,0 +1,581 @@
+//! Reading the vessels of a game from its save file, usually `saves/<name>/persistent.sfs`.
+//!
+//! Every vessel is placed on the map like a vessel of a running game, see
+//! [`DeltavMap::vessel_node`], and its parts are turned into [`Stage`]s with the stock engines, so
+//! routes can be checked without starting the game. Parts are staged by the stage they are
+//! decoupled in and engines of mods are ignored.
+//!
+//! This module is only available with the `savefile` feature.
+//!
+//! # Example
+//! ```no_run
+//! use deltav_calc::savefile;
+//! use deltav_calc::{CalcOptions, DeltavMap};
+//!
+//! let stock_map = DeltavMap::new_stock();
+//! let vessels = savefile::read_vessels_from_path("saves/default/persistent.sfs").unwrap();
+//!
+//! for vessel in vessels.iter().filter(|vessel| vessel.kind() == "Lander") {
+//!     let options = CalcOptions::new().aerobrake(true);
+//!     if let Some(report) = vessel.check_route_to(&stock_map, "Kerbin Surface", &options).unwrap() {
+//!         println!("{} can get home: {}", vessel.name(), report.is_feasible());
+//!     }
+//! }
+//! ```
+
+use crate::engines::{self, Engine};
+use crate::rocketry::{FeasibilityReport, Stage, Vessel};
+use crate::{CalcOptions, DeltavMap, NoSuchNodeError, Situation, VesselState};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// This error is raised when a save file can't be read
+#[derive(Debug)]
+pub enum SfsError {
+    /// The file couldn't be read
+    Io(std::io::Error),
+
+    /// The file isn't a valid save file. The line starts at 1
+    Syntax { line: usize, reason: String },
+
+    /// A vessel is missing a value or has one that can't be read
+    Vessel { name: String, reason: String },
+}
+
+impl Display for SfsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SfsError::Io(e) => write!(f, "{e}"),
+            SfsError::Syntax { line, reason } => write!(f, "Line {line}: {reason}"),
+            SfsError::Vessel { name, reason } => write!(f, "The vessel \"{name}\" {reason}"),
+        }
+    }
+}
+
+impl Error for SfsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SfsError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SfsError {
+    fn from(e: std::io::Error) -> Self {
+        SfsError::Io(e)
+    }
+}
+
+/// A vessel stored in a save file
+#[derive(Clone, Debug)]
+pub struct SavedVessel {
+    name: String,
+    kind: String,
+    state: VesselState,
+    vessel: Vessel,
+}
+
+impl SavedVessel {
+    /// The name of the vessel
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the vessel, like `Ship`, `Lander`, `Probe` or `Debris`
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Where the vessel is
+    pub fn state(&self) -> &VesselState {
+        &self.state
+    }
+
+    /// The stages of the vessel with the propellant that is left
+    pub fn vessel(&self) -> &Vessel {
+        &self.vessel
+    }
+
+    /// Returns the node of the map the vessel is at, see [`DeltavMap::vessel_node`]
+    pub fn node<'a>(&self, map: &'a DeltavMap) -> Option<&'a str> {
+        map.vessel_node(&self.state)
+    }
+
+    /// Checks if the vessel can fly from its node to the target with the propellant that is left.
+    /// Returns `None` if the vessel isn't at a node of the map or there is no route
+    pub fn check_route_to(
+        &self,
+        map: &DeltavMap,
+        target: &str,
+        options: &CalcOptions,
+    ) -> Result<Option<FeasibilityReport>, NoSuchNodeError> {
+        let Some(node) = self.node(map) else {
+            return Ok(None);
+        };
+
+        let route = map.calculate_route(node, target, options)?;
+        Ok(route.map(|route| self.vessel.check_route(&route)))
+    }
+}
+
+/// Reads all vessels of a save file
+pub fn read_vessels(text: &str) -> Result<Vec<SavedVessel>, SfsError> {
+    let root = ConfigNode::parse(text)?;
+    let Some(flight_state) = root.node("GAME").and_then(|game| game.node("FLIGHTSTATE")) else {
+        return Ok(Vec::new());
+    };
+
+    flight_state.nodes("VESSEL").map(read_vessel).collect()
+}
+
+/// Reads all vessels of the save file at the path
+pub fn read_vessels_from_path(path: impl AsRef<Path>) -> Result<Vec<SavedVessel>, SfsError> {
+    read_vessels(&std::fs::read_to_string(path)?)
+}
+
+// A block of a save file with its values and child blocks
+#[derive(Default, Debug)]
+struct ConfigNode {
+    name: String,
+    values: Vec<(String, String)>,
+    children: Vec<ConfigNode>,
+}
+
+impl ConfigNode {
+    // Parses a whole file into an unnamed root block
+    fn parse(text: &str) -> Result<ConfigNode, SfsError> {
+        let syntax = |line: usize, reason: &str| SfsError::Syntax {
+            line: line + 1,
+            reason: reason.to_string(),
+        };
+
+        let mut stack = vec![ConfigNode::default()];
+        // The name of a block whose opening brace is on the next line
+        let mut pending: Option<String> = None;
+
+        for (line, text) in text.lines().enumerate() {
+            let text = text.split("//").next().unwrap_or_default().trim();
+
+            if text.is_empty() {
+                continue;
+            }
+
+            if text == "}" {
+                if pending.is_some() || stack.len() == 1 {
+                    return Err(syntax(line, "Unexpected \"}\""));
+                }
+                let node = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            } else if let Some(name) = text.strip_suffix('{') {
+                let name = match (pending.take(), name.trim()) {
+                    (Some(name), "") => name,
+                    (None, name) if !name.is_empty() => name.to_string(),
+                    _ => return Err(syntax(line, "Expected a block name before \"{\"")),
+                };
+                stack.push(ConfigNode {
+                    name,
+                    ..ConfigNode::default()
+                });
+            } else if pending.is_some() {
+                return Err(syntax(line, "Expected \"{\" after a block name"));
+            } else if let Some((key, value)) = text.split_once('=') {
+                if let Some(node) = stack.last_mut() {
+                    node.values
+                        .push((key.trim().to_string(), value.trim().to_string()));
+                }
+            } else {
+                pending = Some(text.to_string());
+            }
+        }
+
+        if pending.is_some() || stack.len() > 1 {
+            return Err(syntax(
+                text.lines().count().saturating_sub(1),
+                "Unexpected end of the file",
+            ));
+        }
+
+        Ok(stack.pop().unwrap_or_default())
+    }
+
+    // The first value with the key
+    fn value(&self, key: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // The first child block with the name
+    fn node(&self, name: &str) -> Option<&ConfigNode> {
+        self.children.iter().find(|node| node.name == name)
+    }
+
+    // All child blocks with the name
+    fn nodes<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ConfigNode> {
+        self.children.iter().filter(move |node| node.name == name)
+    }
+}
+
+// The names and radii in meters of the stock bodies by their index in the save file
+const BODIES: [(&str, f64); 17] = [
+    ("Kerbol", 261_600_000.0),
+    ("Kerbin", 600_000.0),
+    ("Mun", 200_000.0),
+    ("Minmus", 60_000.0),
+    ("Moho", 250_000.0),
+    ("Eve", 700_000.0),
+    ("Duna", 320_000.0),
+    ("Ike", 130_000.0),
+    ("Jool", 6_000_000.0),
+    ("Laythe", 500_000.0),
+    ("Vall", 300_000.0),
+    ("Bop", 65_000.0),
+    ("Tylo", 600_000.0),
+    ("Gilly", 13_000.0),
+    ("Pol", 44_000.0),
+    ("Dres", 138_000.0),
+    ("Eeloo", 210_000.0),
+];
+
+// The part names of the stock engines and the nicknames they have in the engine table
+const ENGINE_PARTS: [(&str, &str); 16] = [
+    ("microEngine", "Ant"),
+    ("liquidEngineMini", "Spark"),
+    ("omsEngine", "Puff"),
+    ("liquidEngine3", "Terrier"),
+    ("liquidEngine", "Reliant"),
+    ("liquidEngine2", "Swivel"),
+    ("LiquidEngineLV-T91", "Cheetah"),
+    ("toroidalAerospike", "Dart"),
+    ("liquidEngine2-2", "Poodle"),
+    ("engineLargeSkipper", "Skipper"),
+    ("liquidEngine1-2", "Mainsail"),
+    ("liquidEngineMainsail", "Mainsail"),
+    ("SSME", "Vector"),
+    ("Size3AdvancedEngine", "Rhino"),
+    ("nuclearEngine", "Nerv"),
+    ("ionEngine", "Dawn"),
+];
+
+// The mass in t of one unit of the resources an engine can burn or a vessel commonly carries
+const DENSITIES: [(&str, f64); 6] = [
+    ("LiquidFuel", 0.005),
+    ("Oxidizer", 0.005),
+    ("MonoPropellant", 0.004),
+    ("XenonGas", 0.0001),
+    ("SolidFuel", 0.0075),
+    ("Ore", 0.01),
+];
+
+// Finds the stock engine of a part, ignoring the ".v2" suffix of revised parts
+fn engine_of_part(part: &str) -> Option<&'static Engine> {
+    let part = part.strip_suffix(".v2").unwrap_or(part);
+    let (_, nickname) = ENGINE_PARTS.iter().find(|(name, _)| *name == part)?;
+    engines::find(nickname)
+}
+
+// The resources an engine burns
+fn propellants(engine: &Engine) -> &'static [&'static str] {
+    match engine.name() {
+        "Nerv" => &["LiquidFuel"],
+        "Dawn" => &["XenonGas"],
+        "Puff" => &["MonoPropellant"],
+        _ => &["LiquidFuel", "Oxidizer"],
+    }
+}
+
+// The mass in t of an amount of a resource. Unknown resources are weightless
+fn resource_mass(resource: &str, amount: f64) -> f64 {
+    DENSITIES
+        .iter()
+        .find(|(name, _)| *name == resource)
+        .map_or(0.0, |(_, density)| density * amount)
+}
+
+// Reads a value that has to be there and be a number
+fn number(node: &ConfigNode, key: &str, vessel: &str) -> Result<f64, SfsError> {
+    node.value(key)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| SfsError::Vessel {
+            name: vessel.to_string(),
+            reason: format!("has no valid \"{key}\" in its {}", node.name),
+        })
+}
+
+fn read_vessel(node: &ConfigNode) -> Result<SavedVessel, SfsError> {
+    let name = node.value("name").unwrap_or_default().to_string();
+    let error = |reason: String| SfsError::Vessel {
+        name: name.clone(),
+        reason,
+    };
+
+    let situation = match node.value("sit").unwrap_or_default() {
+        "PRELAUNCH" => Situation::PreLaunch,
+        "ORBITING" => Situation::Orbiting,
+        "SUB_ORBITAL" => Situation::SubOrbital,
+        "ESCAPING" => Situation::Escaping,
+        "FLYING" => Situation::Flying,
+        "LANDED" => Situation::Landed,
+        "SPLASHED" => Situation::Splashed,
+        "DOCKED" => Situation::Docked,
+        other => return Err(error(format!("has the unknown situation \"{other}\""))),
+    };
+
+    let orbit = node
+        .node("ORBIT")
+        .ok_or_else(|| error(String::from("has no ORBIT")))?;
+    let body = number(orbit, "REF", &name)?;
+    let (body, radius) = BODIES
+        .get(body as usize)
+        .filter(|_| body >= 0.0)
+        .ok_or_else(|| error(format!("orbits the unknown body {body}")))?;
+    let semi_major_axis = number(orbit, "SMA", &name)?;
+    let eccentricity = number(orbit, "ECC", &name)?;
+
+    let apoapsis = if eccentricity < 1.0 {
+        semi_major_axis * (1.0 + eccentricity) - radius
+    } else {
+        f64::INFINITY
+    };
+
+    Ok(SavedVessel {
+        kind: node.value("type").unwrap_or_default().to_string(),
+        state: VesselState {
+            body: body.to_string(),
+            situation,
+            periapsis: semi_major_axis * (1.0 - eccentricity) - radius,
+            apoapsis,
+        },
+        vessel: read_stages(node, &name)?,
+        name,
+    })
+}
+
+// The parts of a vessel that are dropped together
+#[derive(Default)]
+struct StageParts {
+    mass: f64,
+    resources: BTreeMap<String, f64>,
+    engines: Vec<&'static Engine>,
+}
+
+// Groups the parts by the stage they are decoupled in. Stages with a higher number are dropped
+// first
+fn read_stages(node: &ConfigNode, vessel: &str) -> Result<Vessel, SfsError> {
+    let mut groups: BTreeMap<i64, StageParts> = BTreeMap::new();
+
+    for part in node.nodes("PART") {
+        let group = groups
+            .entry(number(part, "dstg", vessel)? as i64)
+            .or_default();
+        group.mass += number(part, "mass", vessel)?;
+
+        if let Some(engine) = engine_of_part(part.value("name").unwrap_or_default()) {
+            group.engines.push(engine);
+        }
+
+        for resource in part.nodes("RESOURCE") {
+            *group
+                .resources
+                .entry(resource.value("name").unwrap_or_default().to_string())
+                .or_default() += number(resource, "amount", vessel)?;
+        }
+    }
+
+    let stages = groups.into_values().rev().map(|group| {
+        let resources: f64 = group
+            .resources
+            .iter()
+            .map(|(name, amount)| resource_mass(name, *amount))
+            .sum();
+        let wet_mass = group.mass + resources;
+
+        if group.engines.is_empty() {
+            return Stage::new(0.0, wet_mass, 0.0);
+        }
+
+        let propellant: f64 = group
+            .resources
+            .iter()
+            .filter(|(name, _)| {
+                group
+                    .engines
+                    .iter()
+                    .any(|engine| propellants(engine).contains(&name.as_str()))
+            })
+            .map(|(name, amount)| resource_mass(name, *amount))
+            .sum();
+        // Engines firing together have the isp of their combined thrust and propellant flow
+        let thrust: f64 = group.engines.iter().map(|engine| engine.thrust()).sum();
+        let flow: f64 = group
+            .engines
+            .iter()
+            .map(|engine| engine.thrust() / engine.isp_vacuum())
+            .sum();
+
+        Stage::new(thrust / flow, wet_mass - propellant, propellant)
+    });
+
+    Ok(Vessel::new(stages.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigNode, SfsError};
+    use crate::savefile;
+    use crate::{CalcOptions, DeltavMap, Situation};
+
+    const SAVE: &str = r#"
+GAME
+{
+    version = 1.12.5
+    FLIGHTSTATE
+    {
+        UT = 1234.5
+        // A lander with a Terrier and a full FL-T400 tank on the Mun
+        VESSEL
+        {
+            name = Mun Lander
+            type = Lander
+            sit = LANDED
+            ORBIT
+            {
+                SMA = 100000
+                ECC = 1
+                REF = 2
+            }
+            PART
+            {
+                name = mk1pod.v2
+                dstg = 0
+                mass = 0.8
+                RESOURCE
+                {
+                    name = MonoPropellant
+                    amount = 10
+                }
+            }
+            PART
+            {
+                name = fuelTank
+                dstg = 0
+                mass = 0.25
+                RESOURCE
+                {
+                    name = LiquidFuel
+                    amount = 180
+                }
+                RESOURCE
+                {
+                    name = Oxidizer
+                    amount = 220
+                }
+            }
+            PART
+            {
+                name = liquidEngine3.v2
+                dstg = 0
+                mass = 0.5
+            }
+        }
+        VESSEL
+        {
+            name = Relay
+            type = Relay
+            sit = ORBITING
+            ORBIT {
+                SMA = 700000
+                ECC = 0.01
+                REF = 1
+            }
+            PART {
+                name = probeCoreOcto.v2
+                dstg = 0
+                mass = 0.1
+            }
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_read_vessels() {
+        let vessels = savefile::read_vessels(SAVE).unwrap();
+        assert_eq!(vessels.len(), 2);
+
+        let lander = &vessels[0];
+        assert_eq!(lander.name(), "Mun Lander");
+        assert_eq!(lander.kind(), "Lander");
+        assert_eq!(lander.state().body, "Mun");
+        assert_eq!(lander.state().situation, Situation::Landed);
+
+        let stages = lander.vessel().stages();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(stages[0].isp(), 345.0);
+        assert!((stages[0].propellant_mass() - 2.0).abs() < 1e-9);
+        assert!((stages[0].dry_mass() - 1.59).abs() < 1e-9);
+
+        let relay = &vessels[1];
+        assert_eq!(relay.state().body, "Kerbin");
+        assert!((relay.state().periapsis - 93_000.0).abs() < 1e-6);
+        assert!((relay.state().apoapsis - 107_000.0).abs() < 1e-6);
+        assert_eq!(relay.vessel().stages()[0].isp(), 0.0);
+    }
+
+    #[test]
+    fn test_check_route_to() {
+        let map = DeltavMap::new_stock();
+        let vessels = savefile::read_vessels(SAVE).unwrap();
+        let options = CalcOptions::new().aerobrake(true);
+
+        let lander = &vessels[0];
+        assert_eq!(lander.node(&map), Some("Mun Surface"));
+        let report = lander
+            .check_route_to(&map, "Kerbin Surface", &options)
+            .unwrap()
+            .unwrap();
+        assert!(report.is_feasible());
+        let report = lander
+            .check_route_to(&map, "Eve Surface", &options)
+            .unwrap()
+            .unwrap();
+        assert!(!report.is_feasible());
+
+        let relay = &vessels[1];
+        assert_eq!(relay.node(&map), Some("Low Kerbin Orbit (80km)"));
+        assert!(relay.check_route_to(&map, "Nowhere", &options).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let root = ConfigNode::parse("A\n{\n  b = 1 // comment\n  C {\n d = 2\n}\n}").unwrap();
+        let a = root.node("A").unwrap();
+        assert_eq!(a.value("b"), Some("1"));
+        assert_eq!(a.node("C").unwrap().value("d"), Some("2"));
+        assert!(savefile::read_vessels("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let line = |text| match ConfigNode::parse(text) {
+            Err(SfsError::Syntax { line, .. }) => line,
+            other => panic!("Expected a syntax error, got {other:?}"),
+        };
+        assert_eq!(line("A\n{\n}\n}"), 4);
+        assert_eq!(line("A\nb = 1"), 2);
+        assert_eq!(line("A\n{\nb = 1"), 3);
+
+        let vessel =
+            "GAME { \n FLIGHTSTATE { \n VESSEL { \n name = Broken \n sit = LANDED \n } \n } \n }";
+        assert!(matches!(
+            savefile::read_vessels(vessel),
+            Err(SfsError::Vessel { name, .. }) if name == "Broken"
+        ));
+    }
+}
@@ -0,0 +1,540 @@
+use crate::menutree::{depth_error, PathError};
+use crate::{DeltavMap, EdgeInfo, MenuNode, MenuTree, Metadata, MAX_DEPTH};
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{
+    DeserializeSeed, EnumAccess, Error, IgnoredAny, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// The on-disk representation of a map. Nodes are only referred to by name, so the format doesn't
+// depend on how the graph is stored internally
+#[derive(Deserialize, Serialize)]
+struct MapFile<'a> {
+    menu_tree: TreeEntry,
+    #[serde(borrow)]
+    edges: Vec<(Text<'a>, Text<'a>, EdgeInfo)>,
+    #[serde(default)]
+    tags: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+// The representation of human-readable files. Instead of the menu tree, they may contain a flat
+// list of nodes declaring their category paths. They may also be in the old format that stored
+// the serialized graph
+#[derive(Deserialize)]
+struct CompatibleMapFile<'a> {
+    menu_tree: Option<TreeEntry>,
+    #[serde(borrow)]
+    nodes: Option<Vec<FlatNode<'a>>>,
+    #[serde(default, borrow)]
+    edges: Vec<(Text<'a>, Text<'a>, EdgeInfo)>,
+    graph: Option<UnGraph<Arc<str>, EdgeInfo>>,
+    #[serde(default)]
+    tags: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+// Text that is borrowed from the input if possible, so names that are only used to look up
+// nodes don't need to be allocated
+#[derive(Deserialize, Serialize)]
+#[serde(transparent)]
+struct Text<'a>(#[serde(borrow)] Cow<'a, str>);
+
+// A node of the flat node list
+#[derive(Deserialize)]
+struct FlatNode<'a> {
+    name: Arc<str>,
+    #[serde(borrow)]
+    category: Text<'a>,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+}
+
+// An entry of the menu tree in a map file. It is written recursively, so the depth of the tree is
+// checked before, and read with a depth that is checked while parsing
+#[derive(Serialize)]
+pub(crate) enum TreeEntry {
+    Category {
+        name: Arc<str>,
+        children: Vec<TreeEntry>,
+    },
+
+    Node(Arc<str>),
+
+    // Nodes of the old format, pointing at a node of the serialized graph
+    #[serde(skip_serializing)]
+    EndNode {
+        name: Arc<str>,
+        index: NodeIndex,
+    },
+}
+
+// The variants of the tree entries. Categories used to be called middle nodes
+#[derive(Deserialize)]
+#[serde(variant_identifier)]
+enum EntryKind {
+    #[serde(alias = "MiddleNode")]
+    Category,
+    Node,
+    EndNode,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum CategoryField {
+    Name,
+    Children,
+    #[serde(other)]
+    Other,
+}
+
+// The fields of a node of the old format
+#[derive(Deserialize)]
+struct LegacyNode {
+    name: Arc<str>,
+    index: NodeIndex,
+}
+
+// Reads an entry of the menu tree at the given depth below the root
+struct EntrySeed {
+    depth: usize,
+}
+
+// Reads the children of a category at the given depth below the root
+struct ChildrenSeed {
+    depth: usize,
+}
+
+// Reads the fields of a struct variant with the derived implementation of the struct
+struct FieldsVisitor<T>(PhantomData<T>);
+
+impl<'de> Deserialize<'de> for TreeEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        EntrySeed { depth: 0 }.deserialize(deserializer)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for EntrySeed {
+    type Value = TreeEntry;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<TreeEntry, D::Error> {
+        if self.depth > MAX_DEPTH {
+            return Err(D::Error::custom(depth_error()));
+        }
+
+        deserializer.deserialize_enum("TreeEntry", &["Category", "Node", "EndNode"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntrySeed {
+    type Value = TreeEntry;
+
+    fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "a category or node")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<TreeEntry, A::Error> {
+        let (kind, variant) = data.variant()?;
+        match kind {
+            EntryKind::Category => variant.struct_variant(&["name", "children"], self),
+            EntryKind::Node => variant.newtype_variant().map(TreeEntry::Node),
+            EntryKind::EndNode => variant
+                .struct_variant(&["name", "index"], FieldsVisitor(PhantomData))
+                .map(|LegacyNode { name, index }| TreeEntry::EndNode { name, index }),
+        }
+    }
+
+    // The fields of categories in formats that store structs as sequences
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TreeEntry, A::Error> {
+        let name = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let children = seq
+            .next_element_seed(ChildrenSeed { depth: self.depth })?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+        Ok(TreeEntry::Category { name, children })
+    }
+
+    // The fields of categories in formats that store structs as maps
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TreeEntry, A::Error> {
+        let (mut name, mut children) = (None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                CategoryField::Name => name = Some(map.next_value()?),
+                CategoryField::Children => {
+                    children = Some(map.next_value_seed(ChildrenSeed { depth: self.depth })?)
+                }
+                CategoryField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(TreeEntry::Category {
+            name: name.ok_or_else(|| A::Error::missing_field("name"))?,
+            children: children.ok_or_else(|| A::Error::missing_field("children"))?,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ChildrenSeed {
+    type Value = Vec<TreeEntry>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ChildrenSeed {
+    type Value = Vec<TreeEntry>;
+
+    fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "a list of categories and nodes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut children = Vec::new();
+        let seed = || EntrySeed {
+            depth: self.depth + 1,
+        };
+        while let Some(child) = seq.next_element_seed(seed())? {
+            children.push(child);
+        }
+
+        Ok(children)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for FieldsVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "the fields of a struct")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<T, A::Error> {
+        T::deserialize(SeqAccessDeserializer::new(seq))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+        T::deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+impl From<MenuNode<'_>> for TreeEntry {
+    fn from(node: MenuNode) -> Self {
+        match node.id() {
+            None => TreeEntry::Category {
+                name: node.shared_name().clone(),
+                children: node.children().map(TreeEntry::from).collect(),
+            },
+            Some(_) => TreeEntry::Node(node.shared_name().clone()),
+        }
+    }
+}
+
+impl TreeEntry {
+    // Builds the menu tree, adding the nodes to the graph
+    fn build(
+        self,
+        graph: &mut UnGraph<Arc<str>, EdgeInfo>,
+        indices: &mut BTreeMap<Arc<str>, NodeIndex>,
+    ) -> Result<MenuTree, String> {
+        match self {
+            TreeEntry::Category { name, children } => {
+                let mut tree = MenuTree::from_node(name, None);
+                for child in children {
+                    tree = tree.with_child(child.build(graph, indices)?);
+                }
+                Ok(tree)
+            }
+            TreeEntry::Node(name) => {
+                let index = graph.add_node(name.clone());
+                indices.insert(name.clone(), index);
+                Ok(MenuTree::from_node(name, Some(index.into())))
+            }
+            TreeEntry::EndNode { name, .. } => Err(format!(
+                "The node \"{name}\" has an index, but the map has no graph"
+            )),
+        }
+    }
+
+    // Builds the menu tree of the old format, where the nodes already are in the graph
+    pub(crate) fn build_legacy(self) -> Result<MenuTree, String> {
+        match self {
+            TreeEntry::Category { name, children } => {
+                let mut tree = MenuTree::from_node(name, None);
+                for child in children {
+                    tree = tree.with_child(child.build_legacy()?);
+                }
+                Ok(tree)
+            }
+            TreeEntry::Node(name) => Err(format!(
+                "The node \"{name}\" has no index, but the map has a graph"
+            )),
+            TreeEntry::EndNode { name, index } => Ok(MenuTree::from_node(name, Some(index.into()))),
+        }
+    }
+}
+
+// Adds the edges between the named nodes to the graph
+fn add_edges(
+    graph: &mut UnGraph<Arc<str>, EdgeInfo>,
+    indices: &BTreeMap<Arc<str>, NodeIndex>,
+    edges: Vec<(Text, Text, EdgeInfo)>,
+) -> Result<(), String> {
+    for (Text(from), Text(to), edge) in edges {
+        let index = |name: &str| match indices.get(name) {
+            Some(index) => Ok(*index),
+            None => Err(format!(
+                "The edge from \"{from}\" to \"{to}\" refers to the unknown node \"{name}\""
+            )),
+        };
+        graph.add_edge(index(&from)?, index(&to)?, edge);
+    }
+
+    Ok(())
+}
+
+impl MapFile<'_> {
+    fn into_map(self) -> Result<DeltavMap, String> {
+        let mut graph = UnGraph::new_undirected();
+        let mut indices = BTreeMap::new();
+        let menu_tree = self.menu_tree.build(&mut graph, &mut indices)?;
+        add_edges(&mut graph, &indices, self.edges)?;
+
+        Ok(DeltavMap {
+            menu_tree,
+            graph,
+            tags: self.tags,
+            metadata: self.metadata,
+        })
+    }
+}
+
+impl CompatibleMapFile<'_> {
+    fn into_map(mut self) -> Result<DeltavMap, String> {
+        match (self.menu_tree, self.nodes, self.graph) {
+            (Some(menu_tree), None, None) => MapFile {
+                menu_tree,
+                edges: self.edges,
+                tags: self.tags,
+                metadata: self.metadata,
+            }
+            .into_map(),
+            (Some(menu_tree), None, Some(graph)) => Ok(DeltavMap {
+                menu_tree: menu_tree.build_legacy()?,
+                graph,
+                tags: self.tags,
+                metadata: self.metadata,
+            }),
+            (None, Some(nodes), None) => {
+                let mut graph = UnGraph::new_undirected();
+                let mut indices = BTreeMap::new();
+                let mut menu_tree = None;
+
+                for node in nodes {
+                    let index = graph.add_node(node.name.clone());
+                    let end_node = MenuTree::from_node(node.name.clone(), Some(index.into()));
+
+                    MenuTree::insert_at_path(&mut menu_tree, &node.category.0, end_node).map_err(
+                        |e| match e {
+                            PathError::Empty => {
+                                format!("The node \"{}\" has no category", node.name)
+                            }
+                            PathError::DifferentRoot(root) => format!(
+                                "The category of the node \"{}\" doesn't start with \"{root}\"",
+                                node.name
+                            ),
+                            PathError::TooDeep => depth_error(),
+                        },
+                    )?;
+                    if !node.tags.is_empty() {
+                        self.tags
+                            .entry(node.name.to_string())
+                            .or_default()
+                            .extend(node.tags);
+                    }
+                    indices.insert(node.name, index);
+                }
+                add_edges(&mut graph, &indices, self.edges)?;
+
+                Ok(DeltavMap {
+                    menu_tree: menu_tree.ok_or("The map has no nodes")?,
+                    graph,
+                    tags: self.tags,
+                    metadata: self.metadata,
+                })
+            }
+            (None, Some(_), Some(_)) => Err(String::from("A map with a graph needs a menu tree")),
+            (Some(_), Some(_), _) => Err(String::from(
+                "The map can't have both a menu tree and a node list",
+            )),
+            (None, None, _) => Err(String::from("The map needs a menu tree or a node list")),
+        }
+    }
+}
+
+impl Serialize for DeltavMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The menu tree is written recursively
+        if self.menu_tree.depth() > MAX_DEPTH {
+            return Err(S::Error::custom(depth_error()));
+        }
+
+        let edges = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                (
+                    Text(Cow::Borrowed(&self.graph[edge.source()])),
+                    Text(Cow::Borrowed(&self.graph[edge.target()])),
+                    edge.weight().clone(),
+                )
+            })
+            .collect();
+
+        MapFile {
+            menu_tree: TreeEntry::from(self.menu_tree.root()),
+            edges,
+            tags: self.tags.clone(),
+            metadata: self.metadata.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// A map that was deserialized without being validated, so the loaders can validate it with their
+// own options
+pub(crate) struct UncheckedMap(pub(crate) DeltavMap);
+
+impl<'de> Deserialize<'de> for UncheckedMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = if deserializer.is_human_readable() {
+            CompatibleMapFile::deserialize(deserializer)?.into_map()
+        } else {
+            MapFile::deserialize(deserializer)?.into_map()
+        };
+
+        map.map(UncheckedMap).map_err(D::Error::custom)
+    }
+}
+
+// Deserialized maps are always validated, so a broken map can't be used by accident
+impl<'de> Deserialize<'de> for DeltavMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let UncheckedMap(map) = UncheckedMap::deserialize(deserializer)?;
+        map.validate().map_err(D::Error::custom)?;
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::DeltavMap;
+    use std::fs::File;
+
+    #[test]
+    fn test_legacy_format() {
+        let file = File::open("test_res/legacy.json").unwrap();
+        let map: DeltavMap = serde_json::from_reader(file).unwrap();
+
+        assert_eq!(map, get_test_map());
+    }
+
+    #[test]
+    fn test_flat_format() {
+        let file = File::open("test_res/flat.json").unwrap();
+        let map: DeltavMap = serde_json::from_reader(file).unwrap();
+
+        assert_eq!(map, get_test_map());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(get_test_map()).unwrap();
+
+        assert!(json.get("graph").is_none());
+        assert_eq!(json["edges"][0], serde_json::json!(["Node1", "Node2", 900]));
+        assert_eq!(
+            json["menu_tree"]["Category"]["children"][1],
+            serde_json::json!({"Node": "Node3"})
+        );
+    }
+
+    #[test]
+    fn test_escaped_names() {
+        let json = r#"{
+            "menu_tree": {"Category": {"name": "Category1", "children": [
+                {"Node": "Node1"}, {"Node": "Node\"2\""}
+            ]}},
+            "edges": [["Node\u0031", "Node\"2\"", 900]]
+        }"#;
+        let map: DeltavMap = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            map.calculate_delta_v("Node1", "Node\"2\"").unwrap(),
+            Some(900)
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let invalid = [
+            (
+                r#"{"menu_tree": {"Category": {"name": "Category1", "children": [
+                    {"Node": "Node1"}, {"Node": "Node2"}
+                ]}}, "edges": [["Node1", "Node2", -900]]}"#,
+                "The edge between \"Node1\" and \"Node2\" costs zero or less",
+            ),
+            (
+                r#"{"menu_tree": {"Category": {"name": "Category1", "children": [
+                    {"Node": "Node1"}, {"Node": "Node2"}
+                ]}}, "edges": [["Node1", "Node2", 0]]}"#,
+                "The edge between \"Node1\" and \"Node2\" costs zero or less",
+            ),
+            (
+                r#"{"menu_tree": {"Category": {"name": "Category1", "children": [
+                    {"Node": "Node1"}, {"Node": "Node1"}
+                ]}}, "edges": []}"#,
+                "multiple nodes or categories with the name \"Node1\"",
+            ),
+        ];
+        for (json, message) in invalid {
+            let error = serde_json::from_str::<DeltavMap>(json).unwrap_err();
+            assert!(error.to_string().contains(message), "{error}");
+        }
+
+        let mut json: serde_json::Value =
+            serde_json::from_reader(File::open("test_res/legacy.json").unwrap()).unwrap();
+        json["menu_tree"]["MiddleNode"]["children"][1]["EndNode"]["index"] = 17.into();
+        let error = serde_json::from_value::<DeltavMap>(json).unwrap_err();
+        assert!(error.to_string().contains("has the index 17"), "{error}");
+    }
+
+    #[test]
+    fn test_unknown_node() {
+        let json = r#"{"menu_tree": {"Node": "Node1"}, "edges": [["Node1", "Node2", 900]]}"#;
+        let error = serde_json::from_str::<DeltavMap>(json).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("refers to the unknown node \"Node2\""));
+    }
+}
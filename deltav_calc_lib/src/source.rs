@@ -0,0 +1,213 @@
+//! Providers of maps, so applications can offer maps from different places in the same way
+//!
+//! # Example
+//! ```
+//! use deltav_calc::source::{FnSource, MapRegistry};
+//! use deltav_calc::DeltavMap;
+//!
+//! let mut registry = MapRegistry::with_stock();
+//! registry.register(FnSource::new("Rescaled", || Ok(DeltavMap::new_stock().scaled(2.5))));
+//!
+//! let names: Vec<&str> = registry.names().collect();
+//! assert_eq!(names, ["Stock", "Rescaled"]);
+//! let map = registry.load("Rescaled").unwrap().unwrap();
+//! ```
+
+use crate::{DeltavMap, LoadError};
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Something that provides a map, like the builtin stock map, a file or a generator
+pub trait MapSource {
+    /// The name of the map, for example to show it in a map picker
+    fn name(&self) -> &str;
+
+    /// Loads the map
+    fn load(&self) -> Result<DeltavMap, LoadError>;
+}
+
+/// Provides the builtin stock map
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Default, Debug)]
+pub struct StockSource;
+
+impl MapSource for StockSource {
+    fn name(&self) -> &str {
+        "Stock"
+    }
+
+    fn load(&self) -> Result<DeltavMap, LoadError> {
+        Ok(DeltavMap::new_stock())
+    }
+}
+
+/// Provides a map loaded from a file with [`from_path`](DeltavMap::from_path)
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FileSource {
+    name: String,
+    path: PathBuf,
+}
+
+impl FileSource {
+    /// Creates a source for the file, named after the file without the extension
+    pub fn new(path: &Path) -> FileSource {
+        let name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        FileSource::with_name(&name, path)
+    }
+
+    /// Creates a source for the file with the given name
+    pub fn with_name(name: &str, path: &Path) -> FileSource {
+        FileSource {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl MapSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn load(&self) -> Result<DeltavMap, LoadError> {
+        DeltavMap::from_path(&self.path)
+    }
+}
+
+/// Provides a map created by a function, for example a generated or downloaded one
+pub struct FnSource<F> {
+    name: String,
+    function: F,
+}
+
+impl<F: Fn() -> Result<DeltavMap, LoadError>> FnSource<F> {
+    /// Creates a source with the given name, calling the function whenever the map is loaded
+    pub fn new(name: &str, function: F) -> FnSource<F> {
+        FnSource {
+            name: name.to_string(),
+            function,
+        }
+    }
+}
+
+impl<F: Fn() -> Result<DeltavMap, LoadError>> MapSource for FnSource<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn load(&self) -> Result<DeltavMap, LoadError> {
+        (self.function)()
+    }
+}
+
+impl<F> Debug for FnSource<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnSource")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// A list of map sources, in the order they were registered
+#[derive(Default)]
+pub struct MapRegistry {
+    sources: Vec<Box<dyn MapSource>>,
+}
+
+impl MapRegistry {
+    /// Creates an empty registry
+    pub fn new() -> MapRegistry {
+        MapRegistry::default()
+    }
+
+    /// Creates a registry containing the [`StockSource`]
+    pub fn with_stock() -> MapRegistry {
+        let mut registry = MapRegistry::new();
+        registry.register(StockSource);
+        registry
+    }
+
+    /// Adds the source. A source that was registered earlier with the same name is replaced, but
+    /// keeps its position
+    pub fn register(&mut self, source: impl MapSource + 'static) {
+        match self.sources.iter().position(|s| s.name() == source.name()) {
+            Some(position) => self.sources[position] = Box::new(source),
+            None => self.sources.push(Box::new(source)),
+        }
+    }
+
+    /// Registers a [`FileSource`] for every map installed in the
+    /// [`maps_dir`](crate::discovery::maps_dir)
+    #[cfg(feature = "discovery")]
+    pub fn register_installed(&mut self) {
+        if let Some(dir) = crate::discovery::maps_dir() {
+            for path in crate::discovery::map_files_in(&dir) {
+                self.register(FileSource::new(&path));
+            }
+        }
+    }
+
+    /// All registered sources
+    pub fn sources(&self) -> impl Iterator<Item = &dyn MapSource> {
+        self.sources.iter().map(|source| source.as_ref())
+    }
+
+    /// The names of all registered sources
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sources().map(|source| source.name())
+    }
+
+    /// Returns the source with the given name, if there is one
+    pub fn get(&self, name: &str) -> Option<&dyn MapSource> {
+        self.sources().find(|source| source.name() == name)
+    }
+
+    /// Loads the map of the source with the given name. Returns `None` if there is no such source
+    pub fn load(&self, name: &str) -> Option<Result<DeltavMap, LoadError>> {
+        self.get(name).map(|source| source.load())
+    }
+}
+
+impl Debug for MapRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::{FileSource, FnSource, MapRegistry, MapSource};
+    use crate::tests::get_test_map;
+    use crate::DeltavMap;
+    use std::path::Path;
+
+    #[test]
+    fn test_registry() {
+        let mut registry = MapRegistry::with_stock();
+        registry.register(FileSource::new(Path::new("test_res/test.json")));
+        registry.register(FnSource::new("Stock", || Ok(get_test_map())));
+
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["Stock", "test"]);
+        assert_eq!(registry.load("Stock").unwrap().unwrap(), get_test_map());
+        assert_eq!(registry.load("test").unwrap().unwrap(), get_test_map());
+        assert!(registry.load("Other").is_none());
+    }
+
+    #[test]
+    fn test_file_source() {
+        let source = FileSource::new(Path::new("test_res/missing.json"));
+        assert_eq!(source.name(), "missing");
+        assert!(source.load().is_err());
+
+        let source = FileSource::with_name("Test", Path::new("test_res/test.deltav"));
+        assert_eq!(source.name(), "Test");
+        assert!(source.load().is_ok_and(|map| map != DeltavMap::new_stock()));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{DeltavMap, MapGenerator, MenuTree};
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::{any, BoxedStrategy, Strategy};
+
+// The generated maps are kept small, so property tests stay fast
+impl<'a> Arbitrary<'a> for DeltavMap {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let min_cost = u.int_in_range(1..=5000)?;
+
+        Ok(MapGenerator::new()
+            .seed(u.arbitrary()?)
+            .node_count(u.int_in_range(0..=64)?)
+            .depth(u.int_in_range(0..=4)?)
+            .branching(u.int_in_range(1..=4)?)
+            .cost_range(min_cost, u.int_in_range(min_cost..=6000)?)
+            .extra_edges(u.int_in_range(0..=32)?)
+            .generate())
+    }
+}
+
+impl<'a> Arbitrary<'a> for MenuTree {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(DeltavMap::arbitrary(u)?.menu_tree)
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for DeltavMap {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<DeltavMap>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            any::<u64>(),
+            0..=64usize,
+            0..=4usize,
+            1..=4usize,
+            (1..=5000i32, 0..=1000i32),
+            0..=32usize,
+        )
+            .prop_map(|(seed, nodes, depth, branching, (min, spread), extra)| {
+                MapGenerator::new()
+                    .seed(seed)
+                    .node_count(nodes)
+                    .depth(depth)
+                    .branching(branching)
+                    .cost_range(min, min + spread)
+                    .extra_edges(extra)
+                    .generate()
+            })
+            .boxed()
+    }
+}
+
+impl proptest::arbitrary::Arbitrary for MenuTree {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<MenuTree>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<DeltavMap>().prop_map(|map| map.menu_tree).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DeltavMap, MenuTree};
+    use arbitrary::{Arbitrary, Unstructured};
+    use proptest::prelude::{any, proptest};
+
+    #[test]
+    fn test_arbitrary() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let map = DeltavMap::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert!(map.validate().is_ok());
+
+        let tree = MenuTree::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(tree.name(), "Generated");
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest(map in any::<DeltavMap>()) {
+            assert!(map.validate().is_ok());
+        }
+    }
+}
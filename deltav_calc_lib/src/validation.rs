@@ -0,0 +1,315 @@
+use crate::{DeltavMap, EdgeInfo, NodePayload};
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use petgraph::visit::EdgeRef;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// This error is raised when a map is structurally invalid
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ValidationError {
+    /// There are multiple nodes or categories with the same name, which makes searching for them
+    /// ambiguous
+    DuplicateName(String),
+
+    /// The index of the node in the menu tree points outside the graph
+    InvalidIndex { node: String, index: usize },
+
+    /// The index of the node in the menu tree points at a node of the graph with another name
+    LabelMismatch { node: String, label: String },
+
+    /// The edge between the two nodes has a cost of zero or less, or a negative aerobraking cost,
+    /// which breaks the route calculation
+    InvalidWeight { from: String, to: String },
+
+    /// The content hash stored in the metadata doesn't match the content of the map, so the file
+    /// was changed or corrupted
+    HashMismatch { expected: String, actual: String },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::DuplicateName(name) => write!(
+                f,
+                "There are multiple nodes or categories with the name \"{name}\""
+            ),
+            ValidationError::InvalidIndex { node, index } => write!(
+                f,
+                "The node \"{node}\" has the index {index}, which isn't in the graph"
+            ),
+            ValidationError::LabelMismatch { node, label } => write!(
+                f,
+                "The node \"{node}\" points at the node \"{label}\" of the graph"
+            ),
+            ValidationError::InvalidWeight { from, to } => write!(
+                f,
+                "The edge between \"{from}\" and \"{to}\" costs zero or less or has a negative aerobraking cost"
+            ),
+            ValidationError::HashMismatch { expected, actual } => write!(
+                f,
+                "The map has the content hash {actual}, but its metadata expects {expected}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ValidationError {}
+
+/// Options influencing which checks are run when validating a map
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Default, Debug)]
+pub struct ValidationOptions {
+    pub(crate) allow_nonpositive_weights: bool,
+    pub(crate) ignore_content_hash: bool,
+    pub(crate) skip_all: bool,
+}
+
+impl ValidationOptions {
+    /// Creates the default options, which run all checks
+    pub fn new() -> ValidationOptions {
+        ValidationOptions::default()
+    }
+
+    /// Sets if edges may cost zero or less. Only use this if you know the route calculation can
+    /// handle your map
+    pub fn allow_nonpositive_weights(mut self, allow: bool) -> ValidationOptions {
+        self.allow_nonpositive_weights = allow;
+        self
+    }
+
+    /// Sets if the content hash stored in the metadata is ignored instead of being checked
+    pub fn ignore_content_hash(mut self, ignore: bool) -> ValidationOptions {
+        self.ignore_content_hash = ignore;
+        self
+    }
+
+    /// Sets if all checks are skipped, for example to load a broken map and list its problems
+    /// with [`DeltavMap::validation_errors`]. Don't calculate routes on maps that weren't checked
+    pub fn skip_all(mut self, skip: bool) -> ValidationOptions {
+        self.skip_all = skip;
+        self
+    }
+}
+
+impl EdgeInfo {
+    // Checks that the edge costs more than zero and doesn't have a negative aerobraking cost
+    pub(crate) fn has_valid_weights(&self) -> bool {
+        let costs = [Some(self.cost), self.min, self.max];
+
+        costs.into_iter().flatten().all(|cost| cost > 0)
+            && self.aerobrake.is_none_or(|cost| cost >= 0)
+    }
+}
+
+impl<N: NodePayload> DeltavMap<N> {
+    /// Checks that the map is structurally valid. Maps loaded with
+    /// [`from_path`](DeltavMap::from_path) or [`from_reader`](DeltavMap::from_reader) or
+    /// deserialized with serde are validated automatically
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_options(&ValidationOptions::new())
+    }
+
+    /// Checks that the map is structurally valid, skipping the checks disabled in the options
+    pub fn validate_with_options(
+        &self,
+        options: &ValidationOptions,
+    ) -> Result<(), ValidationError> {
+        match self.validation_errors(options).into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs all checks that aren't disabled in the options and returns every problem that was
+    /// found, instead of stopping at the first one like [`validate`](DeltavMap::validate)
+    pub fn validation_errors(&self, options: &ValidationOptions) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if options.skip_all {
+            return errors;
+        }
+
+        let mut names = BTreeSet::new();
+        for name in self.menu_tree.names() {
+            if !names.insert(name) {
+                errors.push(ValidationError::DuplicateName(name.to_string()));
+            }
+        }
+
+        for (_, node) in self.menu_tree.end_nodes() {
+            let index = node.index();
+            match self.graph.node_weight(index) {
+                None => errors.push(ValidationError::InvalidIndex {
+                    node: node.name().to_string(),
+                    index: index.index(),
+                }),
+                Some(label) if label.name() != node.name() => {
+                    errors.push(ValidationError::LabelMismatch {
+                        node: node.name().to_string(),
+                        label: label.name().to_string(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        if !options.allow_nonpositive_weights {
+            for edge in self.graph.edge_references() {
+                if !edge.weight().has_valid_weights() {
+                    errors.push(ValidationError::InvalidWeight {
+                        from: self.graph[edge.source()].name().to_string(),
+                        to: self.graph[edge.target()].name().to_string(),
+                    });
+                }
+            }
+        }
+
+        let expected = self.metadata.content_hash();
+        if let (Some(expected), false) = (expected, options.ignore_content_hash) {
+            let actual = format!("{:016x}", self.content_hash());
+            if !expected.eq_ignore_ascii_case(&actual) {
+                errors.push(ValidationError::HashMismatch {
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::{DeltavMap, EdgeInfo, LoadError, MapFormat, ValidationError, ValidationOptions};
+    use std::fs::File;
+
+    // Loads the test map after changing the index of the given node in the menu tree
+    fn load_with_index(node: usize, index: usize) -> Result<DeltavMap, LoadError> {
+        let file = File::open("test_res/legacy.json").unwrap();
+        let mut json: serde_json::Value = serde_json::from_reader(file).unwrap();
+        json["menu_tree"]["MiddleNode"]["children"][node]["EndNode"]["index"] = index.into();
+
+        DeltavMap::from_reader(json.to_string().as_bytes())
+    }
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(get_test_map().validate(), Ok(()));
+        assert_eq!(DeltavMap::new_stock().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_duplicate_name() {
+        let file = File::open("test_res/duplicate_name.json").unwrap();
+        let result = DeltavMap::from_reader(file);
+
+        match result {
+            Err(LoadError::Invalid { error, .. }) => {
+                assert_eq!(error, ValidationError::DuplicateName(String::from("Node2")))
+            }
+            _ => panic!("The duplicate name wasn't detected"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_weight() {
+        let mut test_map = get_test_map();
+        let edge = test_map.graph.edge_indices().next_back().unwrap();
+        test_map.graph[edge] = EdgeInfo::new(-50);
+
+        assert_eq!(
+            test_map.validate(),
+            Err(ValidationError::InvalidWeight {
+                from: String::from("Node3"),
+                to: String::from("Node4"),
+            })
+        );
+
+        let mut json = Vec::new();
+        test_map.to_writer(&mut json).unwrap();
+        let options = ValidationOptions::new().allow_nonpositive_weights(true);
+        assert!(DeltavMap::from_reader(json.as_slice()).is_err());
+        assert!(
+            DeltavMap::from_reader_with_options(json.as_slice(), MapFormat::Json, &options).is_ok()
+        );
+
+        test_map.graph[edge] = EdgeInfo::new(50).with_aerobrake(0);
+        assert_eq!(test_map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut test_map = get_test_map();
+        for edge in test_map.graph.edge_weights_mut() {
+            *edge = EdgeInfo::new(0);
+        }
+        let options = ValidationOptions::new();
+
+        assert_eq!(test_map.validation_errors(&options).len(), 3);
+        assert!(test_map
+            .validation_errors(&options.allow_nonpositive_weights(true))
+            .is_empty());
+
+        let file = File::open("test_res/duplicate_name.json").unwrap();
+        let options = ValidationOptions::new().skip_all(true);
+        let map = DeltavMap::from_reader_with_options(file, MapFormat::Json, &options).unwrap();
+        assert_eq!(
+            map.validation_errors(&ValidationOptions::new()),
+            vec![ValidationError::DuplicateName(String::from("Node2"))]
+        );
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        match load_with_index(1, 7) {
+            Err(LoadError::Invalid { error, .. }) => assert_eq!(
+                error,
+                ValidationError::InvalidIndex {
+                    node: String::from("Node3"),
+                    index: 7,
+                }
+            ),
+            _ => panic!("The invalid index wasn't detected"),
+        }
+
+        match load_with_index(1, 3) {
+            Err(LoadError::Invalid { error, .. }) => assert_eq!(
+                error,
+                ValidationError::LabelMismatch {
+                    node: String::from("Node3"),
+                    label: String::from("Node4"),
+                }
+            ),
+            _ => panic!("The wrong label wasn't detected"),
+        }
+
+        assert!(load_with_index(1, 2).is_ok());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let mut test_map = get_test_map();
+        test_map.embed_content_hash();
+        let mut json = Vec::new();
+        test_map.to_writer(&mut json).unwrap();
+        assert!(DeltavMap::from_reader(json.as_slice()).is_ok());
+
+        let json = String::from_utf8(json).unwrap().replace("900", "901");
+        match DeltavMap::from_reader(json.as_bytes()) {
+            Err(LoadError::Invalid { error, .. }) => {
+                assert!(matches!(error, ValidationError::HashMismatch { .. }))
+            }
+            _ => panic!("The changed content wasn't detected"),
+        }
+
+        let options = ValidationOptions::new().ignore_content_hash(true);
+        assert!(
+            DeltavMap::from_reader_with_options(json.as_bytes(), MapFormat::Json, &options).is_ok()
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// The kinds of hazards a route can run into
+#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum WarningKind {
+    /// The route enters an atmosphere
+    AtmosphericEntry,
+
+    /// The route needs a heat shield to survive
+    HeatShieldRequired,
+
+    /// The route lands on or launches from a body with a high surface gravity
+    HighGravity,
+}
+
+impl WarningKind {
+    /// All kinds of warnings
+    pub const ALL: [WarningKind; 3] = [
+        WarningKind::AtmosphericEntry,
+        WarningKind::HeatShieldRequired,
+        WarningKind::HighGravity,
+    ];
+
+    /// The tag that marks nodes and edges with this hazard
+    pub fn tag(&self) -> &'static str {
+        match self {
+            WarningKind::AtmosphericEntry => "atmosphere",
+            WarningKind::HeatShieldRequired => "requires_heatshield",
+            WarningKind::HighGravity => "high_gravity",
+        }
+    }
+
+    /// Returns the kind of warning the tag stands for, if any
+    pub fn from_tag(tag: &str) -> Option<WarningKind> {
+        WarningKind::ALL.into_iter().find(|kind| kind.tag() == tag)
+    }
+}
+
+impl Display for WarningKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WarningKind::AtmosphericEntry => {
+                write!(f, "The route enters an atmosphere, bring parachutes")
+            }
+            WarningKind::HeatShieldRequired => write!(f, "Bring a heat shield"),
+            WarningKind::HighGravity => {
+                write!(f, "High gravity, make sure the craft has enough thrust")
+            }
+        }
+    }
+}
+
+/// A hazard encountered while flying a leg of a [`Route`](crate::Route)
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct RouteWarning {
+    pub(crate) kind: WarningKind,
+    pub(crate) leg: usize,
+}
+
+impl RouteWarning {
+    /// The kind of hazard
+    pub fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    /// The index of the leg the hazard is encountered on
+    pub fn leg(&self) -> usize {
+        self.leg
+    }
+}
+
+impl Display for RouteWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Leg {}: {}", self.leg + 1, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WarningKind;
+
+    #[test]
+    fn test_tags() {
+        for kind in WarningKind::ALL {
+            assert_eq!(WarningKind::from_tag(kind.tag()), Some(kind));
+        }
+
+        assert_eq!(WarningKind::from_tag("has_isru"), None);
+    }
+}
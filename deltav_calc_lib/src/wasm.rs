@@ -0,0 +1,102 @@
+//! Bindings to load maps and calculate routes from JavaScript with `wasm-bindgen`
+//!
+//! The map is exported as the `DeltavMap` class:
+//! ```js
+//! const map = DeltavMap.stock();
+//! const deltaV = map.calculateDeltaV("Kerbin Surface", "Mun Surface");
+//! ```
+
+use crate::menutree::depth_error;
+use crate::{DeltavMap, MenuNode, MAX_DEPTH};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A delta-v map that can be used from JavaScript
+#[wasm_bindgen(js_name = DeltavMap)]
+#[derive(Debug, Clone)]
+pub struct WasmMap {
+    map: DeltavMap,
+}
+
+// An entry of the menu tree as it is passed to JavaScript. Categories have children, nodes don't
+#[derive(Serialize)]
+struct JsMenuEntry<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<JsMenuEntry<'a>>>,
+}
+
+impl<'a> From<MenuNode<'a>> for JsMenuEntry<'a> {
+    fn from(node: MenuNode<'a>) -> Self {
+        JsMenuEntry {
+            name: node.name(),
+            children: node
+                .is_category()
+                .then(|| node.children().map(JsMenuEntry::from).collect()),
+        }
+    }
+}
+
+#[wasm_bindgen(js_class = DeltavMap)]
+impl WasmMap {
+    /// Returns the stock map
+    pub fn stock() -> WasmMap {
+        WasmMap {
+            map: DeltavMap::new_stock(),
+        }
+    }
+
+    /// Reads a map from the text of a JSON file
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(text: &str) -> Result<WasmMap, JsError> {
+        Ok(WasmMap {
+            map: DeltavMap::from_reader(text.as_bytes())?,
+        })
+    }
+
+    /// Returns the menu tree as nested objects with a `name`. Categories also have an array of
+    /// `children`. Fails if the tree is nested deeper than `MAX_DEPTH`
+    #[wasm_bindgen(js_name = menuTree)]
+    pub fn menu_tree(&self) -> Result<JsValue, JsError> {
+        // The entries are converted recursively
+        let tree = self.map.menu_tree();
+        if tree.depth() > MAX_DEPTH {
+            return Err(JsError::new(&depth_error()));
+        }
+
+        Ok(serde_wasm_bindgen::to_value(&JsMenuEntry::from(
+            tree.root(),
+        ))?)
+    }
+
+    /// Calculates the delta-v required to get from the start to the end. Returns `undefined` if
+    /// there is no path between the nodes and throws if either node doesn't exist. The result is
+    /// a JavaScript number, which is exact for any realistic deltav
+    #[wasm_bindgen(js_name = calculateDeltaV)]
+    pub fn calculate_delta_v(&self, start: &str, end: &str) -> Result<Option<f64>, JsError> {
+        let delta_v = self.map.calculate_delta_v(start, end)?;
+
+        Ok(delta_v.map(|delta_v| delta_v as f64))
+    }
+}
+
+impl From<DeltavMap> for WasmMap {
+    fn from(map: DeltavMap) -> Self {
+        WasmMap { map }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsMenuEntry;
+    use crate::tests::get_test_map;
+
+    #[test]
+    fn test_menu_entry() {
+        let map = get_test_map();
+        let json = serde_json::to_value(JsMenuEntry::from(map.menu_tree().root())).unwrap();
+
+        assert_eq!(json["name"], "Category1");
+        assert_eq!(json["children"][1], serde_json::json!({"name": "Node3"}));
+    }
+}
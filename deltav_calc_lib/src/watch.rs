@@ -0,0 +1,122 @@
+use crate::{DeltavMap, LoadError};
+use notify::event::EventKind;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// This error is raised when a map file can't be watched
+#[derive(Debug)]
+pub struct WatchError {
+    error: notify::Error,
+}
+
+impl Display for WatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Couldn't watch the map: {}", self.error)
+    }
+}
+
+impl Error for WatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(error: notify::Error) -> Self {
+        WatchError { error }
+    }
+}
+
+/// Watches a map file for changes. The file stops being watched when this is dropped
+#[derive(Debug)]
+pub struct MapWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl DeltavMap {
+    /// Reloads the map with [`from_path`](DeltavMap::from_path) whenever the file changes and
+    /// passes the result to the callback. The callback is called from another thread and may be
+    /// called multiple times for a single change
+    ///
+    /// # Example
+    /// ```no_run
+    /// use deltav_calc::DeltavMap;
+    /// use std::path::Path;
+    ///
+    /// let watcher = DeltavMap::watch(Path::new("my_map.json"), |map| match map {
+    ///     Ok(map) => println!("Reloaded the map"),
+    ///     Err(e) => println!("{e}"),
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn watch(
+        path: &Path,
+        mut callback: impl FnMut(Result<DeltavMap, LoadError>) + Send + 'static,
+    ) -> Result<MapWatcher, WatchError> {
+        let path = path.to_path_buf();
+        let file_name = path.file_name().map(|name| name.to_os_string());
+
+        // Editors often replace the file instead of writing to it, so the directory is watched
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        };
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+
+            let changed = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == file_name.as_deref());
+            if changed && path.is_file() {
+                callback(DeltavMap::from_path(&path));
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(MapWatcher { _watcher: watcher })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::get_test_map;
+    use crate::DeltavMap;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_watch() {
+        let dir = std::env::temp_dir().join("deltav_calc_test_watch");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("map.json");
+        DeltavMap::new_stock().save(&path).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = DeltavMap::watch(&path, move |map| {
+            let _ = sender.send(map);
+        })
+        .unwrap();
+        get_test_map().save(&path).unwrap();
+
+        // The file may be read while it is still being written, so failed reloads are skipped
+        let map = loop {
+            match receiver.recv_timeout(Duration::from_secs(10)) {
+                Ok(Ok(map)) => break map,
+                Ok(Err(_)) => continue,
+                Err(_) => panic!("The map wasn't reloaded within 10 seconds"),
+            }
+        };
+
+        drop(watcher);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(map, get_test_map());
+    }
+}
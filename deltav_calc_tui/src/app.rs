@@ -0,0 +1,338 @@
+use deltav_calc::{CalcOptions, DeltavFormat, DeltavMap, MenuTreeVisitor};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashSet;
+
+/// A line of the menu tree pane, either a category or a node
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Row {
+    pub name: String,
+    pub depth: usize,
+    pub category: bool,
+}
+
+// Flattens the menu tree into rows while it is walked
+#[derive(Default)]
+struct RowBuilder {
+    rows: Vec<Row>,
+    depth: usize,
+}
+
+impl MenuTreeVisitor for RowBuilder {
+    fn enter_category(&mut self, name: &str) {
+        self.rows.push(Row {
+            name: name.to_string(),
+            depth: self.depth,
+            category: true,
+        });
+        self.depth += 1;
+    }
+
+    fn leave_category(&mut self, _name: &str) {
+        self.depth -= 1;
+    }
+
+    fn visit_node(&mut self, name: &str) {
+        self.rows.push(Row {
+            name: name.to_string(),
+            depth: self.depth,
+            category: false,
+        });
+    }
+}
+
+/// The state of the terminal ui
+pub struct App {
+    map: DeltavMap,
+    format: DeltavFormat,
+    rows: Vec<Row>,
+    // The indices of the rows of the categories that are unfolded
+    expanded: HashSet<usize>,
+    /// The position of the highlighted row among the visible rows
+    pub cursor: usize,
+    /// The text nodes are filtered by, shown instead of the tree while it isn't empty
+    pub query: String,
+    /// If keys are typed into the search instead of being commands
+    pub searching: bool,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub aerobrake: bool,
+    pub quit: bool,
+}
+
+impl App {
+    /// Creates the state for the map with the top level categories unfolded
+    pub fn new(map: DeltavMap) -> App {
+        let mut builder = RowBuilder::default();
+        map.menu_tree().walk(&mut builder);
+        let expanded = builder
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.category && row.depth == 0)
+            .map(|(i, _)| i)
+            .collect();
+
+        App {
+            map,
+            format: DeltavFormat::new(),
+            rows: builder.rows,
+            expanded,
+            cursor: 0,
+            query: String::new(),
+            searching: false,
+            from: None,
+            to: None,
+            aerobrake: false,
+            quit: false,
+        }
+    }
+
+    /// Returns the indices of the rows that are shown, which are the nodes matching the search or
+    /// the rows that aren't in a folded category
+    fn visible(&self) -> Vec<usize> {
+        if !self.query.is_empty() {
+            let query = self.query.to_lowercase();
+            return (0..self.rows.len())
+                .filter(|&i| !self.rows[i].category)
+                .filter(|&i| self.rows[i].name.to_lowercase().contains(&query))
+                .collect();
+        }
+
+        let mut visible = Vec::new();
+        // The depth of the folded category whose rows are skipped
+        let mut folded: Option<usize> = None;
+        for (i, row) in self.rows.iter().enumerate() {
+            if folded.is_some_and(|depth| row.depth > depth) {
+                continue;
+            }
+            folded = (row.category && !self.expanded.contains(&i)).then_some(row.depth);
+            visible.push(i);
+        }
+        visible
+    }
+
+    /// Returns the shown rows, each with whether its category is unfolded
+    pub fn visible_rows(&self) -> Vec<(&Row, bool)> {
+        self.visible()
+            .into_iter()
+            .map(|i| (&self.rows[i], self.expanded.contains(&i)))
+            .collect()
+    }
+
+    // Returns the index of the highlighted row
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.cursor).copied()
+    }
+
+    /// Describes the route between the start and the end, or what is missing to calculate it
+    pub fn route_text(&self) -> String {
+        let (Some(from), Some(to)) = (&self.from, &self.to) else {
+            return String::from("Select the start with f and the end with t");
+        };
+
+        let options = CalcOptions::new().aerobrake(self.aerobrake);
+        match self.map.calculate_route(from, to, &options) {
+            Ok(Some(route)) => route.to_table(&self.format),
+            Ok(None) => String::from("There seems to be no connection between the nodes"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Reacts to a key pressed by the user
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        if self.searching {
+            self.handle_search_key(key.code);
+        } else {
+            self.handle_command_key(key.code);
+        }
+
+        let last = self.visible().len().saturating_sub(1);
+        self.cursor = self.cursor.min(last);
+    }
+
+    // Edits the search, which filters the nodes while it is typed
+    fn handle_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.cursor = 0;
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.cursor = 0;
+            }
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.query.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down => self.cursor += 1,
+            _ => {}
+        }
+    }
+
+    // Runs the command bound to the key
+    fn handle_command_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.query.is_empty() => self.quit = true,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor += 1,
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = usize::MAX,
+            KeyCode::Right | KeyCode::Char('l') => self.fold(false),
+            KeyCode::Left | KeyCode::Char('h') => self.fold(true),
+            KeyCode::Enter | KeyCode::Char(' ') => self.activate(),
+            KeyCode::Char('f') => self.from = self.selected_node(),
+            KeyCode::Char('t') => self.to = self.selected_node(),
+            KeyCode::Char('s') => std::mem::swap(&mut self.from, &mut self.to),
+            KeyCode::Char('a') => self.aerobrake = !self.aerobrake,
+            _ => {}
+        }
+    }
+
+    // Returns the name of the highlighted row if it is a node
+    fn selected_node(&self) -> Option<String> {
+        let row = &self.rows[self.selected()?];
+        (!row.category).then(|| row.name.clone())
+    }
+
+    // Folds or unfolds the highlighted category. Folding a row that can't be folded moves to the
+    // category it is in
+    fn fold(&mut self, fold: bool) {
+        let Some(index) = self.selected() else {
+            return;
+        };
+        let row = &self.rows[index];
+
+        if row.category && self.expanded.contains(&index) == fold {
+            if fold {
+                self.expanded.remove(&index);
+            } else {
+                self.expanded.insert(index);
+            }
+        } else if fold {
+            let parent = self.rows[..index]
+                .iter()
+                .rposition(|other| other.depth + 1 == row.depth);
+            if let Some(position) = parent.and_then(|parent| self.position(parent)) {
+                self.cursor = position;
+            }
+        }
+    }
+
+    // Returns where the row is among the visible rows
+    fn position(&self, index: usize) -> Option<usize> {
+        self.visible().iter().position(|&i| i == index)
+    }
+
+    // Toggles the highlighted category or picks the highlighted node as the start, or as the
+    // end once the start is set
+    fn activate(&mut self) {
+        let Some(index) = self.selected() else {
+            return;
+        };
+
+        if self.rows[index].category {
+            if !self.expanded.remove(&index) {
+                self.expanded.insert(index);
+            }
+        } else if self.from.is_none() {
+            self.from = self.selected_node();
+        } else {
+            self.to = self.selected_node();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use deltav_calc::DeltavMap;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+    fn test_app() -> App {
+        let map = DeltavMap::from_dsl(
+            "
+            [System/Planet]
+            Surface
+            Orbit
+            Surface -> Orbit : 3000
+
+            [System/Planet/Moon]
+            Moon Surface
+            Orbit -> Moon Surface : 800
+            ",
+        )
+        .unwrap();
+        App::new(map)
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.visible_rows()
+            .into_iter()
+            .map(|(row, _)| row.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut app = test_app();
+        assert_eq!(names(&app), ["System", "Planet"]);
+
+        press(&mut app, "jl");
+        assert_eq!(
+            names(&app),
+            ["System", "Planet", "Surface", "Orbit", "Moon"]
+        );
+
+        press(&mut app, "jjjjj");
+        assert_eq!(app.cursor, 4);
+        press(&mut app, "h");
+        assert_eq!(app.cursor, 1);
+        press(&mut app, "h");
+        assert_eq!(names(&app), ["System", "Planet"]);
+    }
+
+    #[test]
+    fn test_route() {
+        let mut app = test_app();
+        assert_eq!(
+            app.route_text(),
+            "Select the start with f and the end with t"
+        );
+
+        press(&mut app, "/surf");
+        assert!(app.searching);
+        assert_eq!(names(&app), ["Surface", "Moon Surface"]);
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        press(&mut app, "fjt");
+        assert_eq!(app.from.as_deref(), Some("Surface"));
+        assert_eq!(app.to.as_deref(), Some("Moon Surface"));
+        assert!(app.route_text().contains("3,800 m/s"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.query.is_empty());
+        assert!(!app.quit);
+        press(&mut app, "q");
+        assert!(app.quit);
+    }
+}
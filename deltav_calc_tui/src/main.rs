@@ -0,0 +1,49 @@
+use app::App;
+use clap::Parser;
+use deltav_calc::DeltavMap;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+use std::error::Error;
+use std::path::PathBuf;
+
+mod app;
+mod ui;
+
+// Calculates the deltav requirements of Kerbal Space Program in the terminal
+#[derive(Parser, Debug)]
+#[command(name = "deltav-tui", version, about)]
+struct Args {
+    /// Reads the map from the file instead of using the stock map
+    #[arg(long)]
+    map: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let map = match &args.map {
+        None => DeltavMap::new_stock(),
+        Some(path) => DeltavMap::from_path(path)
+            .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, App::new(map));
+    ratatui::restore();
+
+    result
+}
+
+// Draws the ui and handles keys until the user quits
+fn run(terminal: &mut DefaultTerminal, mut app: App) -> Result<(), Box<dyn Error>> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+
+    Ok(())
+}
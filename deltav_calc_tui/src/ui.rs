@@ -0,0 +1,98 @@
+use crate::app::App;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph};
+use ratatui::Frame;
+
+// The keys shown at the bottom while no search is typed
+const HELP: &str = "↑↓ move  ←→ fold  f start  t end  s swap  a aerobrake  / search  q quit";
+
+/// Draws the menu tree on the left, the route on the right and a status line below them
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [tree, route] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+
+    let items = app.visible_rows().into_iter().map(|(row, expanded)| {
+        let marker = match (row.category, expanded) {
+            (false, _) if app.from.as_ref() == Some(&row.name) => "S ",
+            (false, _) if app.to.as_ref() == Some(&row.name) => "E ",
+            (false, _) => "  ",
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+        };
+        let indent = if app.query.is_empty() { row.depth } else { 0 };
+        format!("{}{marker}{}", "  ".repeat(indent), row.name)
+    });
+    let title = if app.query.is_empty() && !app.searching {
+        String::from(" Nodes ")
+    } else {
+        format!(" Search: {} ", app.query)
+    };
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.cursor));
+    frame.render_stateful_widget(list, tree, &mut state);
+
+    let on_off = if app.aerobrake { "on" } else { "off" };
+    let mut lines = vec![
+        Line::from(format!("Start: {}", app.from.as_deref().unwrap_or("-"))),
+        Line::from(format!("End: {}", app.to.as_deref().unwrap_or("-"))),
+        Line::from(format!("Aerobraking: {on_off}")),
+        Line::default(),
+    ];
+    lines.extend(
+        app.route_text()
+            .lines()
+            .map(|line| Line::from(line.to_string())),
+    );
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Route ")),
+        route,
+    );
+
+    let status_text = if app.searching {
+        format!("/{}", app.query)
+    } else {
+        String::from(HELP)
+    };
+    frame.render_widget(Paragraph::new(status_text), status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draw;
+    use crate::app::App;
+    use deltav_calc::DeltavMap;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_draw() {
+        let map = DeltavMap::from_dsl("[Test]\nNode1\nNode2\nNode1 -> Node2 : 900").unwrap();
+        let mut app = App::new(map);
+        for c in "jfjt".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("▾ Test"));
+        assert!(screen.contains("S Node1"));
+        assert!(screen.contains("Start: Node1"));
+        assert!(screen.contains("Node1 -> Node2"));
+        assert!(screen.contains("f start"));
+    }
+}